@@ -0,0 +1,437 @@
+//! # AggregateThresholdChip: Sum / Mean of Private Values vs. a Threshold
+//!
+//! Proves statements such as "total income over 12 months >= X" or
+//! "average balance >= Y" over a vector of private values:
+//!
+//! 1. Each value is range-checked to `value_bits` with `LimbRangeCheckChip`.
+//! 2. The first `count` of `max_count` slots are summed with a running sum.
+//!    Slots are marked by boolean `active` bits that must form a prefix, and the
+//!    number of active slots must equal the `count` cell (e.g. a public input).
+//!    Unused slots are padded with zero.
+//! 3. The sum is range-checked to `value_bits + ceil(log2(max_count))` bits, so it
+//!    can never wrap around the field modulus.
+//! 4. The sum is compared to `threshold` (`Aggregate::Sum`), or to `threshold * count`
+//!    (`Aggregate::Mean`, i.e. `sum / count` compared to `threshold` without division),
+//!    with the same `Comparison` variants and `flag` semantics as `ComparisonChip`.
+//!    The (non-negative) difference is range-checked to the sum width, so the threshold
+//!    side of the comparison must also fit in that width.
+//!
+//! ```rust
+//! // In configure()
+//! let columns = AggregateColumns {
+//!     advice, aggregate: Aggregate::Mean, comparison: Comparison::GreaterEqual, value_bits: 32, max_count: 12,
+//! };
+//! let config = AggregateThresholdChip::configure(meta, columns);
+//!
+//! // In synthesize()
+//! let chip = AggregateThresholdChip::construct(config);
+//! let mut inputs = vec![count, threshold, flag];
+//! inputs.extend(balances);
+//! let flag = chip.assign(layouter.namespace(|| "average balance"), &inputs)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+use halo2_gadgets::utilities::bool_check;
+
+use super::gadget::Gadget;
+use super::limb_range_check::{LimbRangeCheckChip, LimbRangeCheckColumns, LimbRangeCheckConfig};
+use super::range_check::Comparison;
+use crate::constants::{MAX_DIVISION_BITS, RANGE_CHECK_LIMB_BITS};
+use crate::utils::fe_to_u128;
+
+/// What the threshold is compared against.
+#[derive(Clone, Copy, Debug)]
+pub enum Aggregate {
+    Sum,
+    Mean,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AggregateColumns {
+    pub advice: [Column<Advice>; 5],
+    pub aggregate: Aggregate,
+    pub comparison: Comparison,
+    pub value_bits: usize,
+    pub max_count: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct AggregateConfig {
+    /// `[value, active, sum, count, diff]` in the aggregation rows,
+    /// `[sum, count, threshold, flag, diff]` in the comparison row.
+    pub advice: [Column<Advice>; 5],
+    pub aggregate: Aggregate,
+    pub comparison: Comparison,
+    pub value_bits: usize,
+    pub sum_bits: usize,
+    pub max_count: usize,
+    pub s_first: Selector,
+    pub s_step: Selector,
+    pub s_compare: Selector,
+    pub range_check_config: LimbRangeCheckConfig,
+}
+
+pub struct AggregateThresholdChip<F: PrimeField> {
+    pub config: AggregateConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> AggregateThresholdChip<F> {
+    /// Sum the first `count` of `values` (at most `max_count`) and return the sum cell.
+    pub fn sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>],
+        count: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        if values.len() > config.max_count {
+            return Err(Error::Synthesis);
+        }
+        let [value_column, active_column, sum_column, count_column, _] = config.advice;
+
+        let count_value = count.value().map(|count| fe_to_u128(count).unwrap_or(u128::MAX));
+
+        let (sum, slots) = layouter.assign_region(
+            || "aggregate sum",
+            |mut region| {
+                let mut sum = Value::known(F::ZERO);
+                let mut running_count = Value::known(F::ZERO);
+                let mut sum_cell = None;
+                let mut slots = Vec::with_capacity(config.max_count);
+
+                for i in 0..config.max_count {
+                    if i == 0 {
+                        config.s_first.enable(&mut region, i)?;
+                    } else {
+                        config.s_step.enable(&mut region, i)?;
+                    }
+
+                    let value = match values.get(i) {
+                        Some(value) => value.copy_advice(|| format!("value[{}]", i), &mut region, value_column, i)?,
+                        None => region.assign_advice(|| "padding", value_column, i, || Value::known(F::ZERO))?,
+                    };
+                    slots.push(value.clone());
+                    let active = count_value.map(|count| if (i as u128) < count { F::ONE } else { F::ZERO });
+                    region.assign_advice(|| format!("active[{}]", i), active_column, i, || active)?;
+
+                    sum = sum + active.zip(value.value().copied()).map(|(active, value)| active * value);
+                    running_count = running_count + active;
+                    sum_cell = Some(region.assign_advice(|| format!("sum[{}]", i), sum_column, i, || sum)?);
+
+                    if i + 1 == config.max_count {
+                        count.copy_advice(|| "count", &mut region, count_column, i)?;
+                    } else {
+                        region.assign_advice(|| format!("count[{}]", i), count_column, i, || running_count)?;
+                    }
+                }
+
+                Ok((sum_cell.ok_or(Error::Synthesis)?, slots))
+            },
+        )?;
+
+        // Padding slots are checked too, so a larger `count` cannot pull in unchecked values.
+        let range_chip = LimbRangeCheckChip::construct(config.range_check_config.clone());
+        for (i, slot) in slots.iter().enumerate() {
+            range_chip.check(layouter.namespace(|| format!("value[{}] range", i)), slot, config.value_bits)?;
+        }
+        range_chip.check(layouter.namespace(|| "sum range"), &sum, config.sum_bits)
+    }
+
+    /// Compare `sum` (or `sum / count`) to `threshold` when `flag == 1`. Returns the copied flag.
+    pub fn compare(
+        &self,
+        mut layouter: impl Layouter<F>,
+        sum: &AssignedCell<F, F>,
+        count: &AssignedCell<F, F>,
+        threshold: &AssignedCell<F, F>,
+        flag: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let [sum_column, count_column, threshold_column, flag_column, diff_column] = config.advice;
+
+        let (flag, diff) = layouter.assign_region(
+            || "aggregate comparison",
+            |mut region| {
+                config.s_compare.enable(&mut region, 0)?;
+                let sum = sum.copy_advice(|| "sum", &mut region, sum_column, 0)?;
+                let count = count.copy_advice(|| "count", &mut region, count_column, 0)?;
+                let threshold = threshold.copy_advice(|| "threshold", &mut region, threshold_column, 0)?;
+                let flag = flag.copy_advice(|| "flag", &mut region, flag_column, 0)?;
+
+                let target = match config.aggregate {
+                    Aggregate::Sum => threshold.value().copied(),
+                    Aggregate::Mean => threshold.value().zip(count.value()).map(|(t, c)| *t * c),
+                };
+                let diff = sum
+                    .value()
+                    .copied()
+                    .zip(target)
+                    .zip(flag.value().copied())
+                    .map(|((sum, target), flag)| flag * comparison_difference(config.comparison, sum, target, F::ONE));
+                let diff = region.assign_advice(|| "diff", diff_column, 0, || diff)?;
+                Ok((flag, diff))
+            },
+        )?;
+
+        let range_chip = LimbRangeCheckChip::construct(config.range_check_config.clone());
+        range_chip.check(layouter.namespace(|| "comparison range"), &diff, config.sum_bits)?;
+
+        Ok(flag)
+    }
+}
+
+/// Quantity that must be non-negative (zero for `Equal`) for `lhs <op> rhs` to hold.
+fn comparison_difference<T>(comparison: Comparison, lhs: T, rhs: T, one: T) -> T
+where
+    T: std::ops::Sub<Output = T>,
+{
+    match comparison {
+        Comparison::Equal | Comparison::GreaterEqual => lhs - rhs,
+        Comparison::GreaterThan => lhs - rhs - one,
+        Comparison::LessEqual => rhs - lhs,
+        Comparison::LessThan => rhs - lhs - one,
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for AggregateThresholdChip<F> {
+    type Config = AggregateConfig;
+    type Columns = AggregateColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: AggregateConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: AggregateColumns) -> AggregateConfig {
+        let AggregateColumns { advice, aggregate, comparison, value_bits, max_count } = columns;
+        assert!(max_count > 0, "max_count must be positive");
+
+        // sum < max_count * 2^value_bits, rounded up to whole limbs
+        let count_bits = (usize::BITS - (max_count - 1).leading_zeros()) as usize;
+        let sum_bits = (value_bits + count_bits).div_ceil(RANGE_CHECK_LIMB_BITS) * RANGE_CHECK_LIMB_BITS;
+        assert!(sum_bits <= MAX_DIVISION_BITS, "value_bits too large for an overflow-safe sum");
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let [value, active, sum, count, diff] = advice;
+
+        let range_check_config = LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z: diff, num_bits: value_bits });
+        let s_first = meta.selector();
+        let s_step = meta.selector();
+        let s_compare = meta.selector();
+
+        meta.create_gate("aggregate sum start", |meta| {
+            let s_first = meta.query_selector(s_first);
+            let value = meta.query_advice(value, Rotation::cur());
+            let active = meta.query_advice(active, Rotation::cur());
+            let sum = meta.query_advice(sum, Rotation::cur());
+            let count = meta.query_advice(count, Rotation::cur());
+
+            vec![
+                s_first.clone() * bool_check(active.clone()),
+                s_first.clone() * (sum - active.clone() * value),
+                s_first * (count - active),
+            ]
+        });
+
+        meta.create_gate("aggregate sum step", |meta| {
+            let s_step = meta.query_selector(s_step);
+            let value = meta.query_advice(value, Rotation::cur());
+            let active_cur = meta.query_advice(active, Rotation::cur());
+            let active_prev = meta.query_advice(active, Rotation::prev());
+            let sum_cur = meta.query_advice(sum, Rotation::cur());
+            let sum_prev = meta.query_advice(sum, Rotation::prev());
+            let count_cur = meta.query_advice(count, Rotation::cur());
+            let count_prev = meta.query_advice(count, Rotation::prev());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                s_step.clone() * bool_check(active_cur.clone()),
+                // active slots form a prefix
+                s_step.clone() * active_cur.clone() * (one - active_prev),
+                s_step.clone() * (sum_cur - sum_prev - active_cur.clone() * value),
+                s_step * (count_cur - count_prev - active_cur),
+            ]
+        });
+
+        // Comparison row: [sum, count, threshold, flag, diff]
+        meta.create_gate("aggregate comparison", |meta| {
+            let s_compare = meta.query_selector(s_compare);
+            let sum = meta.query_advice(advice[0], Rotation::cur());
+            let count = meta.query_advice(advice[1], Rotation::cur());
+            let threshold = meta.query_advice(advice[2], Rotation::cur());
+            let flag = meta.query_advice(advice[3], Rotation::cur());
+            let diff = meta.query_advice(advice[4], Rotation::cur());
+
+            let target = match aggregate {
+                Aggregate::Sum => threshold,
+                Aggregate::Mean => threshold * count,
+            };
+            let difference = comparison_difference(comparison, sum, target, Expression::Constant(F::ONE));
+
+            let mut constraints = vec![
+                s_compare.clone() * bool_check(flag.clone()),
+                s_compare.clone() * (diff.clone() - flag * difference),
+            ];
+            if let Comparison::Equal = comparison {
+                constraints.push(s_compare * diff);
+            }
+            constraints
+        });
+
+        AggregateConfig {
+            advice,
+            aggregate,
+            comparison,
+            value_bits,
+            sum_bits,
+            max_count,
+            s_first,
+            s_step,
+            s_compare,
+            range_check_config,
+        }
+    }
+
+    /// `inputs` is `[count, threshold, flag, value_0, ..., value_{n-1}]` with `n <= max_count`.
+    /// Returns the copied flag.
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [count, threshold, flag, values @ ..] = inputs else {
+            return Err(Error::Synthesis);
+        };
+
+        let sum = self.sum(layouter.namespace(|| "sum"), values, count)?;
+        self.compare(layouter.namespace(|| "compare"), &sum, count, threshold, flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::{load_instance, load_private};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+    use halo2_proofs::arithmetic::Field;
+
+    const MAX_COUNT: usize = 12;
+
+    /// Public inputs: `[threshold, count]`.
+    #[derive(Default)]
+    struct AggregateCircuit<const MEAN: bool> {
+        values: Vec<Value<Fp>>,
+        flag: Value<Fp>,
+    }
+
+    impl<const MEAN: bool> Circuit<Fp> for AggregateCircuit<MEAN> {
+        type Config = (AggregateConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: vec![Value::unknown(); self.values.len()],
+                flag: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let columns = AggregateColumns {
+                advice: [(); 5].map(|_| meta.advice_column()),
+                aggregate: if MEAN { Aggregate::Mean } else { Aggregate::Sum },
+                comparison: Comparison::GreaterEqual,
+                value_bits: 32,
+                max_count: MAX_COUNT,
+            };
+            (AggregateThresholdChip::configure(meta, columns), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let [value_column, _, threshold_column, flag_column, count_column] = config.advice;
+            let threshold = load_instance(layouter.namespace(|| "threshold"), instance, 0, threshold_column)?;
+            let count = load_instance(layouter.namespace(|| "count"), instance, 1, count_column)?;
+            let flag = load_private(layouter.namespace(|| "flag"), flag_column, self.flag)?;
+
+            let mut inputs = vec![count, threshold, flag];
+            for (i, value) in self.values.iter().enumerate() {
+                inputs.push(load_private(layouter.namespace(|| format!("value[{}]", i)), value_column, *value)?);
+            }
+
+            let chip = AggregateThresholdChip::construct(config);
+            chip.assign(layouter.namespace(|| "aggregate threshold"), &inputs)?;
+            Ok(())
+        }
+    }
+
+    fn run<const MEAN: bool>(values: &[u64], threshold: u64, count: u64) -> MockProver<Fp> {
+        let circuit = AggregateCircuit::<MEAN> {
+            values: values.iter().map(|v| Value::known(Fp::from(*v))).collect(),
+            flag: Value::known(Fp::ONE),
+        };
+        MockProver::run(9, &circuit, vec![vec![Fp::from(threshold), Fp::from(count)]]).unwrap()
+    }
+
+    #[test]
+    fn test_total_income_over_year_pass() {
+        let incomes = [3000, 3100, 2900, 3050, 3000, 2950, 3200, 3000, 3000, 3100, 2800, 3400];
+        // Total is 36_500
+        run::<false>(&incomes, 36_500, 12).assert_satisfied();
+        run::<false>(&incomes, 30_000, 12).assert_satisfied();
+    }
+
+    #[test]
+    fn test_total_income_over_year_fail() {
+        let incomes = [3000, 3100, 2900, 3050, 3000, 2950, 3200, 3000, 3000, 3100, 2800, 3400];
+        assert!(run::<false>(&incomes, 36_501, 12).verify().is_err());
+        // Dropping months from the count lowers the sum below the threshold.
+        assert!(run::<false>(&incomes, 36_500, 11).verify().is_err());
+    }
+
+    #[test]
+    fn test_average_balance_with_padding() {
+        // 5 of 12 slots used, mean = 2_000
+        let balances = [1000, 3000, 2000, 2500, 1500];
+        run::<true>(&balances, 2_000, 5).assert_satisfied();
+        assert!(run::<true>(&balances, 2_001, 5).verify().is_err());
+    }
+
+    #[test]
+    fn test_count_beyond_max_fail() {
+        let balances = [1000, 3000, 2000, 2500, 1500];
+        assert!(run::<true>(&balances, 0, MAX_COUNT as u64 + 1).verify().is_err());
+    }
+
+    #[test]
+    fn test_value_out_of_range_fail() {
+        let balances = [1000, 1 << 32, 2000];
+        assert!(run::<false>(&balances, 0, 3).verify().is_err());
+    }
+
+    #[test]
+    fn test_flag_off_pass() {
+        let circuit = AggregateCircuit::<false> {
+            values: vec![Value::known(Fp::from(10)); 3],
+            flag: Value::known(Fp::ZERO),
+        };
+        let prover = MockProver::run(9, &circuit, vec![vec![Fp::from(1_000), Fp::from(3)]]).unwrap();
+        prover.assert_satisfied();
+    }
+}
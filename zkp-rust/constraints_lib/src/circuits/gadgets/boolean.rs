@@ -0,0 +1,395 @@
+//! # BooleanChip: Bit Constraints and Boolean Logic for Halo2
+//!
+//! All operations take and return `AssignedCell`s, so results can be wired into other
+//! gadgets (e.g. the `flag` input of `ComparisonChip`) with copy constraints.
+//! Every operand is constrained to be a bit inside the same gate that uses it.
+//!
+//! ## Supported Operations
+//! - `assert_bit(a)`: a ∈ {0, 1}
+//! - `and(a, b)` = a·b
+//! - `or(a, b)` = a + b − a·b
+//! - `not(a)` = 1 − a
+//! - `xor(a, b)` = a + b − 2·a·b
+//! - `select(c, a, b)` = c ? a : b (only `c` has to be a bit)
+//!
+//! ```rust
+//! // In configure()
+//! let config = BooleanChip::configure(meta, [a, b, c, out]);
+//!
+//! // In synthesize()
+//! let chip = BooleanChip::construct(config);
+//! let both = chip.and(layouter.namespace(|| "age and nationality"), &age_flag, &nationality_flag)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+use halo2_gadgets::utilities::{bool_check, ternary};
+
+use super::gadget::Gadget;
+
+#[derive(Clone, Debug)]
+pub struct BooleanConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub c: Column<Advice>,
+    pub out: Column<Advice>,
+
+    pub s_bit: Selector,
+    pub s_and: Selector,
+    pub s_or: Selector,
+    pub s_not: Selector,
+    pub s_xor: Selector,
+    pub s_select: Selector,
+}
+
+pub struct BooleanChip<F: PrimeField> {
+    pub config: BooleanConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> BooleanChip<F> {
+    /// Constrain `a` to be 0 or 1 and return a copy of it.
+    pub fn assert_bit(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "assert bit",
+            |mut region| {
+                self.config.s_bit.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.a, 0)
+            },
+        )
+    }
+
+    pub fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary_op(layouter, "and", self.config.s_and, a, b, |a, b| a * b)
+    }
+
+    pub fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary_op(layouter, "or", self.config.s_or, a, b, |a, b| a + b - a * b)
+    }
+
+    pub fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.binary_op(layouter, "xor", self.config.s_xor, a, b, |a, b| a + b - a * b.double())
+    }
+
+    pub fn not(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "not",
+            |mut region| {
+                self.config.s_not.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                let out = a.value().map(|a| F::ONE - a);
+                region.assign_advice(|| "not a", self.config.out, 0, || out)
+            },
+        )
+    }
+
+    /// `cond ? when_true : when_false`. Only `cond` is constrained to be a bit.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        when_true: &AssignedCell<F, F>,
+        when_false: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                self.config.s_select.enable(&mut region, 0)?;
+                let a = when_true.copy_advice(|| "when true", &mut region, self.config.a, 0)?;
+                let b = when_false.copy_advice(|| "when false", &mut region, self.config.b, 0)?;
+                let c = cond.copy_advice(|| "cond", &mut region, self.config.c, 0)?;
+
+                let out = c
+                    .value()
+                    .zip(a.value().zip(b.value()))
+                    .map(|(c, (a, b))| if *c == F::ONE { *a } else { *b });
+                region.assign_advice(|| "selected", self.config.out, 0, || out)
+            },
+        )
+    }
+
+    fn binary_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        op: impl Fn(F, F) -> F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, self.config.b, 0)?;
+                let out = a.value().zip(b.value()).map(|(a, b)| op(*a, *b));
+                region.assign_advice(|| format!("{} out", name), self.config.out, 0, || out)
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for BooleanChip<F> {
+    type Config = BooleanConfig;
+    /// `[a, b, c, out]`
+    type Columns = [Column<Advice>; 4];
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: BooleanConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: [Column<Advice>; 4]) -> BooleanConfig {
+        let [a, b, c, out] = columns;
+        for column in columns {
+            meta.enable_equality(column);
+        }
+
+        let s_bit = meta.selector();
+        let s_and = meta.selector();
+        let s_or = meta.selector();
+        let s_not = meta.selector();
+        let s_xor = meta.selector();
+        let s_select = meta.selector();
+
+        meta.create_gate("bool bit", |meta| {
+            let s_bit = meta.query_selector(s_bit);
+            let a = meta.query_advice(a, Rotation::cur());
+
+            vec![s_bit * bool_check(a)]
+        });
+
+        meta.create_gate("bool and", |meta| {
+            let s_and = meta.query_selector(s_and);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            vec![
+                s_and.clone() * bool_check(a.clone()),
+                s_and.clone() * bool_check(b.clone()),
+                s_and * (out - a * b),
+            ]
+        });
+
+        meta.create_gate("bool or", |meta| {
+            let s_or = meta.query_selector(s_or);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            vec![
+                s_or.clone() * bool_check(a.clone()),
+                s_or.clone() * bool_check(b.clone()),
+                s_or * (out - (a.clone() + b.clone() - a * b)),
+            ]
+        });
+
+        meta.create_gate("bool not", |meta| {
+            let s_not = meta.query_selector(s_not);
+            let a = meta.query_advice(a, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                s_not.clone() * bool_check(a.clone()),
+                s_not * (out - (one - a)),
+            ]
+        });
+
+        meta.create_gate("bool xor", |meta| {
+            let s_xor = meta.query_selector(s_xor);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let two = Expression::Constant(F::from(2));
+
+            vec![
+                s_xor.clone() * bool_check(a.clone()),
+                s_xor.clone() * bool_check(b.clone()),
+                s_xor * (out - (a.clone() + b.clone() - two * a * b)),
+            ]
+        });
+
+        // Only the condition has to be a bit; the selected values are arbitrary.
+        meta.create_gate("bool select", |meta| {
+            let s_select = meta.query_selector(s_select);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            vec![
+                s_select.clone() * bool_check(c.clone()),
+                s_select * (out - ternary(c, a, b)),
+            ]
+        });
+
+        BooleanConfig {
+            a,
+            b,
+            c,
+            out,
+            s_bit,
+            s_and,
+            s_or,
+            s_not,
+            s_xor,
+            s_select,
+        }
+    }
+
+    /// `inputs` is `[a]`; constrains it to be a bit and returns the copied cell.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [a] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        self.assert_bit(layouter, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    #[derive(Clone, Copy, Debug)]
+    enum Op {
+        And,
+        Or,
+        Xor,
+        Not,
+        Select,
+    }
+
+    struct BooleanCircuit {
+        op: Op,
+        a: Value<Fp>,
+        b: Value<Fp>,
+        c: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for BooleanCircuit {
+        type Config = (BooleanConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                op: self.op,
+                a: Value::unknown(),
+                b: Value::unknown(),
+                c: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let columns = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            (BooleanChip::configure(meta, columns), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let a = load_private(layouter.namespace(|| "a"), config.a, self.a)?;
+            let b = load_private(layouter.namespace(|| "b"), config.b, self.b)?;
+            let c = load_private(layouter.namespace(|| "c"), config.c, self.c)?;
+
+            let chip = BooleanChip::construct(config);
+            let out = match self.op {
+                Op::And => chip.and(layouter.namespace(|| "and"), &a, &b)?,
+                Op::Or => chip.or(layouter.namespace(|| "or"), &a, &b)?,
+                Op::Xor => chip.xor(layouter.namespace(|| "xor"), &a, &b)?,
+                Op::Not => chip.not(layouter.namespace(|| "not"), &a)?,
+                Op::Select => chip.select(layouter.namespace(|| "select"), &c, &a, &b)?,
+            };
+            layouter.constrain_instance(out.cell(), instance, 0)
+        }
+    }
+
+    fn run(op: Op, a: u64, b: u64, c: u64, expected: u64) -> MockProver<Fp> {
+        let circuit = BooleanCircuit {
+            op,
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+            c: Value::known(Fp::from(c)),
+        };
+        MockProver::run(5, &circuit, vec![vec![Fp::from(expected)]]).unwrap()
+    }
+
+    #[test]
+    fn test_truth_tables() {
+        for a in 0..2u64 {
+            for b in 0..2u64 {
+                run(Op::And, a, b, 0, a & b).assert_satisfied();
+                run(Op::Or, a, b, 0, a | b).assert_satisfied();
+                run(Op::Xor, a, b, 0, a ^ b).assert_satisfied();
+            }
+            run(Op::Not, a, 0, 0, 1 - a).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        run(Op::Select, 42, 7, 1, 42).assert_satisfied();
+        run(Op::Select, 42, 7, 0, 7).assert_satisfied();
+    }
+
+    #[test]
+    fn test_non_bit_operand_fail() {
+        // 2 * 1 = 2 would satisfy the product constraint alone.
+        assert!(run(Op::And, 2, 1, 0, 2).verify().is_err());
+        assert!(run(Op::Not, 2, 0, 0, 0).verify().is_err());
+    }
+
+    #[test]
+    fn test_non_bit_condition_fail() {
+        // c = 2 is not a valid condition.
+        assert!(run(Op::Select, 5, 3, 2, 7).verify().is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! # ByteStringChip: Range-Checked Byte Strings for Halo2
+//!
+//! Identity attributes such as names, e-mail addresses and document numbers are byte
+//! strings. This chip provides the building blocks to work with them in-circuit:
+//!
+//! - `assign_bytes`: witness bytes, each range-checked to 8 bits with `LimbRangeCheckChip`.
+//! - `pack`: pack bytes big-endian into field elements, `BYTES_PER_FIELD` bytes per element.
+//! - `is_equal`: boolean cell that is 1 iff two strings of the same (padded) length are equal,
+//!   combined chunk by chunk with `BooleanChip::and`.
+//!
+//! Strings shorter than their slot are padded with trailing zero bytes.
+//! Suffix matching lives in `SuffixMatchChip`, which consumes bytes from `assign_bytes`.
+//!
+//! ```rust
+//! // In configure()
+//! let config = ByteStringChip::configure(meta, [a, b, c, out]);
+//!
+//! // In synthesize()
+//! let chip = ByteStringChip::construct(config);
+//! let name = chip.assign_bytes(layouter.namespace(|| "name"), &name_bytes)?;
+//! let same = chip.is_equal(layouter.namespace(|| "name == registered"), &name, &registered)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+
+use super::boolean::{BooleanChip, BooleanConfig};
+use super::gadget::Gadget;
+use super::limb_range_check::{LimbRangeCheckChip, LimbRangeCheckColumns, LimbRangeCheckConfig};
+use crate::constants::BYTES_PER_FIELD;
+
+#[derive(Clone, Debug)]
+pub struct ByteStringConfig {
+    /// `[byte, acc, inv, out]`
+    pub advice: [Column<Advice>; 4],
+    pub s_pack_first: Selector,
+    pub s_pack_step: Selector,
+    pub s_is_equal: Selector,
+    pub range_check_config: LimbRangeCheckConfig,
+    pub boolean_config: BooleanConfig,
+}
+
+pub struct ByteStringChip<F: PrimeField> {
+    pub config: ByteStringConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> ByteStringChip<F> {
+    /// Witness `bytes` and range-check each of them to 8 bits.
+    pub fn assign_bytes(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: &[Value<u8>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let range_chip = LimbRangeCheckChip::construct(self.config.range_check_config.clone());
+
+        bytes
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                let cell = layouter.assign_region(
+                    || format!("byte[{}]", i),
+                    |mut region| {
+                        region.assign_advice(|| "byte", self.config.advice[0], 0, || byte.map(|b| F::from(b as u64)))
+                    },
+                )?;
+                range_chip.check(layouter.namespace(|| format!("byte[{}] range", i)), &cell, 8)
+            })
+            .collect()
+    }
+
+    /// Pack range-checked bytes into field elements, `BYTES_PER_FIELD` bytes per element.
+    pub fn pack(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let [byte_column, acc_column, _, _] = self.config.advice;
+        let shift = F::from(256);
+
+        bytes
+            .chunks(BYTES_PER_FIELD)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                layouter.assign_region(
+                    || format!("pack chunk {}", chunk_index),
+                    |mut region| {
+                        let mut acc = Value::known(F::ZERO);
+                        let mut acc_cell = None;
+                        for (i, byte) in chunk.iter().enumerate() {
+                            if i == 0 {
+                                self.config.s_pack_first.enable(&mut region, i)?;
+                            } else {
+                                self.config.s_pack_step.enable(&mut region, i)?;
+                            }
+                            let byte = byte.copy_advice(|| format!("byte[{}]", i), &mut region, byte_column, i)?;
+                            acc = acc.zip(byte.value()).map(|(acc, byte)| acc * shift + byte);
+                            acc_cell = Some(region.assign_advice(|| format!("acc[{}]", i), acc_column, i, || acc)?);
+                        }
+                        acc_cell.ok_or(Error::Synthesis)
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Returns a cell that is 1 iff `lhs == rhs`, and 0 otherwise.
+    pub fn is_equal_field(
+        &self,
+        mut layouter: impl Layouter<F>,
+        lhs: &AssignedCell<F, F>,
+        rhs: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [lhs_column, rhs_column, inv_column, out_column] = self.config.advice;
+
+        layouter.assign_region(
+            || "is equal",
+            |mut region| {
+                self.config.s_is_equal.enable(&mut region, 0)?;
+                let lhs = lhs.copy_advice(|| "lhs", &mut region, lhs_column, 0)?;
+                let rhs = rhs.copy_advice(|| "rhs", &mut region, rhs_column, 0)?;
+
+                let diff = lhs.value().zip(rhs.value()).map(|(lhs, rhs)| *lhs - rhs);
+                let inv = diff.map(|diff| diff.invert().unwrap_or(F::ZERO));
+                region.assign_advice(|| "inv", inv_column, 0, || inv)?;
+                let out = diff.map(|diff| if diff == F::ZERO { F::ONE } else { F::ZERO });
+                region.assign_advice(|| "is equal", out_column, 0, || out)
+            },
+        )
+    }
+
+    /// Returns a cell that is 1 iff the byte strings `lhs` and `rhs` are equal.
+    /// Both must be padded to the same length.
+    pub fn is_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        lhs: &[AssignedCell<F, F>],
+        rhs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if lhs.is_empty() || lhs.len() != rhs.len() {
+            return Err(Error::Synthesis);
+        }
+        let lhs = self.pack(layouter.namespace(|| "pack lhs"), lhs)?;
+        let rhs = self.pack(layouter.namespace(|| "pack rhs"), rhs)?;
+
+        let boolean_chip = BooleanChip::construct(self.config.boolean_config.clone());
+        let mut result: Option<AssignedCell<F, F>> = None;
+        for (i, (lhs, rhs)) in lhs.iter().zip(rhs.iter()).enumerate() {
+            let equal = self.is_equal_field(layouter.namespace(|| format!("chunk {} equal", i)), lhs, rhs)?;
+            result = Some(match result {
+                None => equal,
+                Some(acc) => boolean_chip.and(layouter.namespace(|| format!("and chunk {}", i)), &acc, &equal)?,
+            });
+        }
+        result.ok_or(Error::Synthesis)
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for ByteStringChip<F> {
+    type Config = ByteStringConfig;
+    /// `[byte, acc, inv, out]`, shared with the inner `BooleanChip`.
+    type Columns = [Column<Advice>; 4];
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: ByteStringConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4]) -> ByteStringConfig {
+        let [byte, acc, inv, out] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let range_check_config = LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z: acc, num_bits: 8 });
+        let boolean_config = BooleanChip::configure(meta, advice);
+        let s_pack_first = meta.selector();
+        let s_pack_step = meta.selector();
+        let s_is_equal = meta.selector();
+
+        meta.create_gate("pack bytes start", |meta| {
+            let s_pack_first = meta.query_selector(s_pack_first);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+
+            vec![s_pack_first * (acc - byte)]
+        });
+
+        meta.create_gate("pack bytes step", |meta| {
+            let s_pack_step = meta.query_selector(s_pack_step);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+
+            vec![s_pack_step * (acc_cur - (acc_prev * Expression::Constant(F::from(256)) + byte))]
+        });
+
+        // out = 1 - (lhs - rhs) * inv, (lhs - rhs) * out = 0
+        meta.create_gate("is equal", |meta| {
+            let s_is_equal = meta.query_selector(s_is_equal);
+            let lhs = meta.query_advice(byte, Rotation::cur());
+            let rhs = meta.query_advice(acc, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let diff = lhs - rhs;
+
+            vec![
+                s_is_equal.clone() * (out.clone() - (Expression::Constant(F::ONE) - diff.clone() * inv)),
+                s_is_equal * diff * out,
+            ]
+        });
+
+        ByteStringConfig {
+            advice,
+            s_pack_first,
+            s_pack_step,
+            s_is_equal,
+            range_check_config,
+            boolean_config,
+        }
+    }
+
+    /// `inputs` is `[lhs_0, ..., lhs_{n-1}, rhs_0, ..., rhs_{n-1}]` (range-checked bytes).
+    /// Returns the string equality bit.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if inputs.len() % 2 != 0 {
+            return Err(Error::Synthesis);
+        }
+        let (lhs, rhs) = inputs.split_at(inputs.len() / 2);
+        self.is_equal(layouter, lhs, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    const STRING_LEN: usize = 40;
+
+    /// Public input: `[lhs == rhs]`.
+    #[derive(Default)]
+    struct EqualityCircuit {
+        lhs: Vec<Value<u8>>,
+        rhs: Vec<Value<u8>>,
+    }
+
+    impl Circuit<Fp> for EqualityCircuit {
+        type Config = (ByteStringConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                lhs: vec![Value::unknown(); STRING_LEN],
+                rhs: vec![Value::unknown(); STRING_LEN],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let advice = [(); 4].map(|_| meta.advice_column());
+            (ByteStringChip::configure(meta, advice), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = ByteStringChip::construct(config);
+            let lhs = chip.assign_bytes(layouter.namespace(|| "lhs"), &self.lhs)?;
+            let rhs = chip.assign_bytes(layouter.namespace(|| "rhs"), &self.rhs)?;
+
+            let mut inputs = lhs;
+            inputs.extend(rhs);
+            let equal = chip.assign(layouter.namespace(|| "strings equal"), &inputs)?;
+            layouter.constrain_instance(equal.cell(), instance, 0)
+        }
+    }
+
+    fn padded(s: &str) -> Vec<Value<u8>> {
+        let mut bytes: Vec<Value<u8>> = s.bytes().map(Value::known).collect();
+        bytes.resize(STRING_LEN, Value::known(0));
+        bytes
+    }
+
+    fn run(lhs: &str, rhs: &str, expected: u64) -> MockProver<Fp> {
+        let circuit = EqualityCircuit { lhs: padded(lhs), rhs: padded(rhs) };
+        MockProver::run(10, &circuit, vec![vec![Fp::from(expected)]]).unwrap()
+    }
+
+    #[test]
+    fn test_equal_strings() {
+        run("Hong Gildong", "Hong Gildong", 1).assert_satisfied();
+        // Spans two chunks
+        let long = "M12345678-passport-number-0987654321";
+        run(long, long, 1).assert_satisfied();
+    }
+
+    #[test]
+    fn test_different_strings() {
+        run("Hong Gildong", "Hong Gilsoon", 0).assert_satisfied();
+        // Differs only in the second chunk
+        run("M12345678-passport-number-0987654321", "M12345678-passport-number-0987654322", 0).assert_satisfied();
+    }
+
+    #[test]
+    fn test_claimed_equal_fail() {
+        assert!(run("Hong Gildong", "Hong Gilsoon", 1).verify().is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! Property-based differential tests: each chip is run through `MockProver` on random
+//! inputs, and the circuit must be satisfied **exactly** when the native Rust predicate holds.
+//!
+//! Inputs are drawn as `i64` and mapped into the field (negative values wrap around to
+//! `p - |x|`), mixing boundary values of each gadget with arbitrary values. To cover a new
+//! gadget, add a small test circuit, a native predicate and a `proptest!` case that calls
+//! `assert_matches_native`.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    pasta::Fp,
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+use proptest::prelude::*;
+
+use super::division::{DivisionChip, DivisionColumns, DivisionConfig};
+use super::gadget::{load_private, Gadget};
+use super::limb_range_check::{LimbRangeCheckChip, LimbRangeCheckColumns, LimbRangeCheckConfig};
+use super::range_check::{Comparison, ComparisonChip, ComparisonColumns, ComparisonConfig};
+use super::set_membership_check::{SetMembershipChip, SetMembershipConfig};
+use crate::constants::MAX_MEMBERSHIP_NUMBER;
+
+const MAX_VALUE: usize = 32;
+const RANGE_BITS: usize = 8;
+
+fn fe(value: i64) -> Fp {
+    if value < 0 { -Fp::from(value.unsigned_abs()) } else { Fp::from(value as u64) }
+}
+
+/// Small values around `bound`, negative values and arbitrary values.
+fn value_around(bound: i64) -> impl Strategy<Value = i64> {
+    prop_oneof![
+        4 => -2..bound + 2,
+        1 => prop::sample::select(vec![0, 1, bound - 1, bound, bound + 1, -1, -bound]),
+        1 => any::<i32>().prop_map(i64::from),
+    ]
+}
+
+/// Values outside `[0, bound)`: boundary values just outside it and arbitrary ones.
+fn value_outside(bound: i64) -> impl Strategy<Value = i64> {
+    prop_oneof![
+        1 => prop::sample::select(vec![-1, -bound, bound, bound + 1, 2 * bound]),
+        1 => any::<i32>()
+            .prop_map(i64::from)
+            .prop_filter("outside the domain", move |value| !(0..bound).contains(value)),
+    ]
+}
+
+/// A flag is usually a bit, but occasionally not.
+fn flag() -> impl Strategy<Value = i64> {
+    prop_oneof![8 => 0..2i64, 1 => Just(2i64), 1 => Just(-1i64)]
+}
+
+fn is_bit(flag: i64) -> bool {
+    flag == 0 || flag == 1
+}
+
+fn assert_matches_native<C: Circuit<Fp>>(k: u32, circuit: &C, instance: Vec<Vec<Fp>>, expected: bool) {
+    let satisfied = MockProver::run(k, circuit, instance).unwrap().verify().is_ok();
+    assert_eq!(satisfied, expected, "circuit satisfied = {}, native predicate = {}", satisfied, expected);
+}
+
+// ---------------------------------------------------------------------------------------
+// ComparisonChip
+// ---------------------------------------------------------------------------------------
+
+const fn comparison(op: u8) -> Comparison {
+    match op {
+        0 => Comparison::Equal,
+        1 => Comparison::GreaterThan,
+        2 => Comparison::GreaterEqual,
+        3 => Comparison::LessThan,
+        _ => Comparison::LessEqual,
+    }
+}
+
+/// Native comparison, for inputs in the chip's domain `0..MAX_VALUE`.
+fn comparison_holds(comparison: Comparison, lhs: i64, rhs: i64) -> bool {
+    match comparison {
+        Comparison::Equal => lhs == rhs,
+        Comparison::GreaterThan => lhs > rhs,
+        Comparison::GreaterEqual => lhs >= rhs,
+        Comparison::LessThan => lhs < rhs,
+        Comparison::LessEqual => lhs <= rhs,
+    }
+}
+
+#[derive(Default)]
+struct ComparisonCircuit<const OP: u8> {
+    lhs: Value<Fp>,
+    rhs: Value<Fp>,
+    flag: Value<Fp>,
+}
+
+impl<const OP: u8> Circuit<Fp> for ComparisonCircuit<OP> {
+    type Config = ComparisonConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let columns = ComparisonColumns {
+            lhs: meta.advice_column(),
+            rhs: meta.advice_column(),
+            flag: meta.advice_column(),
+            comparison: comparison(OP),
+            max_value: MAX_VALUE,
+        };
+        ComparisonChip::configure(meta, columns)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let lhs = load_private(layouter.namespace(|| "lhs"), config.lhs, self.lhs)?;
+        let rhs = load_private(layouter.namespace(|| "rhs"), config.rhs, self.rhs)?;
+        let flag = load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?;
+
+        let chip = ComparisonChip::construct(config);
+        chip.assign(layouter.namespace(|| "comparison"), &[lhs, rhs, flag])?;
+        Ok(())
+    }
+}
+
+fn run_comparison<const OP: u8>(lhs: i64, rhs: i64, flag: i64, expected: bool) {
+    let circuit = ComparisonCircuit::<OP> {
+        lhs: Value::known(fe(lhs)),
+        rhs: Value::known(fe(rhs)),
+        flag: Value::known(fe(flag)),
+    };
+    assert_matches_native(6, &circuit, vec![], expected);
+}
+
+fn check_comparison<const OP: u8>(lhs: i64, rhs: i64, flag: i64) {
+    let expected = is_bit(flag) && (flag == 0 || comparison_holds(comparison(OP), lhs, rhs));
+    run_comparison::<OP>(lhs, rhs, flag, expected);
+}
+
+/// Any operand outside `0..MAX_VALUE` is rejected, whatever the comparison and flag.
+fn check_comparison_out_of_domain(op: u8, lhs: i64, rhs: i64, flag: i64) {
+    match op {
+        0 => run_comparison::<0>(lhs, rhs, flag, false),
+        1 => run_comparison::<1>(lhs, rhs, flag, false),
+        2 => run_comparison::<2>(lhs, rhs, flag, false),
+        3 => run_comparison::<3>(lhs, rhs, flag, false),
+        _ => run_comparison::<4>(lhs, rhs, flag, false),
+    }
+}
+
+// ---------------------------------------------------------------------------------------
+// SetMembershipChip
+// ---------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct SetMembershipCircuit {
+    input: Value<Fp>,
+    flag: Value<Fp>,
+    set: Vec<Value<Fp>>,
+}
+
+impl Circuit<Fp> for SetMembershipCircuit {
+    type Config = SetMembershipConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            set: vec![Value::unknown(); self.set.len()],
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let columns = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        SetMembershipChip::configure(meta, columns)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let mut inputs = vec![
+            load_private(layouter.namespace(|| "input"), config.input_value, self.input)?,
+            load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?,
+        ];
+        for (i, member) in self.set.iter().enumerate() {
+            inputs.push(load_private(layouter.namespace(|| format!("set[{}]", i)), config.set_column, *member)?);
+        }
+
+        let chip = SetMembershipChip::construct(config);
+        chip.assign(layouter.namespace(|| "set membership"), &inputs)?;
+        Ok(())
+    }
+}
+
+fn check_set_membership(input: i64, set: Vec<i64>, flag: i64) {
+    // Missing members are padded with zero, so 0 is a member of any short set.
+    let padded = set.len() < MAX_MEMBERSHIP_NUMBER;
+    let is_member = set.contains(&input) || (padded && input == 0);
+    let expected = is_bit(flag) && (flag == 0 || is_member);
+
+    let circuit = SetMembershipCircuit {
+        input: Value::known(fe(input)),
+        flag: Value::known(fe(flag)),
+        set: set.into_iter().map(|member| Value::known(fe(member))).collect(),
+    };
+    assert_matches_native(6, &circuit, vec![], expected);
+}
+
+// ---------------------------------------------------------------------------------------
+// LimbRangeCheckChip
+// ---------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct LimbRangeCheckCircuit {
+    value: Value<Fp>,
+}
+
+impl Circuit<Fp> for LimbRangeCheckCircuit {
+    type Config = LimbRangeCheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let z = meta.advice_column();
+        LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z, num_bits: RANGE_BITS })
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let value = load_private(layouter.namespace(|| "value"), config.z, self.value)?;
+        let chip = LimbRangeCheckChip::construct(config);
+        chip.assign(layouter.namespace(|| "range check"), &[value])?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------------------
+// DivisionChip
+// ---------------------------------------------------------------------------------------
+
+/// Public inputs: `[q, r]`.
+#[derive(Default)]
+struct DivisionCircuit {
+    a: Value<Fp>,
+    b: Value<Fp>,
+}
+
+impl Circuit<Fp> for DivisionCircuit {
+    type Config = (DivisionConfig, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        let advice = [(); 5].map(|_| meta.advice_column());
+        (DivisionChip::configure(meta, DivisionColumns { advice, num_bits: RANGE_BITS }), instance)
+    }
+
+    fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let a = load_private(layouter.namespace(|| "a"), config.a, self.a)?;
+        let b = load_private(layouter.namespace(|| "b"), config.b, self.b)?;
+
+        let chip = DivisionChip::construct(config);
+        let (q, r) = chip.div_rem(layouter.namespace(|| "div rem"), &a, &b)?;
+        layouter.constrain_instance(q.cell(), instance, 0)?;
+        layouter.constrain_instance(r.cell(), instance, 1)
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn comparison_equal_matches_native(lhs in 0..MAX_VALUE as i64, rhs in 0..MAX_VALUE as i64, flag in flag()) {
+        check_comparison::<0>(lhs, rhs, flag);
+    }
+
+    #[test]
+    fn comparison_greater_than_matches_native(lhs in 0..MAX_VALUE as i64, rhs in 0..MAX_VALUE as i64, flag in flag()) {
+        check_comparison::<1>(lhs, rhs, flag);
+    }
+
+    #[test]
+    fn comparison_greater_equal_matches_native(lhs in 0..MAX_VALUE as i64, rhs in 0..MAX_VALUE as i64, flag in flag()) {
+        check_comparison::<2>(lhs, rhs, flag);
+    }
+
+    #[test]
+    fn comparison_less_than_matches_native(lhs in 0..MAX_VALUE as i64, rhs in 0..MAX_VALUE as i64, flag in flag()) {
+        check_comparison::<3>(lhs, rhs, flag);
+    }
+
+    #[test]
+    fn comparison_less_equal_matches_native(lhs in 0..MAX_VALUE as i64, rhs in 0..MAX_VALUE as i64, flag in flag()) {
+        check_comparison::<4>(lhs, rhs, flag);
+    }
+
+    #[test]
+    fn comparison_out_of_domain_rejected(
+        op in 0..5u8,
+        outside in value_outside(MAX_VALUE as i64),
+        other in value_around(MAX_VALUE as i64),
+        outside_is_lhs in any::<bool>(),
+        flag in flag(),
+    ) {
+        let (lhs, rhs) = if outside_is_lhs { (outside, other) } else { (other, outside) };
+        check_comparison_out_of_domain(op, lhs, rhs, flag);
+    }
+
+    #[test]
+    fn set_membership_matches_native(
+        input in value_around(8),
+        set in prop::collection::vec(value_around(8), 0..=MAX_MEMBERSHIP_NUMBER),
+        flag in flag(),
+    ) {
+        check_set_membership(input, set, flag);
+    }
+
+    #[test]
+    fn limb_range_check_matches_native(value in value_around(1 << RANGE_BITS)) {
+        let circuit = LimbRangeCheckCircuit { value: Value::known(fe(value)) };
+        let expected = (0..1i64 << RANGE_BITS).contains(&value);
+        assert_matches_native(5, &circuit, vec![], expected);
+    }
+
+    #[test]
+    fn division_matches_native(a in value_around(1 << RANGE_BITS), b in value_around(16)) {
+        // The claimed quotient and remainder follow Rust semantics for non-negative operands.
+        let (q, r) = if b > 0 { (a.div_euclid(b), a.rem_euclid(b)) } else { (0, a) };
+        let bound = 0..1i64 << RANGE_BITS;
+        let expected = a >= 0 && b > 0 && bound.contains(&q) && bound.contains(&r) && bound.contains(&(b - r - 1));
+
+        let circuit = DivisionCircuit { a: Value::known(fe(a)), b: Value::known(fe(b)) };
+        assert_matches_native(6, &circuit, vec![vec![fe(q), fe(r)]], expected);
+    }
+}
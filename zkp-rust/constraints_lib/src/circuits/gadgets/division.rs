@@ -0,0 +1,259 @@
+//! # DivisionChip: Integer Division with Remainder for Halo2
+//!
+//! Proves `a = q * b + r` with `0 <= r < b` over integers, i.e. `q = a / b` and `r = a % b`.
+//!
+//! ## Constraints
+//! - gate: `a - (q * b + r) = 0` and `diff - (b - r - 1) = 0`
+//! - `q`, `r` and `diff` are range-checked to `num_bits` with `LimbRangeCheckChip`
+//!
+//! `diff < 2^num_bits` gives `r < b` and bounds `b`, so `q * b + r` cannot wrap
+//! around the field modulus as long as `num_bits <= MAX_DIVISION_BITS`.
+//!
+//! ```rust
+//! // In configure()
+//! let config = DivisionChip::configure(meta, DivisionColumns { advice: [a, b, q, r, diff], num_bits: 64 });
+//!
+//! // In synthesize()
+//! let chip = DivisionChip::construct(config);
+//! let (years, _days_left) = chip.div_rem(layouter.namespace(|| "age in years"), &days, &days_per_year)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+
+use super::gadget::Gadget;
+use super::limb_range_check::{LimbRangeCheckChip, LimbRangeCheckColumns, LimbRangeCheckConfig};
+use crate::constants::MAX_DIVISION_BITS;
+use crate::utils::{fe_div_rem, fe_to_u128};
+
+#[derive(Clone, Copy, Debug)]
+pub struct DivisionColumns {
+    /// `[a, b, q, r, diff]`
+    pub advice: [Column<Advice>; 5],
+    pub num_bits: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct DivisionConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub q: Column<Advice>,
+    pub r: Column<Advice>,
+    pub diff: Column<Advice>,
+    pub num_bits: usize,
+    pub selector: Selector,
+    pub range_check_config: LimbRangeCheckConfig,
+}
+
+pub struct DivisionChip<F: PrimeField> {
+    pub config: DivisionConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> DivisionChip<F> {
+    /// Returns `(a / b, a % b)`.
+    pub fn div_rem(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let config = &self.config;
+
+        let (q, r, diff) = layouter.assign_region(
+            || "division",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.b, 0)?;
+
+                // A zero or out-of-range divisor yields a witness that fails the range checks.
+                // `a` may be wider than 128 bits (e.g. a fixed-point product of two 120-bit values).
+                let quotient_remainder: Value<(F, F)> = a.value().zip(b.value()).map(|(a, b)| {
+                    match fe_to_u128(b).and_then(|b| fe_div_rem(a, b)) {
+                        Some((q, r)) => (q, F::from_u128(r)),
+                        None => (F::ZERO, *a),
+                    }
+                });
+
+                let q = region.assign_advice(|| "q", config.q, 0, || quotient_remainder.map(|(q, _)| q))?;
+                let r = region.assign_advice(|| "r", config.r, 0, || quotient_remainder.map(|(_, r)| r))?;
+                let diff = region.assign_advice(
+                    || "b - r - 1",
+                    config.diff,
+                    0,
+                    || b.value().zip(r.value()).map(|(b, r)| *b - r - F::ONE),
+                )?;
+                Ok((q, r, diff))
+            },
+        )?;
+
+        let range_chip = LimbRangeCheckChip::construct(config.range_check_config.clone());
+        range_chip.check(layouter.namespace(|| "q range"), &q, config.num_bits)?;
+        range_chip.check(layouter.namespace(|| "r range"), &r, config.num_bits)?;
+        range_chip.check(layouter.namespace(|| "r < b"), &diff, config.num_bits)?;
+
+        Ok((q, r))
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for DivisionChip<F> {
+    type Config = DivisionConfig;
+    type Columns = DivisionColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: DivisionConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: DivisionColumns) -> DivisionConfig {
+        let DivisionColumns { advice, num_bits } = columns;
+        assert!(num_bits <= MAX_DIVISION_BITS, "num_bits too large for a sound division");
+        let [a, b, q, r, diff] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        // The range checks live in their own regions, so they can share the `diff` column.
+        let range_check_config = LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z: diff, num_bits });
+        let selector = meta.selector();
+
+        meta.create_gate("division with remainder", |meta| {
+            let sel = meta.query_selector(selector);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let q = meta.query_advice(q, Rotation::cur());
+            let r = meta.query_advice(r, Rotation::cur());
+            let diff = meta.query_advice(diff, Rotation::cur());
+
+            vec![
+                sel.clone() * (a - (q * b.clone() + r.clone())),
+                sel * (diff - (b - r - Expression::Constant(F::ONE))),
+            ]
+        });
+
+        DivisionConfig {
+            a,
+            b,
+            q,
+            r,
+            diff,
+            num_bits,
+            selector,
+            range_check_config,
+        }
+    }
+
+    /// `inputs` is `[a, b]`. Returns the quotient `a / b`.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [a, b] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        let (q, _) = self.div_rem(layouter, a, b)?;
+        Ok(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    #[derive(Default)]
+    struct DivisionCircuit<const NUM_BITS: usize> {
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl<const NUM_BITS: usize> Circuit<Fp> for DivisionCircuit<NUM_BITS> {
+        type Config = (DivisionConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let advice = [(); 5].map(|_| meta.advice_column());
+            (DivisionChip::configure(meta, DivisionColumns { advice, num_bits: NUM_BITS }), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let a = load_private(layouter.namespace(|| "a"), config.a, self.a)?;
+            let b = load_private(layouter.namespace(|| "b"), config.b, self.b)?;
+
+            let chip = DivisionChip::construct(config);
+            let (q, r) = chip.div_rem(layouter.namespace(|| "div rem"), &a, &b)?;
+            layouter.constrain_instance(q.cell(), instance, 0)?;
+            layouter.constrain_instance(r.cell(), instance, 1)
+        }
+    }
+
+    fn run(a: u64, b: u64, q: u64, r: u64) -> MockProver<Fp> {
+        let circuit = DivisionCircuit::<32> {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        };
+        MockProver::run(7, &circuit, vec![vec![Fp::from(q), Fp::from(r)]]).unwrap()
+    }
+
+    fn run_wide(a: Fp, b: Fp, q: Fp, r: Fp) -> MockProver<Fp> {
+        let circuit = DivisionCircuit::<MAX_DIVISION_BITS> {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        MockProver::run(9, &circuit, vec![vec![q, r]]).unwrap()
+    }
+
+    #[test]
+    fn test_age_in_years_from_days() {
+        // 7400 days = 20 years and 100 days
+        run(7400, 365, 20, 100).assert_satisfied();
+    }
+
+    #[test]
+    fn test_division_exact_and_small() {
+        run(7, 7, 1, 0).assert_satisfied();
+        run(3, 10, 0, 3).assert_satisfied();
+    }
+
+    #[test]
+    fn test_division_wrong_quotient_fail() {
+        assert!(run(7400, 365, 19, 465).verify().is_err());
+    }
+
+    #[test]
+    fn test_division_by_zero_fail() {
+        assert!(run(10, 0, 0, 10).verify().is_err());
+    }
+
+    #[test]
+    fn test_division_dividend_above_128_bits() {
+        // a = 2^80 * 2^80 + 12345, as in a fixed-point product of two 80-bit operands
+        let two_80 = Fp::from_u128(1 << 80);
+        let a = two_80 * two_80 + Fp::from(12345);
+        let b = Fp::from_u128(1 << 60);
+        let q = Fp::from_u128(1 << 100);
+        run_wide(a, b, q, Fp::from(12345)).assert_satisfied();
+        assert!(run_wide(a, b, q + Fp::from(1), Fp::from(12345)).verify().is_err());
+    }
+}
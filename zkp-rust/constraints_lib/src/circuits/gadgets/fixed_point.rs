@@ -0,0 +1,288 @@
+//! # FixedPointChip: Fixed-Point Multiply/Divide on top of DivisionChip
+//!
+//! A fixed-point number `x` is stored as the integer `x * scale` (e.g. `scale = 100`
+//! stores `1.25` as `125`). Results are rounded down:
+//!
+//! - `mul(x, y) = (x * y) / scale`
+//! - `div(x, y) = (x * scale) / y`
+//!
+//! Both products are computed in a gate and then divided with `DivisionChip`,
+//! so every result is range-checked to the configured `num_bits`. The operands are
+//! range-checked to `num_bits` as well: the gates only hold modulo the field, and
+//! `2 * num_bits <= 2 * MAX_DIVISION_BITS` keeps `x * y` and `x * scale` below it.
+//!
+//! ```rust
+//! // In configure()
+//! let config = FixedPointChip::configure(meta, FixedPointColumns { division, constants, scale: 100 });
+//!
+//! // In synthesize()
+//! let chip = FixedPointChip::construct(config);
+//! let ratio = chip.div(layouter.namespace(|| "paid / income"), &paid, &income)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+
+use super::gadget::Gadget;
+use super::division::{DivisionChip, DivisionColumns, DivisionConfig};
+use super::limb_range_check::LimbRangeCheckChip;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FixedPointColumns {
+    pub division: DivisionColumns,
+    /// Fixed column used to load `scale` as a constant.
+    pub constants: Column<Fixed>,
+    pub scale: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct FixedPointConfig {
+    pub division_config: DivisionConfig,
+    pub scale: u64,
+    pub q_mul: Selector,
+    pub q_scale: Selector,
+}
+
+pub struct FixedPointChip<F: PrimeField> {
+    pub config: FixedPointConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> FixedPointChip<F> {
+    /// Constrain an operand to `num_bits`, so that its product cannot wrap around the modulus.
+    fn check_operand(&self, layouter: impl Layouter<F>, value: &AssignedCell<F, F>) -> Result<(), Error> {
+        let division = &self.config.division_config;
+        let range_chip = LimbRangeCheckChip::construct(division.range_check_config.clone());
+        range_chip.check(layouter, value, division.num_bits)?;
+        Ok(())
+    }
+
+    /// `(x * y) / scale`
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let division = &self.config.division_config;
+        self.check_operand(layouter.namespace(|| "x range"), x)?;
+        self.check_operand(layouter.namespace(|| "y range"), y)?;
+
+        let (product, scale) = layouter.assign_region(
+            || "fixed-point mul",
+            |mut region| {
+                self.config.q_mul.enable(&mut region, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, division.a, 0)?;
+                let y = y.copy_advice(|| "y", &mut region, division.b, 0)?;
+                let product = region.assign_advice(
+                    || "x * y",
+                    division.q,
+                    0,
+                    || x.value().zip(y.value()).map(|(x, y)| *x * y),
+                )?;
+                let scale = region.assign_advice_from_constant(
+                    || "scale",
+                    division.r,
+                    0,
+                    F::from(self.config.scale),
+                )?;
+                Ok((product, scale))
+            },
+        )?;
+
+        let division_chip = DivisionChip::construct(division.clone());
+        let (result, _) = division_chip.div_rem(layouter.namespace(|| "rescale"), &product, &scale)?;
+        Ok(result)
+    }
+
+    /// `(x * scale) / y`
+    pub fn div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let division = &self.config.division_config;
+        let scale = F::from(self.config.scale);
+        // `y` is bounded by the division itself
+        self.check_operand(layouter.namespace(|| "x range"), x)?;
+
+        let numerator = layouter.assign_region(
+            || "fixed-point scale",
+            |mut region| {
+                self.config.q_scale.enable(&mut region, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, division.a, 0)?;
+                region.assign_advice(|| "x * scale", division.q, 0, || x.value().map(|x| *x * scale))
+            },
+        )?;
+
+        let division_chip = DivisionChip::construct(division.clone());
+        let (result, _) = division_chip.div_rem(layouter.namespace(|| "divide"), &numerator, y)?;
+        Ok(result)
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for FixedPointChip<F> {
+    type Config = FixedPointConfig;
+    type Columns = FixedPointColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: FixedPointConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: FixedPointColumns) -> FixedPointConfig {
+        let FixedPointColumns { division, constants, scale } = columns;
+        assert!(scale > 0, "scale must be positive");
+        meta.enable_constant(constants);
+
+        let division_config = DivisionChip::configure(meta, division);
+        let [x, y, out, _, _] = division.advice;
+        let q_mul = meta.selector();
+        let q_scale = meta.selector();
+
+        meta.create_gate("fixed-point mul", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            vec![q_mul * (out - x * y)]
+        });
+
+        meta.create_gate("fixed-point scale", |meta| {
+            let q_scale = meta.query_selector(q_scale);
+            let x = meta.query_advice(x, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            vec![q_scale * (out - x * Expression::Constant(F::from(scale)))]
+        });
+
+        FixedPointConfig {
+            division_config,
+            scale,
+            q_mul,
+            q_scale,
+        }
+    }
+
+    /// `inputs` is `[x, y]`. Returns the fixed-point product `x * y`.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [x, y] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        self.mul(layouter, x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    const SCALE: u64 = 100;
+
+    struct FixedPointCircuit {
+        divide: bool,
+        x: Value<Fp>,
+        y: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for FixedPointCircuit {
+        type Config = (FixedPointConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                divide: self.divide,
+                x: Value::unknown(),
+                y: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let columns = FixedPointColumns {
+                division: DivisionColumns {
+                    advice: [(); 5].map(|_| meta.advice_column()),
+                    num_bits: 32,
+                },
+                constants: meta.fixed_column(),
+                scale: SCALE,
+            };
+            (FixedPointChip::configure(meta, columns), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let x = load_private(layouter.namespace(|| "x"), config.division_config.a, self.x)?;
+            let y = load_private(layouter.namespace(|| "y"), config.division_config.b, self.y)?;
+
+            let chip = FixedPointChip::construct(config);
+            let out = if self.divide {
+                chip.div(layouter.namespace(|| "div"), &x, &y)?
+            } else {
+                chip.mul(layouter.namespace(|| "mul"), &x, &y)?
+            };
+            layouter.constrain_instance(out.cell(), instance, 0)
+        }
+    }
+
+    fn run_fe(divide: bool, x: Fp, y: Fp, expected: u64) -> MockProver<Fp> {
+        let circuit = FixedPointCircuit {
+            divide,
+            x: Value::known(x),
+            y: Value::known(y),
+        };
+        MockProver::run(8, &circuit, vec![vec![Fp::from(expected)]]).unwrap()
+    }
+
+    fn run(divide: bool, x: u64, y: u64, expected: u64) -> MockProver<Fp> {
+        run_fe(divide, Fp::from(x), Fp::from(y), expected)
+    }
+
+    #[test]
+    fn test_fixed_point_mul() {
+        // 1.50 * 2.50 = 3.75
+        run(false, 150, 250, 375).assert_satisfied();
+        // 0.33 * 0.33 = 0.1089 -> 0.10
+        run(false, 33, 33, 10).assert_satisfied();
+    }
+
+    #[test]
+    fn test_fixed_point_percentage() {
+        // 37 / 50 = 74%
+        run(true, 3700, 5000, 74).assert_satisfied();
+        assert!(run(true, 3700, 5000, 75).verify().is_err());
+    }
+
+    #[test]
+    fn test_fixed_point_operands_range_checked() {
+        // x * y = 5 * scale mod p with an out-of-range x: the product gate alone would accept 5
+        let x = Fp::from(1u64 << 40).invert().unwrap();
+        let y = Fp::from(5 * SCALE) * Fp::from(1u64 << 40);
+        assert!(run_fe(false, x, y, 5).verify().is_err());
+
+        // x * scale = 3700 mod p with an out-of-range x
+        let x = Fp::from(3700) * Fp::from(SCALE).invert().unwrap();
+        assert!(run_fe(true, x, Fp::from(5000), 74).verify().is_err());
+    }
+}
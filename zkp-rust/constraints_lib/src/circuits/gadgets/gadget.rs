@@ -0,0 +1,70 @@
+//! # Gadget: Common Interface for constraints_lib Chips
+//!
+//! Every chip in this crate follows the same convention:
+//!
+//! - `configure(meta, columns)` creates the chip's gates over caller-provided columns.
+//! - `assign(layouter, inputs)` copies already-assigned input cells into its own region
+//!   (so they are linked with copy constraints) and returns the gadget's output, a single
+//!   cell for every chip except those producing a vector (`SortedPermutationChip`).
+//!
+//! For check gadgets (comparison, set membership, ...) the returned cell is the `flag`
+//! that enables the check, so the result can be wired into further logic.
+//!
+//! ```rust
+//! // In configure()
+//! let config = ComparisonChip::configure(meta, ComparisonColumns { .. });
+//!
+//! // In synthesize()
+//! let chip = ComparisonChip::construct(config);
+//! let lhs = load_private(layouter.namespace(|| "lhs"), column, lhs_val)?;
+//! let flag = chip.assign(layouter.namespace(|| "compare"), &[lhs, rhs, flag])?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance},
+};
+use group::ff::PrimeField;
+
+pub trait Gadget<F: PrimeField>: Sized {
+    type Config: Clone + std::fmt::Debug;
+    /// Columns (and any shape parameters) the gadget is configured over.
+    type Columns;
+    /// Result of `assign`, usually `AssignedCell<F, F>`.
+    type Output;
+
+    fn construct(config: Self::Config) -> Self;
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: Self::Columns) -> Self::Config;
+
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<Self::Output, Error>;
+}
+
+/// Witness a private value in its own region so it can be passed to `Gadget::assign`.
+pub fn load_private<F: PrimeField>(
+    mut layouter: impl Layouter<F>,
+    column: Column<Advice>,
+    value: Value<F>,
+) -> Result<AssignedCell<F, F>, Error> {
+    layouter.assign_region(
+        || "load private",
+        |mut region| region.assign_advice(|| "private input", column, 0, || value),
+    )
+}
+
+/// Copy a public input into advice so it can be passed to `Gadget::assign`.
+pub fn load_instance<F: PrimeField>(
+    mut layouter: impl Layouter<F>,
+    instance: Column<Instance>,
+    row: usize,
+    column: Column<Advice>,
+) -> Result<AssignedCell<F, F>, Error> {
+    layouter.assign_region(
+        || "load instance",
+        |mut region| region.assign_advice_from_instance(|| "public input", instance, row, column, 0),
+    )
+}
@@ -0,0 +1,203 @@
+//! # LimbRangeCheckChip: Bit-Width Range Checks for Halo2
+//!
+//! `halo2_gadgets::utilities::range_check(word, range)` builds a degree-`range` polynomial,
+//! which is only practical for small ranges such as `MAX_AGE`. This chip proves
+//! `0 <= value < 2^num_bits` for large widths by decomposing `value` into
+//! `RANGE_CHECK_LIMB_BITS`-bit limbs with a running sum, and applying `range_check` per limb:
+//!
+//! ```text
+//! z_0 = value
+//! z_i - z_{i+1} * 2^K  in [0, 2^K)   (one row per limb)
+//! z_n = 0
+//! ```
+//!
+//! `num_bits` must be a multiple of `RANGE_CHECK_LIMB_BITS`.
+//!
+//! ```rust
+//! // In configure()
+//! let config = LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z, num_bits: 64 });
+//!
+//! // In synthesize()
+//! let chip = LimbRangeCheckChip::construct(config);
+//! chip.assign(layouter.namespace(|| "amount < 2^64"), &[amount])?;
+//! chip.check(layouter.namespace(|| "byte"), &byte, 8)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+use halo2_gadgets::utilities::range_check;
+
+use super::gadget::Gadget;
+use crate::constants::RANGE_CHECK_LIMB_BITS;
+use crate::utils::fe_low_bits;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LimbRangeCheckColumns {
+    pub z: Column<Advice>,
+    /// Width used by `Gadget::assign`; `check` accepts any multiple of `RANGE_CHECK_LIMB_BITS`.
+    pub num_bits: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct LimbRangeCheckConfig {
+    pub z: Column<Advice>,
+    pub num_bits: usize,
+    pub q_limb: Selector,
+    pub q_zero: Selector,
+}
+
+pub struct LimbRangeCheckChip<F: PrimeField> {
+    pub config: LimbRangeCheckConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> LimbRangeCheckChip<F> {
+    /// Constrain `0 <= value < 2^num_bits` and return the copied value.
+    pub fn check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if num_bits == 0 || num_bits % RANGE_CHECK_LIMB_BITS != 0 {
+            return Err(Error::Synthesis);
+        }
+        let num_limbs = num_bits / RANGE_CHECK_LIMB_BITS;
+        let shift_inv = F::from(1u64 << RANGE_CHECK_LIMB_BITS).invert().unwrap();
+
+        layouter.assign_region(
+            || format!("range check {} bits", num_bits),
+            |mut region| {
+                let copied = value.copy_advice(|| "z_0", &mut region, self.config.z, 0)?;
+
+                let mut z: Value<F> = copied.value().copied();
+                for i in 0..num_limbs {
+                    self.config.q_limb.enable(&mut region, i)?;
+                    z = z.map(|z| {
+                        let limb = F::from(fe_low_bits(&z, RANGE_CHECK_LIMB_BITS));
+                        (z - limb) * shift_inv
+                    });
+                    region.assign_advice(|| format!("z_{}", i + 1), self.config.z, i + 1, || z)?;
+                }
+                self.config.q_zero.enable(&mut region, num_limbs)?;
+
+                Ok(copied)
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for LimbRangeCheckChip<F> {
+    type Config = LimbRangeCheckConfig;
+    type Columns = LimbRangeCheckColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: LimbRangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: LimbRangeCheckColumns) -> LimbRangeCheckConfig {
+        let LimbRangeCheckColumns { z, num_bits } = columns;
+        assert_eq!(num_bits % RANGE_CHECK_LIMB_BITS, 0, "num_bits must be a multiple of the limb width");
+        meta.enable_equality(z);
+
+        let q_limb = meta.selector();
+        let q_zero = meta.selector();
+
+        meta.create_gate("limb range check", |meta| {
+            let q_limb = meta.query_selector(q_limb);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+
+            let shift = Expression::Constant(F::from(1u64 << RANGE_CHECK_LIMB_BITS));
+            let limb = z_cur - z_next * shift;
+
+            vec![q_limb * range_check(limb, 1 << RANGE_CHECK_LIMB_BITS)]
+        });
+
+        meta.create_gate("limb range check final", |meta| {
+            let q_zero = meta.query_selector(q_zero);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+
+            vec![q_zero * z_cur]
+        });
+
+        LimbRangeCheckConfig { z, num_bits, q_limb, q_zero }
+    }
+
+    /// `inputs` is `[value]`; checks it against the configured `num_bits`.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [value] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        self.check(layouter, value, self.config.num_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct RangeCircuit {
+        value: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for RangeCircuit {
+        type Config = LimbRangeCheckConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let z = meta.advice_column();
+            LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z, num_bits: 16 })
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let value = load_private(layouter.namespace(|| "value"), config.z, self.value)?;
+            let chip = LimbRangeCheckChip::construct(config);
+            chip.assign(layouter.namespace(|| "range check"), &[value])?;
+            Ok(())
+        }
+    }
+
+    fn run(value: Fp) -> MockProver<Fp> {
+        let circuit = RangeCircuit { value: Value::known(value) };
+        MockProver::run(6, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_limb_range_check_pass() {
+        run(Fp::from(0)).assert_satisfied();
+        run(Fp::from(12345)).assert_satisfied();
+        run(Fp::from(u16::MAX as u64)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_limb_range_check_fail() {
+        assert!(run(Fp::from(1 << 16)).verify().is_err());
+        // Negative values wrap around to huge field elements.
+        assert!(run(-Fp::from(1)).verify().is_err());
+    }
+}
@@ -1,3 +1,17 @@
+pub mod gadget;
+pub mod boolean;
 pub mod set_membership_check;
+pub mod set_membership_lookup;
 pub mod range_check;
-pub mod equality_check;
\ No newline at end of file
+pub mod equality_check;
+pub mod limb_range_check;
+pub mod division;
+pub mod fixed_point;
+pub mod set_non_membership;
+pub mod aggregate;
+pub mod sorted_permutation;
+pub mod byte_string;
+pub mod suffix_match;
+
+#[cfg(test)]
+mod differential_tests;
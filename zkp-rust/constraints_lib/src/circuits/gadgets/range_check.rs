@@ -11,28 +11,35 @@
 //! - LessThan
 //! - LessEqual
 //!
+//! Both inputs are constrained to `[0, max_value)`, whatever the flag, so the range-checked
+//! difference (e.g. `0 <= lhs - rhs < max_value` for `GreaterEqual`) is the integer comparison.
+//!
 //! ## Usage
-//! Call `ComparisonChip::configure(meta, comparison, max_value)` in the `configure` function
-//! and store the returned config.
+//! Call `ComparisonChip::configure(meta, columns)` in the `configure` function
+//! and store the returned config. `columns` also carries the comparison and `max_value`.
 //!
-//! In `synthesize`, construct the chip via `ComparisonChip::construct(config)` and call `assign`.
+//! In `synthesize`, construct the chip via `ComparisonChip::construct(config)` and call `assign`
+//! with the `[lhs, rhs, flag]` cells. The copied flag cell is returned.
 //!
 //! ```rust
 //! // In configure()
-//! let config = ComparisonChip::configure(meta, Comparison::GreaterEqual, 256);
+//! let columns = ComparisonColumns { lhs, rhs, flag, comparison: Comparison::GreaterEqual, max_value: 256 };
+//! let config = ComparisonChip::configure(meta, columns);
 //!
 //! // In synthesize()
 //! let chip = ComparisonChip::construct(config);
-//! chip.assign(&mut region, offset, lhs_val, rhs_val, flag)?;
+//! let flag = chip.assign(layouter.namespace(|| "compare"), &[lhs_cell, rhs_cell, flag_cell])?;
 //! ```
 
 use halo2_proofs::{
-    circuit::{Value, Region},
+    circuit::{AssignedCell, Layouter},
     plonk::{Advice, Column, ConstraintSystem, Error, Selector, Expression},
     poly::Rotation,
 };
 use group::ff::PrimeField;
-use halo2_gadgets::utilities::range_check;
+use halo2_gadgets::utilities::{bool_check, range_check};
+
+use super::gadget::Gadget;
 
 /// Enum representing supported comparison operations.
 #[derive(Clone, Copy, Debug)]
@@ -51,6 +58,16 @@ impl Default for Comparison {
     }
 }
 
+/// Columns and shape parameters for the comparison chip.
+#[derive(Clone, Copy, Debug)]
+pub struct ComparisonColumns {
+    pub lhs: Column<Advice>,
+    pub rhs: Column<Advice>,
+    pub flag: Column<Advice>,
+    pub comparison: Comparison,
+    pub max_value: usize,
+}
+
 /// Configuration object for the comparison chip.
 #[derive(Clone, Debug)]
 pub struct ComparisonConfig {
@@ -67,31 +84,6 @@ pub struct ComparisonChip<F: PrimeField> {
 }
 
 impl<F: PrimeField> ComparisonChip<F> {
-    /// Construct a ComparisonChip from config.
-    pub fn construct(config: ComparisonConfig) -> Self {
-        Self {
-            config,
-            _marker: std::marker::PhantomData,
-        }
-    }
-
-    /// Configure the circuit and create the appropriate comparison gate.
-    pub fn configure(meta: &mut ConstraintSystem<F>, comparison: Comparison, max_value: usize) -> ComparisonConfig {
-        let lhs = meta.advice_column();
-        let rhs = meta.advice_column();
-        let flag = meta.advice_column();
-        let selector = meta.selector();
-
-        meta.enable_equality(lhs);
-        meta.enable_equality(rhs);
-        meta.enable_equality(flag);
-
-        let config = ComparisonConfig { lhs, rhs, flag, selector };
-
-        Self::create_gate_static(meta, &config, comparison, max_value);
-        config
-    }
-
     /// Internal static function to generate comparison constraint.
     pub fn create_gate_static(
         meta: &mut ConstraintSystem<F>,
@@ -103,55 +95,85 @@ impl<F: PrimeField> ComparisonChip<F> {
             let sel = meta.query_selector(cfg.selector);
             let lhs = meta.query_advice(cfg.lhs, Rotation::cur());
             let rhs = meta.query_advice(cfg.rhs, Rotation::cur());
+            let flag = meta.query_advice(cfg.flag, Rotation::cur());
 
+            // The (adjusted) difference must lie in [0, max_value).
             let constraint = match comparison {
                 Comparison::Equal => lhs.clone() - rhs.clone(),
-                Comparison::GreaterEqual => {
-                    let diff = lhs.clone() - rhs.clone();
-                    let adjusted = Expression::Constant(F::from(max_value as u64)) - diff;
-                    range_check(adjusted, max_value)
-                },
-                Comparison::LessEqual => {
-                    let diff = rhs.clone() - lhs.clone();
-                    let adjusted = Expression::Constant(F::from(max_value as u64)) - diff;
-                    range_check(adjusted, max_value)
-                },
+                Comparison::GreaterEqual => range_check(lhs.clone() - rhs.clone(), max_value),
+                Comparison::LessEqual => range_check(rhs.clone() - lhs.clone(), max_value),
                 Comparison::GreaterThan => {
-                    let diff = lhs.clone() - rhs.clone() - Expression::Constant(F::ONE);
-                    let adjusted = Expression::Constant(F::from(max_value as u64)) - diff;
-                    range_check(adjusted, max_value)
+                    range_check(lhs.clone() - rhs.clone() - Expression::Constant(F::ONE), max_value)
                 },
                 Comparison::LessThan => {
-                    let diff = rhs.clone() - lhs.clone() - Expression::Constant(F::ONE);
-                    let adjusted = Expression::Constant(F::from(max_value as u64)) - diff;
-                    range_check(adjusted, max_value)
+                    range_check(rhs.clone() - lhs.clone() - Expression::Constant(F::ONE), max_value)
                 },
             };
 
-            vec![sel * constraint]
+            vec![
+                sel.clone() * bool_check(flag.clone()),
+                sel.clone() * range_check(lhs, max_value),
+                sel.clone() * range_check(rhs, max_value),
+                sel * flag * constraint,
+            ]
         });
     }
+}
+
+impl<F: PrimeField> Gadget<F> for ComparisonChip<F> {
+    type Config = ComparisonConfig;
+    type Columns = ComparisonColumns;
+    type Output = AssignedCell<F, F>;
+
+    /// Construct a ComparisonChip from config.
+    fn construct(config: ComparisonConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Configure the circuit and create the appropriate comparison gate.
+    fn configure(meta: &mut ConstraintSystem<F>, columns: ComparisonColumns) -> ComparisonConfig {
+        let ComparisonColumns { lhs, rhs, flag, comparison, max_value } = columns;
+        let selector = meta.selector();
+
+        meta.enable_equality(lhs);
+        meta.enable_equality(rhs);
+        meta.enable_equality(flag);
+
+        let config = ComparisonConfig { lhs, rhs, flag, selector };
+
+        Self::create_gate_static(meta, &config, comparison, max_value);
+        config
+    }
 
-    /// Assign witnesses for the comparison operation.
-    pub fn assign(
+    /// Copy `[lhs, rhs, flag]` into the comparison row and return the copied flag.
+    fn assign(
         &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        lhs: Value<F>,
-        rhs: Value<F>,
-        flag: Value<F>,
-    ) -> Result<(), Error> {
-        self.config.selector.enable(region, offset)?;
-        region.assign_advice(|| "lhs", self.config.lhs, offset, || lhs)?;
-        region.assign_advice(|| "rhs", self.config.rhs, offset, || rhs)?;
-        region.assign_advice(|| "flag", self.config.flag, offset, || flag)?;
-        Ok(())
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [lhs, rhs, flag] = inputs else {
+            return Err(Error::Synthesis);
+        };
+
+        layouter.assign_region(
+            || "comparison",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                lhs.copy_advice(|| "lhs", &mut region, self.config.lhs, 0)?;
+                rhs.copy_advice(|| "rhs", &mut region, self.config.rhs, 0)?;
+                flag.copy_advice(|| "flag", &mut region, self.config.flag, 0)
+            },
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
     use halo2_proofs::{
         pasta::Fp,
         circuit::{Value, SimpleFloorPlanner, Layouter},
@@ -160,10 +182,20 @@ mod tests {
     };
     use halo2_proofs::arithmetic::Field;
 
-    #[derive(Default)]
     struct TestCircuit {
         lhs: Value<Fp>,
         rhs: Value<Fp>,
+        flag: Value<Fp>,
+    }
+
+    impl Default for TestCircuit {
+        fn default() -> Self {
+            Self {
+                lhs: Value::unknown(),
+                rhs: Value::unknown(),
+                flag: Value::known(Fp::ONE),
+            }
+        }
     }
 
     impl Circuit<Fp> for TestCircuit {
@@ -175,17 +207,24 @@ mod tests {
         }
 
         fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
-            ComparisonChip::configure(meta, Comparison::GreaterEqual, 256)
+            let columns = ComparisonColumns {
+                lhs: meta.advice_column(),
+                rhs: meta.advice_column(),
+                flag: meta.advice_column(),
+                comparison: Comparison::GreaterEqual,
+                max_value: 256,
+            };
+            ComparisonChip::configure(meta, columns)
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let lhs = load_private(layouter.namespace(|| "lhs"), config.lhs, self.lhs)?;
+            let rhs = load_private(layouter.namespace(|| "rhs"), config.rhs, self.rhs)?;
+            let flag = load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?;
+
             let chip = ComparisonChip::construct(config);
-            layouter.assign_region(
-                || "comparison check",
-                |mut region| {
-                    chip.assign(&mut region, 0, self.lhs, self.rhs, Value::known(Fp::ONE))
-                },
-            )
+            chip.assign(layouter.namespace(|| "comparison check"), &[lhs, rhs, flag])?;
+            Ok(())
         }
     }
 
@@ -194,6 +233,7 @@ mod tests {
         let circuit = TestCircuit {
             lhs: Value::known(Fp::from(30)),
             rhs: Value::known(Fp::from(10)),
+            ..Default::default()
         };
         let prover = MockProver::run(8, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
@@ -204,6 +244,64 @@ mod tests {
         let circuit = TestCircuit {
             lhs: Value::known(Fp::from(5)),
             rhs: Value::known(Fp::from(10)),
+            ..Default::default()
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_greater_equal_boundary() {
+        let equal = TestCircuit {
+            lhs: Value::known(Fp::from(10)),
+            rhs: Value::known(Fp::from(10)),
+            ..Default::default()
+        };
+        MockProver::run(8, &equal, vec![]).unwrap().assert_satisfied();
+
+        let too_far = TestCircuit {
+            lhs: Value::known(Fp::from(266)),
+            rhs: Value::known(Fp::from(10)),
+            ..Default::default()
+        };
+        assert!(MockProver::run(8, &too_far, vec![]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_operand_out_of_range_fail() {
+        // lhs - rhs = 10 is in range, but both operands are above max_value
+        let circuit = TestCircuit {
+            lhs: Value::known(Fp::from(300)),
+            rhs: Value::known(Fp::from(290)),
+            ..Default::default()
+        };
+        assert!(MockProver::run(8, &circuit, vec![]).unwrap().verify().is_err());
+
+        let flag_off = TestCircuit {
+            lhs: Value::known(Fp::from(5)),
+            rhs: Value::known(-Fp::ONE),
+            flag: Value::known(Fp::ZERO),
+        };
+        assert!(MockProver::run(8, &flag_off, vec![]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_greater_equal_flag_off_pass() {
+        let circuit = TestCircuit {
+            lhs: Value::known(Fp::from(5)),
+            rhs: Value::known(Fp::from(10)),
+            flag: Value::known(Fp::ZERO),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_non_boolean_flag_fail() {
+        let circuit = TestCircuit {
+            lhs: Value::known(Fp::from(30)),
+            rhs: Value::known(Fp::from(10)),
+            flag: Value::known(Fp::from(2)),
         };
         let prover = MockProver::run(8, &circuit, vec![]).unwrap();
         assert!(prover.verify().is_err());
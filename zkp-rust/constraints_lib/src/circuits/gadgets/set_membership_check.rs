@@ -1,11 +1,13 @@
 use halo2_proofs::{
-    circuit::{Region, Value},
+    circuit::{AssignedCell, Layouter, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Selector, Expression},
     poly::Rotation,
 };
 use group::ff::PrimeField;
+use halo2_gadgets::utilities::bool_check;
 
 use crate::constants::MAX_MEMBERSHIP_NUMBER; 
+use super::gadget::Gadget;
 
 #[derive(Debug, Clone)]
 pub struct SetMembershipConfig {
@@ -20,20 +22,24 @@ pub struct SetMembershipChip<F: PrimeField> {
     pub _marker: std::marker::PhantomData<F>,
 }
 
-impl<F: PrimeField> SetMembershipChip<F> {
-    pub fn construct(config: SetMembershipConfig) -> Self {
+impl<F: PrimeField> Gadget<F> for SetMembershipChip<F> {
+    type Config = SetMembershipConfig;
+    /// `[input_value, flag, set_column]`
+    type Columns = [Column<Advice>; 3];
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: SetMembershipConfig) -> Self {
         Self {
             config,
             _marker: std::marker::PhantomData,
         }
     }
 
-    pub fn configure(
+    fn configure(
         meta: &mut ConstraintSystem<F>,
-        input_value: Column<Advice>,
-        flag: Column<Advice>,
-        set_column: Column<Advice>,
+        columns: [Column<Advice>; 3],
     ) -> SetMembershipConfig {
+        let [input_value, flag, set_column] = columns;
         meta.enable_equality(input_value);
         meta.enable_equality(flag);
         meta.enable_equality(set_column);
@@ -51,7 +57,10 @@ impl<F: PrimeField> SetMembershipChip<F> {
                 product = product * (input.clone() - set_i);
             }
 
-            vec![sel * flag * product]
+            vec![
+                sel.clone() * bool_check(flag.clone()),
+                sel * flag * product,
+            ]
         });
 
         SetMembershipConfig {
@@ -62,42 +71,51 @@ impl<F: PrimeField> SetMembershipChip<F> {
         }
     }
 
-    pub fn assign(
+    /// `inputs` is `[input_value, flag, set_0, .., set_k]` with at most `MAX_MEMBERSHIP_NUMBER`
+    /// set members; missing members are padded with zero. Returns the copied flag.
+    fn assign(
         &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        input_value: Value<F>,
-        flag: Value<F>,
-        set_values: Vec<Value<F>>,
-    ) -> Result<(), Error> {
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [input_value, flag, set_values @ ..] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        if set_values.len() > MAX_MEMBERSHIP_NUMBER {
+            return Err(Error::Synthesis);
+        }
         let config = &self.config;
 
-        config.selector.enable(region, offset)?;
+        layouter.assign_region(
+            || "set membership",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
 
-        region.assign_advice(|| "input value", config.input_value, offset, || input_value)?;
-        region.assign_advice(|| "flag", config.flag, offset, || flag)?;
+                input_value.copy_advice(|| "input value", &mut region, config.input_value, 0)?;
+                let flag = flag.copy_advice(|| "flag", &mut region, config.flag, 0)?;
 
-        for i in 0..MAX_MEMBERSHIP_NUMBER {
-            region.assign_advice(
-                || format!("set[{}]", i),
-                config.set_column,
-                offset + i,
-                || {
+                for i in 0..MAX_MEMBERSHIP_NUMBER {
                     if i < set_values.len() {
-                        set_values[i]
+                        set_values[i].copy_advice(|| format!("set[{}]", i), &mut region, config.set_column, i)?;
                     } else {
-                        Value::known(F::ZERO)
+                        region.assign_advice(
+                            || format!("set[{}]", i),
+                            config.set_column,
+                            i,
+                            || Value::known(F::ZERO),
+                        )?;
                     }
-                },
-            )?;
-        }
-        Ok(())
+                }
+                Ok(flag)
+            },
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner},
         dev::MockProver,
@@ -124,17 +142,21 @@ mod tests {
             let input = meta.advice_column();
             let flag = meta.advice_column();
             let set = meta.advice_column();
-            SetMembershipChip::configure(meta, input, flag, set)
+            SetMembershipChip::configure(meta, [input, flag, set])
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let mut inputs = vec![
+                load_private(layouter.namespace(|| "input"), config.input_value, self.input)?,
+                load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?,
+            ];
+            for (i, value) in self.set.iter().enumerate() {
+                inputs.push(load_private(layouter.namespace(|| format!("set[{}]", i)), config.set_column, *value)?);
+            }
+
             let chip = SetMembershipChip::construct(config);
-            layouter.assign_region(
-                || "membership check",
-                |mut region| {
-                    chip.assign(&mut region, 0, self.input, self.flag, self.set.clone())
-                },
-            )
+            chip.assign(layouter.namespace(|| "membership check"), &inputs)?;
+            Ok(())
         }
     }
 
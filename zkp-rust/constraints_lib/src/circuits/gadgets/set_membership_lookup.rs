@@ -0,0 +1,431 @@
+//! # SetMembershipLookupChip: Dynamic-Size Set Membership for Halo2
+//!
+//! Unlike `SetMembershipChip`, whose gate multiplies `(input - set_i)` over
+//! `MAX_MEMBERSHIP_NUMBER` rotations, this chip keeps the constraint degree constant
+//! regardless of the set size. The set can come from two sources:
+//!
+//! - `SetSource::Fixed`: a compile-time set loaded into lookup table columns.
+//!   `input_value` is looked up against the table, so the set can have any length
+//!   up to the number of usable rows.
+//! - `SetSource::Instance`: a verifier-supplied set read from an instance column.
+//!   halo2 lookups only target fixed tables, so each instance row is copied into advice
+//!   and folded into a running product `acc_i = acc_{i-1} * (input - set_i)` (degree 2 per row).
+//!
+//! In both cases the check is enforced only when `flag == 1`, as in `SetMembershipChip`.
+//!
+//! ## Usage
+//! ```rust
+//! // In configure()
+//! let columns = SetMembershipLookupColumns { input_value, flag, source: SetSource::Fixed };
+//! let config = SetMembershipLookupChip::configure(meta, columns);
+//!
+//! // In synthesize()
+//! let chip = SetMembershipLookupChip::construct(config);
+//! chip.load_fixed_set(&mut layouter, &allowed_values)?;
+//! let flag = chip.assign(layouter.namespace(|| "membership"), &[input_cell, flag_cell])?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance, Selector, TableColumn},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+
+use super::gadget::Gadget;
+
+/// Where the set members are taken from.
+#[derive(Clone, Copy, Debug)]
+pub enum SetSource {
+    /// Members are loaded into a fixed lookup table (compile-time set).
+    Fixed,
+    /// Members are the first `size` rows of the given instance column (verifier-supplied set).
+    Instance { instance: Column<Instance>, size: usize },
+}
+
+/// Source-specific columns of the chip.
+#[derive(Clone, Debug)]
+pub enum SetSourceConfig {
+    Fixed {
+        selector: Selector,
+        // Row 0 of the table is the (0, 0) default row used by disabled lookups.
+        // Real members are stored as (1, value), so 0 is never accepted implicitly.
+        table_tag: TableColumn,
+        table_value: TableColumn,
+    },
+    Instance {
+        instance: Column<Instance>,
+        size: usize,
+        set_column: Column<Advice>,
+        accumulator: Column<Advice>,
+        s_first: Selector,
+        s_step: Selector,
+        s_last: Selector,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SetMembershipLookupColumns {
+    pub input_value: Column<Advice>,
+    pub flag: Column<Advice>,
+    pub source: SetSource,
+}
+
+#[derive(Clone, Debug)]
+pub struct SetMembershipLookupConfig {
+    pub input_value: Column<Advice>,
+    pub flag: Column<Advice>,
+    pub source: SetSourceConfig,
+}
+
+pub struct SetMembershipLookupChip<F: PrimeField> {
+    pub config: SetMembershipLookupConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> SetMembershipLookupChip<F> {
+    /// Load a compile-time set into the lookup table. Only used with `SetSource::Fixed`.
+    pub fn load_fixed_set(&self, layouter: &mut impl Layouter<F>, set_values: &[F]) -> Result<(), Error> {
+        let (table_tag, table_value) = match &self.config.source {
+            SetSourceConfig::Fixed { table_tag, table_value, .. } => (*table_tag, *table_value),
+            SetSourceConfig::Instance { .. } => return Err(Error::Synthesis),
+        };
+
+        layouter.assign_table(
+            || "set membership table",
+            |mut table| {
+                table.assign_cell(|| "default tag", table_tag, 0, || Value::known(F::ZERO))?;
+                table.assign_cell(|| "default value", table_value, 0, || Value::known(F::ZERO))?;
+                for (i, value) in set_values.iter().enumerate() {
+                    table.assign_cell(|| format!("tag[{}]", i), table_tag, i + 1, || Value::known(F::ONE))?;
+                    table.assign_cell(|| format!("set[{}]", i), table_value, i + 1, || Value::known(*value))?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for SetMembershipLookupChip<F> {
+    type Config = SetMembershipLookupConfig;
+    type Columns = SetMembershipLookupColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: SetMembershipLookupConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        columns: SetMembershipLookupColumns,
+    ) -> SetMembershipLookupConfig {
+        let SetMembershipLookupColumns { input_value, flag, source } = columns;
+        meta.enable_equality(input_value);
+        meta.enable_equality(flag);
+
+        match source {
+            SetSource::Fixed => {
+                let selector = meta.complex_selector();
+                let table_tag = meta.lookup_table_column();
+                let table_value = meta.lookup_table_column();
+
+                meta.lookup("set membership lookup", |meta| {
+                    let sel = meta.query_selector(selector);
+                    let input = meta.query_advice(input_value, Rotation::cur());
+                    let flag = meta.query_advice(flag, Rotation::cur());
+                    let enabled = sel * flag;
+
+                    vec![
+                        (enabled.clone(), table_tag),
+                        (enabled * input, table_value),
+                    ]
+                });
+
+                SetMembershipLookupConfig {
+                    input_value,
+                    flag,
+                    source: SetSourceConfig::Fixed { selector, table_tag, table_value },
+                }
+            }
+            SetSource::Instance { instance, size } => {
+                assert!(size > 0, "instance set must have at least one member");
+                let s_first = meta.selector();
+                let s_step = meta.selector();
+                let s_last = meta.selector();
+                let set_column = meta.advice_column();
+                let accumulator = meta.advice_column();
+
+                meta.enable_equality(instance);
+                meta.enable_equality(set_column);
+
+                // acc_0 = input - set_0
+                meta.create_gate("set membership running product start", |meta| {
+                    let s_first = meta.query_selector(s_first);
+                    let input = meta.query_advice(input_value, Rotation::cur());
+                    let set = meta.query_advice(set_column, Rotation::cur());
+                    let acc = meta.query_advice(accumulator, Rotation::cur());
+
+                    vec![s_first * (acc - (input - set))]
+                });
+
+                // acc_i = acc_{i-1} * (input - set_i), with the same input on every row
+                meta.create_gate("set membership running product step", |meta| {
+                    let s_step = meta.query_selector(s_step);
+                    let input_cur = meta.query_advice(input_value, Rotation::cur());
+                    let input_prev = meta.query_advice(input_value, Rotation::prev());
+                    let set_cur = meta.query_advice(set_column, Rotation::cur());
+                    let acc_cur = meta.query_advice(accumulator, Rotation::cur());
+                    let acc_prev = meta.query_advice(accumulator, Rotation::prev());
+
+                    vec![
+                        s_step.clone() * (acc_cur - acc_prev * (input_cur.clone() - set_cur)),
+                        s_step * (input_cur - input_prev),
+                    ]
+                });
+
+                meta.create_gate("set membership result", |meta| {
+                    let s_last = meta.query_selector(s_last);
+                    let flag = meta.query_advice(flag, Rotation::cur());
+                    let acc = meta.query_advice(accumulator, Rotation::cur());
+
+                    vec![s_last * flag * acc]
+                });
+
+                SetMembershipLookupConfig {
+                    input_value,
+                    flag,
+                    source: SetSourceConfig::Instance {
+                        instance,
+                        size,
+                        set_column,
+                        accumulator,
+                        s_first,
+                        s_step,
+                        s_last,
+                    },
+                }
+            }
+        }
+    }
+
+    /// `inputs` is `[input_value, flag]`. Returns the copied flag.
+    ///
+    /// With `SetSource::Fixed` this uses a single row; with `SetSource::Instance { size, .. }`
+    /// it uses `size` rows, one per set member.
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [input_value, flag] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "set membership lookup",
+            |mut region| match &config.source {
+                SetSourceConfig::Fixed { selector, .. } => {
+                    selector.enable(&mut region, 0)?;
+                    input_value.copy_advice(|| "input value", &mut region, config.input_value, 0)?;
+                    flag.copy_advice(|| "flag", &mut region, config.flag, 0)
+                }
+                SetSourceConfig::Instance { instance, size, set_column, accumulator, s_first, s_step, s_last } => {
+                    let input = input_value.value().copied();
+                    let mut acc = Value::known(F::ONE);
+                    for i in 0..*size {
+                        if i == 0 {
+                            s_first.enable(&mut region, i)?;
+                            input_value.copy_advice(|| "input value", &mut region, config.input_value, i)?;
+                        } else {
+                            // Equality with the previous row is enforced by the running product gate.
+                            s_step.enable(&mut region, i)?;
+                            region.assign_advice(|| format!("input value[{}]", i), config.input_value, i, || input)?;
+                        }
+
+                        let set_cell = region.assign_advice_from_instance(
+                            || format!("set[{}]", i),
+                            *instance,
+                            i,
+                            *set_column,
+                            i,
+                        )?;
+
+                        acc = acc
+                            .zip(input)
+                            .zip(set_cell.value().copied())
+                            .map(|((acc, input), member)| acc * (input - member));
+                        region.assign_advice(|| format!("acc[{}]", i), *accumulator, i, || acc)?;
+                    }
+
+                    let last = size - 1;
+                    s_last.enable(&mut region, last)?;
+                    flag.copy_advice(|| "flag", &mut region, config.flag, last)
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    const FIXED_SET_SIZE: u64 = 150;
+    const INSTANCE_SET_SIZE: usize = 120;
+
+    #[derive(Default)]
+    struct FixedSetCircuit {
+        input: Value<Fp>,
+        flag: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for FixedSetCircuit {
+        type Config = SetMembershipLookupConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let columns = SetMembershipLookupColumns {
+                input_value: meta.advice_column(),
+                flag: meta.advice_column(),
+                source: SetSource::Fixed,
+            };
+            SetMembershipLookupChip::configure(meta, columns)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let input = load_private(layouter.namespace(|| "input"), config.input_value, self.input)?;
+            let flag = load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?;
+
+            let chip = SetMembershipLookupChip::construct(config);
+            // Members: 1000, 1002, 1004, ...
+            let set: Vec<Fp> = (0..FIXED_SET_SIZE).map(|i| Fp::from(1000 + 2 * i)).collect();
+            chip.load_fixed_set(&mut layouter, &set)?;
+
+            chip.assign(layouter.namespace(|| "membership check"), &[input, flag])?;
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InstanceSetCircuit {
+        input: Value<Fp>,
+        flag: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for InstanceSetCircuit {
+        type Config = SetMembershipLookupConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            let columns = SetMembershipLookupColumns {
+                input_value: meta.advice_column(),
+                flag: meta.advice_column(),
+                source: SetSource::Instance { instance, size: INSTANCE_SET_SIZE },
+            };
+            SetMembershipLookupChip::configure(meta, columns)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let input = load_private(layouter.namespace(|| "input"), config.input_value, self.input)?;
+            let flag = load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?;
+
+            let chip = SetMembershipLookupChip::construct(config);
+            chip.assign(layouter.namespace(|| "membership check"), &[input, flag])?;
+            Ok(())
+        }
+    }
+
+    fn instance_set() -> Vec<Fp> {
+        (0..INSTANCE_SET_SIZE as u64).map(|i| Fp::from(500 + 3 * i)).collect()
+    }
+
+    #[test]
+    fn test_fixed_set_membership_pass() {
+        let circuit = FixedSetCircuit {
+            input: Value::known(Fp::from(1000 + 2 * 123)),
+            flag: Value::known(Fp::from(1)),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_fixed_set_membership_fail() {
+        let circuit = FixedSetCircuit {
+            input: Value::known(Fp::from(1001)),
+            flag: Value::known(Fp::from(1)),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_fixed_set_zero_is_not_a_member() {
+        let circuit = FixedSetCircuit {
+            input: Value::known(Fp::from(0)),
+            flag: Value::known(Fp::from(1)),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_fixed_set_flag_off_pass() {
+        let circuit = FixedSetCircuit {
+            input: Value::known(Fp::from(1001)),
+            flag: Value::known(Fp::from(0)),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_instance_set_membership_pass() {
+        let circuit = InstanceSetCircuit {
+            input: Value::known(Fp::from(500 + 3 * 119)),
+            flag: Value::known(Fp::from(1)),
+        };
+        let prover = MockProver::run(8, &circuit, vec![instance_set()]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_instance_set_membership_fail() {
+        let circuit = InstanceSetCircuit {
+            input: Value::known(Fp::from(501)),
+            flag: Value::known(Fp::from(1)),
+        };
+        let prover = MockProver::run(8, &circuit, vec![instance_set()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_instance_set_flag_off_pass() {
+        let circuit = InstanceSetCircuit {
+            input: Value::known(Fp::from(501)),
+            flag: Value::known(Fp::from(0)),
+        };
+        let prover = MockProver::run(8, &circuit, vec![instance_set()]).unwrap();
+        prover.assert_satisfied();
+    }
+}
@@ -0,0 +1,651 @@
+//! # SetNonMembershipChip: Sorted-Neighbour Set Non-Membership for Halo2
+//!
+//! Complements `SetMembershipChip` / `SetMembershipLookupChip`: proves that `input_value`
+//! is **not** in a sorted set. The prover supplies two adjacent elements of the set and
+//! their position, and the chip checks
+//!
+//! - `lo < input < hi` with strict comparisons (`input - lo - 1` and `hi - input - 1`
+//!   are range-checked to `num_bits` with `LimbRangeCheckChip`), and
+//! - `lo` and `hi` are consecutive in the set.
+//!
+//! The set is extended with the sentinels `-1` and `2^num_bits`, so every value in
+//! `[0, 2^num_bits)` that is not a member has a pair of neighbours:
+//!
+//! ```text
+//! extended = [-1, s_0, s_1, ..., s_{n-1}, 2^num_bits]
+//! (lo, hi) = (extended[index], extended[index + 1])
+//! ```
+//!
+//! Consecutiveness depends on the set source (see `SetSource`):
+//!
+//! - `SetSource::Fixed`: the extended set is loaded into a `(tag, index, value)` lookup table,
+//!   and `(index, lo)` and `(index + 1, hi)` are both looked up.
+//! - `SetSource::Instance`: the extended set is laid out row by row next to a one-hot
+//!   `neighbour` column. The selected row must hold `lo` and the following row `hi`.
+//!   `index` is not constrained in this mode: it only tells the prover which row to
+//!   select, and any row holding the pair is equally valid.
+//!   The verifier is responsible for supplying a strictly increasing set.
+//!
+//! As in the membership chips, everything is enforced only when `flag == 1`.
+//!
+//! ```rust
+//! // In configure()
+//! let columns = SetNonMembershipColumns { advice, source: SetSource::Fixed, num_bits: 32 };
+//! let config = SetNonMembershipChip::configure(meta, columns);
+//!
+//! // In synthesize()
+//! let chip = SetNonMembershipChip::construct(config);
+//! chip.load_sorted_set(&mut layouter, &revoked_ids)?;
+//! let flag = chip.assign(layouter.namespace(|| "not revoked"), &[input, lo, hi, index, flag])?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+use halo2_gadgets::utilities::bool_check;
+
+use super::gadget::Gadget;
+use super::limb_range_check::{LimbRangeCheckChip, LimbRangeCheckColumns, LimbRangeCheckConfig};
+use super::set_membership_lookup::SetSource;
+use crate::utils::fe_to_u128;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SetNonMembershipColumns {
+    /// `[input, lo, hi, index, flag, lo_gap, hi_gap]`
+    pub advice: [Column<Advice>; 7],
+    pub source: SetSource,
+    /// Members and inputs are assumed to be below `2^num_bits`.
+    pub num_bits: usize,
+}
+
+/// Source-specific columns of the chip.
+#[derive(Clone, Debug)]
+pub enum SortedSetSourceConfig {
+    Fixed {
+        selector: Selector,
+        // Row 0 of the table is the (0, 0, 0) default row used by disabled lookups.
+        table_tag: TableColumn,
+        table_index: TableColumn,
+        table_value: TableColumn,
+    },
+    Instance {
+        instance: Column<Instance>,
+        size: usize,
+        set_column: Column<Advice>,
+        neighbour: Column<Advice>,
+        accumulator: Column<Advice>,
+        s_lower: Selector,
+        s_upper: Selector,
+        s_first: Selector,
+        s_step: Selector,
+        s_last: Selector,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct SetNonMembershipConfig {
+    pub input_value: Column<Advice>,
+    pub lo: Column<Advice>,
+    pub hi: Column<Advice>,
+    pub index: Column<Advice>,
+    pub flag: Column<Advice>,
+    pub lo_gap: Column<Advice>,
+    pub hi_gap: Column<Advice>,
+    pub num_bits: usize,
+    pub selector: Selector,
+    pub source: SortedSetSourceConfig,
+    pub range_check_config: LimbRangeCheckConfig,
+}
+
+pub struct SetNonMembershipChip<F: PrimeField> {
+    pub config: SetNonMembershipConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> SetNonMembershipChip<F> {
+    /// Upper sentinel `2^num_bits`.
+    fn upper_sentinel(num_bits: usize) -> F {
+        F::from_u128(1u128 << num_bits)
+    }
+
+    /// Native helper for the prover: `(index, lo, hi)` of `value` in the extended set.
+    ///
+    /// If `value` is a member, the returned neighbours do not satisfy `lo < value < hi`.
+    pub fn neighbours(sorted_set: &[F], value: &F, num_bits: usize) -> (F, F, F) {
+        let value = fe_to_u128(value);
+        let position = sorted_set
+            .iter()
+            .take_while(|member| match (fe_to_u128(*member), value) {
+                (Some(member), Some(value)) => member < value,
+                (_, None) => true,
+                (None, Some(_)) => false,
+            })
+            .count();
+
+        let lo = if position == 0 { -F::ONE } else { sorted_set[position - 1] };
+        let hi = sorted_set.get(position).copied().unwrap_or(Self::upper_sentinel(num_bits));
+        (F::from(position as u64), lo, hi)
+    }
+
+    /// Load a compile-time sorted set into the lookup table. Only used with `SetSource::Fixed`.
+    ///
+    /// Fails if the set is not strictly increasing or has a member `>= 2^num_bits`.
+    pub fn load_sorted_set(&self, layouter: &mut impl Layouter<F>, sorted_set: &[F]) -> Result<(), Error> {
+        let (table_tag, table_index, table_value) = match &self.config.source {
+            SortedSetSourceConfig::Fixed { table_tag, table_index, table_value, .. } => {
+                (*table_tag, *table_index, *table_value)
+            }
+            SortedSetSourceConfig::Instance { .. } => return Err(Error::Synthesis),
+        };
+
+        let bound = 1u128 << self.config.num_bits;
+        let members: Option<Vec<u128>> = sorted_set.iter().map(fe_to_u128).collect();
+        let members = members.ok_or(Error::Synthesis)?;
+        if members.iter().any(|m| *m >= bound) || members.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(Error::Synthesis);
+        }
+
+        let extended: Vec<F> = std::iter::once(-F::ONE)
+            .chain(sorted_set.iter().copied())
+            .chain(std::iter::once(Self::upper_sentinel(self.config.num_bits)))
+            .collect();
+
+        layouter.assign_table(
+            || "sorted set table",
+            |mut table| {
+                table.assign_cell(|| "default tag", table_tag, 0, || Value::known(F::ZERO))?;
+                table.assign_cell(|| "default index", table_index, 0, || Value::known(F::ZERO))?;
+                table.assign_cell(|| "default value", table_value, 0, || Value::known(F::ZERO))?;
+                for (i, value) in extended.iter().enumerate() {
+                    table.assign_cell(|| format!("tag[{}]", i), table_tag, i + 1, || Value::known(F::ONE))?;
+                    table.assign_cell(|| format!("index[{}]", i), table_index, i + 1, || Value::known(F::from(i as u64)))?;
+                    table.assign_cell(|| format!("set[{}]", i), table_value, i + 1, || Value::known(*value))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Lay out the extended instance set and select the `(lo, hi)` pair. `index` only
+    /// places the one-hot bit in the witness; the constraints do not refer to it.
+    fn assign_instance_neighbours(
+        &self,
+        mut layouter: impl Layouter<F>,
+        lo: &AssignedCell<F, F>,
+        hi: &AssignedCell<F, F>,
+        index: &AssignedCell<F, F>,
+        flag: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        let SortedSetSourceConfig::Instance {
+            instance, size, set_column, neighbour, accumulator, s_lower, s_upper, s_first, s_step, s_last,
+        } = &config.source
+        else {
+            return Err(Error::Synthesis);
+        };
+
+        layouter.assign_region(
+            || "sorted set neighbours",
+            |mut region| {
+                // extended[0] = -1, extended[1..=size] = instance rows, extended[size + 1] = 2^num_bits
+                s_lower.enable(&mut region, 0)?;
+                region.assign_advice(|| "lower sentinel", *set_column, 0, || Value::known(-F::ONE))?;
+                for i in 0..*size {
+                    region.assign_advice_from_instance(|| format!("set[{}]", i), *instance, i, *set_column, i + 1)?;
+                }
+                s_upper.enable(&mut region, size + 1)?;
+                region.assign_advice(
+                    || "upper sentinel",
+                    *set_column,
+                    size + 1,
+                    || Value::known(Self::upper_sentinel(config.num_bits)),
+                )?;
+
+                // One pair per row j = 0..=size: (extended[j], extended[j + 1]).
+                let selected = index.value().zip(flag.value()).map(|(index, flag)| {
+                    if *flag == F::ONE { fe_to_u128(index) } else { None }
+                });
+                let mut acc = Value::known(F::ZERO);
+                for j in 0..=*size {
+                    let bit = selected.map(|selected| if selected == Some(j as u128) { F::ONE } else { F::ZERO });
+                    acc = acc + bit;
+
+                    if j == 0 {
+                        s_first.enable(&mut region, j)?;
+                        lo.copy_advice(|| "lo", &mut region, config.lo, j)?;
+                        hi.copy_advice(|| "hi", &mut region, config.hi, j)?;
+                    } else {
+                        // Equality with the previous row is enforced by the step gate.
+                        s_step.enable(&mut region, j)?;
+                        region.assign_advice(|| format!("lo[{}]", j), config.lo, j, || lo.value().copied())?;
+                        region.assign_advice(|| format!("hi[{}]", j), config.hi, j, || hi.value().copied())?;
+                    }
+                    region.assign_advice(|| format!("neighbour[{}]", j), *neighbour, j, || bit)?;
+                    region.assign_advice(|| format!("acc[{}]", j), *accumulator, j, || acc)?;
+                }
+
+                s_last.enable(&mut region, *size)?;
+                flag.copy_advice(|| "flag", &mut region, config.flag, *size)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// A selected row must hold `(lo, hi)` as `(extended[j], extended[j + 1])`.
+fn neighbour_pair_constraints<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    neighbour: Column<Advice>,
+    lo: Column<Advice>,
+    hi: Column<Advice>,
+    set_column: Column<Advice>,
+) -> Vec<Expression<F>> {
+    let bit = meta.query_advice(neighbour, Rotation::cur());
+    let lo = meta.query_advice(lo, Rotation::cur());
+    let hi = meta.query_advice(hi, Rotation::cur());
+    let member_cur = meta.query_advice(set_column, Rotation::cur());
+    let member_next = meta.query_advice(set_column, Rotation::next());
+
+    vec![
+        bool_check(bit.clone()),
+        bit.clone() * (lo - member_cur),
+        bit * (hi - member_next),
+    ]
+}
+
+impl<F: PrimeField> Gadget<F> for SetNonMembershipChip<F> {
+    type Config = SetNonMembershipConfig;
+    type Columns = SetNonMembershipColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: SetNonMembershipConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: SetNonMembershipColumns) -> SetNonMembershipConfig {
+        let SetNonMembershipColumns { advice, source, num_bits } = columns;
+        assert!(num_bits < 128, "num_bits too large for the upper sentinel");
+        let [input_value, lo, hi, index, flag, lo_gap, hi_gap] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let range_check_config = LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z: lo_gap, num_bits });
+
+        let selector = match source {
+            SetSource::Fixed => meta.complex_selector(),
+            SetSource::Instance { .. } => meta.selector(),
+        };
+
+        // lo < input < hi, both gaps are zero when the check is disabled
+        meta.create_gate("set non-membership bounds", |meta| {
+            let sel = meta.query_selector(selector);
+            let input = meta.query_advice(input_value, Rotation::cur());
+            let lo = meta.query_advice(lo, Rotation::cur());
+            let hi = meta.query_advice(hi, Rotation::cur());
+            let flag = meta.query_advice(flag, Rotation::cur());
+            let lo_gap = meta.query_advice(lo_gap, Rotation::cur());
+            let hi_gap = meta.query_advice(hi_gap, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                sel.clone() * bool_check(flag.clone()),
+                sel.clone() * (lo_gap - flag.clone() * (input.clone() - lo - one.clone())),
+                sel * (hi_gap - flag * (hi - input - one)),
+            ]
+        });
+
+        let source = match source {
+            SetSource::Fixed => {
+                let table_tag = meta.lookup_table_column();
+                let table_index = meta.lookup_table_column();
+                let table_value = meta.lookup_table_column();
+
+                meta.lookup("sorted set lower neighbour", |meta| {
+                    let enabled = meta.query_selector(selector) * meta.query_advice(flag, Rotation::cur());
+                    let index = meta.query_advice(index, Rotation::cur());
+                    let lo = meta.query_advice(lo, Rotation::cur());
+
+                    vec![
+                        (enabled.clone(), table_tag),
+                        (enabled.clone() * index, table_index),
+                        (enabled * lo, table_value),
+                    ]
+                });
+
+                meta.lookup("sorted set upper neighbour", |meta| {
+                    let enabled = meta.query_selector(selector) * meta.query_advice(flag, Rotation::cur());
+                    let index = meta.query_advice(index, Rotation::cur());
+                    let hi = meta.query_advice(hi, Rotation::cur());
+
+                    vec![
+                        (enabled.clone(), table_tag),
+                        (enabled.clone() * (index + Expression::Constant(F::ONE)), table_index),
+                        (enabled * hi, table_value),
+                    ]
+                });
+
+                SortedSetSourceConfig::Fixed { selector, table_tag, table_index, table_value }
+            }
+            SetSource::Instance { instance, size } => {
+                let set_column = meta.advice_column();
+                let neighbour = meta.advice_column();
+                let accumulator = meta.advice_column();
+                let s_lower = meta.selector();
+                let s_upper = meta.selector();
+                let s_first = meta.selector();
+                let s_step = meta.selector();
+                let s_last = meta.selector();
+
+                meta.enable_equality(instance);
+                meta.enable_equality(set_column);
+
+                meta.create_gate("sorted set lower sentinel", |meta| {
+                    let s_lower = meta.query_selector(s_lower);
+                    let member = meta.query_advice(set_column, Rotation::cur());
+
+                    vec![s_lower * (member + Expression::Constant(F::ONE))]
+                });
+
+                meta.create_gate("sorted set upper sentinel", |meta| {
+                    let s_upper = meta.query_selector(s_upper);
+                    let member = meta.query_advice(set_column, Rotation::cur());
+
+                    vec![s_upper * (member - Expression::Constant(Self::upper_sentinel(num_bits)))]
+                });
+
+                meta.create_gate("sorted set neighbour start", |meta| {
+                    let s_first = meta.query_selector(s_first);
+                    let bit = meta.query_advice(neighbour, Rotation::cur());
+                    let acc = meta.query_advice(accumulator, Rotation::cur());
+
+                    let mut constraints = neighbour_pair_constraints(meta, neighbour, lo, hi, set_column);
+                    constraints.push(acc - bit);
+                    constraints.into_iter().map(|c| s_first.clone() * c).collect::<Vec<_>>()
+                });
+
+                meta.create_gate("sorted set neighbour step", |meta| {
+                    let s_step = meta.query_selector(s_step);
+                    let bit = meta.query_advice(neighbour, Rotation::cur());
+                    let acc_cur = meta.query_advice(accumulator, Rotation::cur());
+                    let acc_prev = meta.query_advice(accumulator, Rotation::prev());
+                    let lo_cur = meta.query_advice(lo, Rotation::cur());
+                    let lo_prev = meta.query_advice(lo, Rotation::prev());
+                    let hi_cur = meta.query_advice(hi, Rotation::cur());
+                    let hi_prev = meta.query_advice(hi, Rotation::prev());
+
+                    let mut constraints = neighbour_pair_constraints(meta, neighbour, lo, hi, set_column);
+                    constraints.push(acc_cur - acc_prev - bit);
+                    constraints.push(lo_cur - lo_prev);
+                    constraints.push(hi_cur - hi_prev);
+                    constraints.into_iter().map(|c| s_step.clone() * c).collect::<Vec<_>>()
+                });
+
+                // Exactly one pair is selected when the check is enabled, none otherwise.
+                meta.create_gate("sorted set neighbour result", |meta| {
+                    let s_last = meta.query_selector(s_last);
+                    let flag = meta.query_advice(flag, Rotation::cur());
+                    let acc = meta.query_advice(accumulator, Rotation::cur());
+
+                    vec![s_last * (acc - flag)]
+                });
+
+                SortedSetSourceConfig::Instance {
+                    instance,
+                    size,
+                    set_column,
+                    neighbour,
+                    accumulator,
+                    s_lower,
+                    s_upper,
+                    s_first,
+                    s_step,
+                    s_last,
+                }
+            }
+        };
+
+        SetNonMembershipConfig {
+            input_value,
+            lo,
+            hi,
+            index,
+            flag,
+            lo_gap,
+            hi_gap,
+            num_bits,
+            selector,
+            source,
+            range_check_config,
+        }
+    }
+
+    /// `inputs` is `[input_value, lo, hi, index, flag]`, where `(index, lo, hi)` comes from
+    /// `SetNonMembershipChip::neighbours`. `index` is constrained only with
+    /// `SetSource::Fixed`. Returns the copied flag.
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [input_value, lo, hi, index, flag] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        let config = &self.config;
+
+        let (lo, hi, index, flag, lo_gap, hi_gap) = layouter.assign_region(
+            || "set non-membership",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                let input = input_value.copy_advice(|| "input value", &mut region, config.input_value, 0)?;
+                let lo = lo.copy_advice(|| "lo", &mut region, config.lo, 0)?;
+                let hi = hi.copy_advice(|| "hi", &mut region, config.hi, 0)?;
+                let index = index.copy_advice(|| "index", &mut region, config.index, 0)?;
+                let flag = flag.copy_advice(|| "flag", &mut region, config.flag, 0)?;
+
+                let lo_gap = region.assign_advice(
+                    || "input - lo - 1",
+                    config.lo_gap,
+                    0,
+                    || flag.value().zip(input.value()).zip(lo.value()).map(|((f, x), lo)| *f * (*x - lo - F::ONE)),
+                )?;
+                let hi_gap = region.assign_advice(
+                    || "hi - input - 1",
+                    config.hi_gap,
+                    0,
+                    || flag.value().zip(input.value()).zip(hi.value()).map(|((f, x), hi)| *f * (*hi - x - F::ONE)),
+                )?;
+                Ok((lo, hi, index, flag, lo_gap, hi_gap))
+            },
+        )?;
+
+        let range_chip = LimbRangeCheckChip::construct(config.range_check_config.clone());
+        range_chip.check(layouter.namespace(|| "lo < input"), &lo_gap, config.num_bits)?;
+        range_chip.check(layouter.namespace(|| "input < hi"), &hi_gap, config.num_bits)?;
+
+        if let SortedSetSourceConfig::Instance { .. } = config.source {
+            self.assign_instance_neighbours(layouter.namespace(|| "neighbours"), &lo, &hi, &index, &flag)?;
+        }
+
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    const NUM_BITS: usize = 16;
+    const FIXED_SET_SIZE: u64 = 100;
+    const INSTANCE_SET_SIZE: usize = 20;
+
+    fn fixed_set() -> Vec<Fp> {
+        (0..FIXED_SET_SIZE).map(|i| Fp::from(1000 + 7 * i)).collect()
+    }
+
+    fn instance_set() -> Vec<Fp> {
+        (0..INSTANCE_SET_SIZE as u64).map(|i| Fp::from(500 + 3 * i)).collect()
+    }
+
+    struct NonMembershipCircuit {
+        fixed: bool,
+        input: Value<Fp>,
+        lo: Value<Fp>,
+        hi: Value<Fp>,
+        index: Value<Fp>,
+        flag: Value<Fp>,
+    }
+
+    impl NonMembershipCircuit {
+        fn new(fixed: bool, input: u64, flag: u64) -> Self {
+            let set = if fixed { fixed_set() } else { instance_set() };
+            let input = Fp::from(input);
+            let (index, lo, hi) = SetNonMembershipChip::neighbours(&set, &input, NUM_BITS);
+            Self {
+                fixed,
+                input: Value::known(input),
+                lo: Value::known(lo),
+                hi: Value::known(hi),
+                index: Value::known(index),
+                flag: Value::known(Fp::from(flag)),
+            }
+        }
+
+        fn run(&self) -> MockProver<Fp> {
+            if self.fixed {
+                MockProver::run(8, &FixedCircuit(self), vec![]).unwrap()
+            } else {
+                MockProver::run(7, &InstanceCircuit(self), vec![instance_set()]).unwrap()
+            }
+        }
+
+        fn synthesize(&self, config: SetNonMembershipConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let input = load_private(layouter.namespace(|| "input"), config.input_value, self.input)?;
+            let lo = load_private(layouter.namespace(|| "lo"), config.lo, self.lo)?;
+            let hi = load_private(layouter.namespace(|| "hi"), config.hi, self.hi)?;
+            let index = load_private(layouter.namespace(|| "index"), config.index, self.index)?;
+            let flag = load_private(layouter.namespace(|| "flag"), config.flag, self.flag)?;
+
+            let chip = SetNonMembershipChip::construct(config);
+            if self.fixed {
+                chip.load_sorted_set(&mut layouter, &fixed_set())?;
+            }
+            chip.assign(layouter.namespace(|| "non-membership"), &[input, lo, hi, index, flag])?;
+            Ok(())
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>, source: SetSource) -> SetNonMembershipConfig {
+        let columns = SetNonMembershipColumns {
+            advice: [(); 7].map(|_| meta.advice_column()),
+            source,
+            num_bits: NUM_BITS,
+        };
+        SetNonMembershipChip::configure(meta, columns)
+    }
+
+    struct FixedCircuit<'a>(&'a NonMembershipCircuit);
+
+    impl Circuit<Fp> for FixedCircuit<'_> {
+        type Config = SetNonMembershipConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            configure(meta, SetSource::Fixed)
+        }
+
+        fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    struct InstanceCircuit<'a>(&'a NonMembershipCircuit);
+
+    impl Circuit<Fp> for InstanceCircuit<'_> {
+        type Config = SetNonMembershipConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            configure(meta, SetSource::Instance { instance, size: INSTANCE_SET_SIZE })
+        }
+
+        fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    #[test]
+    fn test_fixed_set_non_membership_pass() {
+        NonMembershipCircuit::new(true, 1000 + 7 * 50 + 3, 1).run().assert_satisfied();
+        // Below the smallest and above the largest member, via the sentinels
+        NonMembershipCircuit::new(true, 0, 1).run().assert_satisfied();
+        NonMembershipCircuit::new(true, (1 << NUM_BITS) - 1, 1).run().assert_satisfied();
+    }
+
+    #[test]
+    fn test_fixed_set_member_fail() {
+        assert!(NonMembershipCircuit::new(true, 1000 + 7 * 50, 1).run().verify().is_err());
+    }
+
+    #[test]
+    fn test_fixed_set_non_adjacent_neighbours_fail() {
+        // lo and hi bracket the input but skip the member 1007 in between.
+        let circuit = NonMembershipCircuit {
+            lo: Value::known(Fp::from(1000)),
+            hi: Value::known(Fp::from(1014)),
+            index: Value::known(Fp::from(1)),
+            ..NonMembershipCircuit::new(true, 1008, 1)
+        };
+        assert!(circuit.run().verify().is_err());
+    }
+
+    #[test]
+    fn test_fixed_set_flag_off_pass() {
+        NonMembershipCircuit::new(true, 1000 + 7 * 50, 0).run().assert_satisfied();
+    }
+
+    #[test]
+    fn test_instance_set_non_membership_pass() {
+        NonMembershipCircuit::new(false, 501, 1).run().assert_satisfied();
+        NonMembershipCircuit::new(false, 400, 1).run().assert_satisfied();
+        NonMembershipCircuit::new(false, 10_000, 1).run().assert_satisfied();
+    }
+
+    #[test]
+    fn test_instance_set_member_fail() {
+        assert!(NonMembershipCircuit::new(false, 500 + 3 * 19, 1).run().verify().is_err());
+    }
+
+    #[test]
+    fn test_instance_set_non_adjacent_neighbours_fail() {
+        let circuit = NonMembershipCircuit {
+            lo: Value::known(Fp::from(500)),
+            hi: Value::known(Fp::from(506)),
+            index: Value::known(Fp::from(1)),
+            ..NonMembershipCircuit::new(false, 504, 1)
+        };
+        assert!(circuit.run().verify().is_err());
+    }
+
+    #[test]
+    fn test_instance_set_flag_off_pass() {
+        NonMembershipCircuit::new(false, 503, 0).run().assert_satisfied();
+    }
+}
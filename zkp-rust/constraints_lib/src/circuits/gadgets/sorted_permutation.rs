@@ -0,0 +1,413 @@
+//! # SortedPermutationChip: Prove a Vector is a Sorted Permutation of Another
+//!
+//! Given cells `input = [x_0, ..., x_{n-1}]`, the chip witnesses `sorted = [y_0, ..., y_{n-1}]`
+//! and proves
+//!
+//! 1. **Permutation** with a grand-product argument over a challenge `gamma`:
+//!
+//!    ```text
+//!    z_0 = 1
+//!    z_{i+1} * (y_i + gamma) = z_i * (x_i + gamma)
+//!    z_n = 1
+//!    ```
+//!
+//!    i.e. `prod(x_i + gamma) = prod(y_i + gamma)`. The chip derives `gamma` itself, once
+//!    both vectors are assigned, as the Poseidon (`P128Pow5T3`) hash chain
+//!    `H(...H(H(x_0, x_1), x_2)..., y_{n-1})` over the input and sorted cells. A prover
+//!    who could fix `gamma` first (a witness, or a public input it knows in advance)
+//!    could solve for a non-permutation with the same product; with `gamma` depending
+//!    on `sorted`, changing `sorted` changes `gamma`.
+//!
+//! 2. **Sortedness**: `y_{i+1} - y_i` (minus one if `strict`) and `y_0` are range-checked
+//!    to `num_bits` with `LimbRangeCheckChip`. With `strict = true` this also proves that
+//!    all elements are distinct (deduplication).
+//!
+//! ```rust
+//! // In configure()
+//! let columns = SortedPermutationColumns { advice, constants, num_bits: 64, strict: false };
+//! let config = SortedPermutationChip::configure(meta, columns);
+//!
+//! // In synthesize()
+//! let chip = SortedPermutationChip::construct(config);
+//! let sorted = chip.sort(layouter.namespace(|| "sort"), &inputs)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+use halo2_gadgets::poseidon::{Hash, Pow5Chip, Pow5Config};
+use halo2_gadgets::poseidon::primitives::{ConstantLength, P128Pow5T3, Spec};
+
+use super::gadget::Gadget;
+use super::limb_range_check::{LimbRangeCheckChip, LimbRangeCheckColumns, LimbRangeCheckConfig};
+use crate::utils::fe_to_u128;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SortedPermutationColumns {
+    /// `[input, sorted, gamma, z, diff]`. The first four are also the Poseidon state and
+    /// partial S-box columns.
+    pub advice: [Column<Advice>; 5],
+    /// Poseidon round constants `[rc_a; 3]` then `[rc_b; 3]`
+    pub constants: [Column<Fixed>; 6],
+    pub num_bits: usize,
+    /// Require strictly increasing output (no duplicates).
+    pub strict: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct SortedPermutationConfig<F: PrimeField> {
+    pub input: Column<Advice>,
+    pub sorted: Column<Advice>,
+    pub gamma: Column<Advice>,
+    pub z: Column<Advice>,
+    pub diff: Column<Advice>,
+    pub num_bits: usize,
+    pub strict: bool,
+    pub s_first: Selector,
+    pub s_product: Selector,
+    pub s_last: Selector,
+    pub s_sorted: Selector,
+    pub range_check_config: LimbRangeCheckConfig,
+    pub poseidon_config: Pow5Config<F, 3, 2>,
+}
+
+pub struct SortedPermutationChip<F: PrimeField> {
+    pub config: SortedPermutationConfig<F>,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> SortedPermutationChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    /// Witness the sorted permutation of `input` and return its cells in ascending order.
+    pub fn sort(
+        &self,
+        layouter: impl Layouter<F>,
+        input: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let values = input.iter().fold(Value::known(Vec::with_capacity(input.len())), |acc, cell| {
+            acc.zip(cell.value().copied()).map(|(mut acc, value)| {
+                acc.push(value);
+                acc
+            })
+        });
+        // Values above 128 bits sort last; they fail the range checks anyway.
+        let sorted = values.map(|mut values| {
+            values.sort_by_key(|value| fe_to_u128(value).unwrap_or(u128::MAX));
+            values
+        });
+        let sorted: Vec<Value<F>> = (0..input.len()).map(|i| sorted.as_ref().map(|sorted| sorted[i])).collect();
+
+        self.check_sorted_permutation(layouter, input, &sorted)
+    }
+
+    /// Prove that `sorted` (witnessed by the caller) is a sorted permutation of `input`.
+    pub fn check_sorted_permutation(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &[AssignedCell<F, F>],
+        sorted: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = &self.config;
+        let n = input.len();
+        if n == 0 || sorted.len() != n {
+            return Err(Error::Synthesis);
+        }
+        let one = if config.strict { F::ONE } else { F::ZERO };
+
+        let sorted = layouter.assign_region(
+            || "sorted witness",
+            |mut region| {
+                sorted
+                    .iter()
+                    .enumerate()
+                    .map(|(i, y)| region.assign_advice(|| format!("sorted[{}]", i), config.sorted, i, || *y))
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+        let gamma = self.challenge(layouter.namespace(|| "gamma"), input, &sorted)?;
+
+        let diffs = layouter.assign_region(
+            || "sorted permutation",
+            |mut region| {
+                let gamma_value = gamma.value().copied();
+                let mut z = Value::known(F::ONE);
+                let mut diffs = Vec::with_capacity(n - 1);
+
+                config.s_first.enable(&mut region, 0)?;
+                for (i, (x, y)) in input.iter().zip(&sorted).enumerate() {
+                    config.s_product.enable(&mut region, i)?;
+                    let x = x.copy_advice(|| format!("input[{}]", i), &mut region, config.input, i)?;
+                    let y = y.copy_advice(|| format!("sorted[{}]", i), &mut region, config.sorted, i)?;
+                    if i == 0 {
+                        gamma.copy_advice(|| "gamma", &mut region, config.gamma, i)?;
+                    } else {
+                        region.assign_advice(|| format!("gamma[{}]", i), config.gamma, i, || gamma_value)?;
+                    }
+                    region.assign_advice(|| format!("z[{}]", i), config.z, i, || z)?;
+
+                    // z_{i+1} = z_i * (x_i + gamma) / (y_i + gamma)
+                    z = z
+                        .zip(x.value().copied())
+                        .zip(y.value().copied())
+                        .zip(gamma_value)
+                        .map(|(((z, x), y), gamma)| z * (x + gamma) * (y + gamma).invert().unwrap_or(F::ZERO));
+                }
+                region.assign_advice(|| format!("gamma[{}]", n), config.gamma, n, || gamma_value)?;
+                region.assign_advice(|| format!("z[{}]", n), config.z, n, || z)?;
+                config.s_last.enable(&mut region, n)?;
+
+                for i in 0..n - 1 {
+                    config.s_sorted.enable(&mut region, i)?;
+                    let diff = sorted[i + 1].value().zip(sorted[i].value()).map(|(next, cur)| *next - cur - one);
+                    diffs.push(region.assign_advice(|| format!("diff[{}]", i), config.diff, i, || diff)?);
+                }
+
+                Ok(diffs)
+            },
+        )?;
+
+        let range_chip = LimbRangeCheckChip::construct(config.range_check_config.clone());
+        range_chip.check(layouter.namespace(|| "sorted[0] range"), &sorted[0], config.num_bits)?;
+        for (i, diff) in diffs.iter().enumerate() {
+            range_chip.check(layouter.namespace(|| format!("sorted[{}] order", i)), diff, config.num_bits)?;
+        }
+
+        Ok(sorted)
+    }
+
+    /// `gamma`: Poseidon hash chain over the input cells, then the sorted cells.
+    fn challenge(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &[AssignedCell<F, F>],
+        sorted: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut words = input.iter().chain(sorted);
+        let mut acc = words.next().ok_or(Error::Synthesis)?.clone();
+        for (i, word) in words.enumerate() {
+            let chip = Pow5Chip::construct(self.config.poseidon_config.clone());
+            let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+                chip,
+                layouter.namespace(|| format!("init {}", i)),
+            )?;
+            acc = hasher.hash(layouter.namespace(|| format!("absorb {}", i)), [acc, word.clone()])?;
+        }
+        Ok(acc)
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for SortedPermutationChip<F>
+where
+    P128Pow5T3: Spec<F, 3, 2>,
+{
+    type Config = SortedPermutationConfig<F>;
+    type Columns = SortedPermutationColumns;
+    type Output = Vec<AssignedCell<F, F>>;
+
+    fn construct(config: SortedPermutationConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: SortedPermutationColumns) -> SortedPermutationConfig<F> {
+        let SortedPermutationColumns { advice, constants, num_bits, strict } = columns;
+        let [input, sorted, gamma, z, diff] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        // Poseidon for gamma; its state shares the input, sorted and gamma columns
+        let [rc_a0, rc_a1, rc_a2, rc_b0, rc_b1, rc_b2] = constants;
+        meta.enable_constant(rc_b0);
+        let poseidon_config = Pow5Chip::configure::<P128Pow5T3>(
+            meta,
+            [input, sorted, gamma],
+            z,
+            [rc_a0, rc_a1, rc_a2],
+            [rc_b0, rc_b1, rc_b2],
+        );
+
+        let range_check_config = LimbRangeCheckChip::configure(meta, LimbRangeCheckColumns { z: diff, num_bits });
+        let s_first = meta.selector();
+        let s_product = meta.selector();
+        let s_last = meta.selector();
+        let s_sorted = meta.selector();
+
+        meta.create_gate("grand product start", |meta| {
+            let s_first = meta.query_selector(s_first);
+            let z = meta.query_advice(z, Rotation::cur());
+
+            vec![s_first * (z - Expression::Constant(F::ONE))]
+        });
+
+        meta.create_gate("grand product step", |meta| {
+            let s_product = meta.query_selector(s_product);
+            let x = meta.query_advice(input, Rotation::cur());
+            let y = meta.query_advice(sorted, Rotation::cur());
+            let gamma_cur = meta.query_advice(gamma, Rotation::cur());
+            let gamma_next = meta.query_advice(gamma, Rotation::next());
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+
+            vec![
+                s_product.clone() * (z_next * (y + gamma_cur.clone()) - z_cur * (x + gamma_cur.clone())),
+                s_product * (gamma_next - gamma_cur),
+            ]
+        });
+
+        meta.create_gate("grand product end", |meta| {
+            let s_last = meta.query_selector(s_last);
+            let z = meta.query_advice(z, Rotation::cur());
+
+            vec![s_last * (z - Expression::Constant(F::ONE))]
+        });
+
+        meta.create_gate("sorted order", |meta| {
+            let s_sorted = meta.query_selector(s_sorted);
+            let y_cur = meta.query_advice(sorted, Rotation::cur());
+            let y_next = meta.query_advice(sorted, Rotation::next());
+            let diff = meta.query_advice(diff, Rotation::cur());
+            let one = Expression::Constant(if strict { F::ONE } else { F::ZERO });
+
+            vec![s_sorted * (diff - (y_next - y_cur - one))]
+        });
+
+        SortedPermutationConfig {
+            input,
+            sorted,
+            gamma,
+            z,
+            diff,
+            num_bits,
+            strict,
+            s_first,
+            s_product,
+            s_last,
+            s_sorted,
+            range_check_config,
+            poseidon_config,
+        }
+    }
+
+    /// `inputs` is `[x_0, ..., x_{n-1}]`. Returns the sorted cells.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        self.sort(layouter, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::gadget::load_private;
+    use group::ff::Field;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    const NUM_BITS: usize = 16;
+
+    /// If `claimed` is set it is used as the sorted witness.
+    #[derive(Default)]
+    struct SortCircuit<const STRICT: bool> {
+        input: Vec<Value<Fp>>,
+        claimed: Option<Vec<Value<Fp>>>,
+    }
+
+    impl<const STRICT: bool> Circuit<Fp> for SortCircuit<STRICT> {
+        type Config = SortedPermutationConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                input: vec![Value::unknown(); self.input.len()],
+                claimed: self.claimed.as_ref().map(|claimed| vec![Value::unknown(); claimed.len()]),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let columns = SortedPermutationColumns {
+                advice: [(); 5].map(|_| meta.advice_column()),
+                constants: [(); 6].map(|_| meta.fixed_column()),
+                num_bits: NUM_BITS,
+                strict: STRICT,
+            };
+            SortedPermutationChip::configure(meta, columns)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let input = self
+                .input
+                .iter()
+                .enumerate()
+                .map(|(i, value)| load_private(layouter.namespace(|| format!("input[{}]", i)), config.input, *value))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let chip = SortedPermutationChip::construct(config);
+            match &self.claimed {
+                Some(claimed) => chip.check_sorted_permutation(layouter.namespace(|| "check"), &input, claimed)?,
+                None => chip.sort(layouter.namespace(|| "sort"), &input)?,
+            };
+            Ok(())
+        }
+    }
+
+    fn known(values: &[u64]) -> Vec<Value<Fp>> {
+        values.iter().map(|v| Value::known(Fp::from(*v))).collect()
+    }
+
+    fn run<const STRICT: bool>(input: &[u64], claimed: Option<&[u64]>) -> MockProver<Fp> {
+        let circuit = SortCircuit::<STRICT> {
+            input: known(input),
+            claimed: claimed.map(known),
+        };
+        MockProver::run(11, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_sort_pass() {
+        run::<false>(&[30, 10, 20, 10, 0, 65535], None).assert_satisfied();
+        run::<true>(&[30, 10, 20, 0, 65535], None).assert_satisfied();
+    }
+
+    #[test]
+    fn test_duplicates_fail_when_strict() {
+        assert!(run::<true>(&[30, 10, 20, 10], None).verify().is_err());
+    }
+
+    #[test]
+    fn test_sorted_but_not_permutation_fail() {
+        assert!(run::<false>(&[30, 10, 20], Some(&[10, 20, 31])).verify().is_err());
+        assert!(run::<false>(&[30, 10, 20], Some(&[10, 10, 20])).verify().is_err());
+    }
+
+    #[test]
+    fn test_product_collision_for_fixed_gamma_fail() {
+        // [10, 20] is sorted and in range but not a permutation of [30, 10]; with
+        // gamma = -10 both products are zero, so a prover choosing gamma would pass.
+        let gamma = -Fp::from(10);
+        let product = |values: &[u64]| values.iter().map(|v| Fp::from(*v) + gamma).product::<Fp>();
+        assert_eq!(product(&[30, 10]), product(&[10, 20]));
+        assert_eq!(product(&[30, 10]), Fp::ZERO);
+
+        assert!(run::<false>(&[30, 10], Some(&[10, 20])).verify().is_err());
+    }
+
+    #[test]
+    fn test_permutation_but_not_sorted_fail() {
+        assert!(run::<false>(&[30, 10, 20], Some(&[10, 30, 20])).verify().is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_fail() {
+        assert!(run::<false>(&[(1 << NUM_BITS) + 1, 1 << NUM_BITS], None).verify().is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! # SuffixMatchChip: Prove a Private String Ends with a Public Suffix
+//!
+//! Proves statements such as "e-mail ends with `@company.com`" for a private string of
+//! `n` bytes, padded with trailing zero bytes. The suffix `s` (`m` bytes) is fixed at
+//! configuration time, so it is part of the circuit and therefore public.
+//!
+//! The prover marks the position where the suffix starts with a one-hot `start` column:
+//!
+//! ```text
+//! start_i ∈ {0, 1},  acc_i = start_0 + ... + start_i,  acc_{n-1} = flag
+//! start_i * (byte_{i+j} - s_j) = 0     for j < m      (suffix matches at i)
+//! start_i = 0                          for i > n - m  (suffix fits in the string)
+//! acc_{i-m} * byte_i = 0               for i >= m     (only padding after the suffix)
+//! ```
+//!
+//! Bytes must come from `ByteStringChip::assign_bytes` (range-checked), and the string
+//! itself must not contain zero bytes. As with the other check gadgets, the check is
+//! enforced only when `flag == 1`.
+//!
+//! ```rust
+//! // In configure()
+//! let columns = SuffixMatchColumns { advice: [byte, start, acc, flag], suffix: b"@company.com".to_vec() };
+//! let config = SuffixMatchChip::configure(meta, columns);
+//!
+//! // In synthesize()
+//! let chip = SuffixMatchChip::construct(config);
+//! let mut inputs = vec![flag];
+//! inputs.extend(email_bytes);
+//! let flag = chip.assign(layouter.namespace(|| "company e-mail"), &inputs)?;
+//! ```
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use group::ff::PrimeField;
+use halo2_gadgets::utilities::bool_check;
+
+use super::gadget::Gadget;
+use crate::utils::fe_low_bits;
+
+#[derive(Clone, Debug)]
+pub struct SuffixMatchColumns {
+    /// `[byte, start, acc, flag]`
+    pub advice: [Column<Advice>; 4],
+    pub suffix: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SuffixMatchConfig {
+    pub byte: Column<Advice>,
+    pub start: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub flag: Column<Advice>,
+    pub suffix: Vec<u8>,
+    pub s_first: Selector,
+    pub s_step: Selector,
+    pub s_window: Selector,
+    pub s_no_start: Selector,
+    pub s_tail: Selector,
+    pub s_last: Selector,
+}
+
+pub struct SuffixMatchChip<F: PrimeField> {
+    pub config: SuffixMatchConfig,
+    pub _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> SuffixMatchChip<F> {
+    /// Native helper: where `suffix` starts in the zero-padded `bytes`, if it ends the string.
+    pub fn suffix_start(bytes: &[u8], suffix: &[u8]) -> Option<usize> {
+        let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |last| last + 1);
+        bytes[..len].ends_with(suffix).then(|| len - suffix.len())
+    }
+}
+
+impl<F: PrimeField> Gadget<F> for SuffixMatchChip<F> {
+    type Config = SuffixMatchConfig;
+    type Columns = SuffixMatchColumns;
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: SuffixMatchConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, columns: SuffixMatchColumns) -> SuffixMatchConfig {
+        let SuffixMatchColumns { advice, suffix } = columns;
+        assert!(!suffix.is_empty(), "suffix must not be empty");
+        let [byte, start, acc, flag] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let s_first = meta.selector();
+        let s_step = meta.selector();
+        let s_window = meta.selector();
+        let s_no_start = meta.selector();
+        let s_tail = meta.selector();
+        let s_last = meta.selector();
+
+        meta.create_gate("suffix start first", |meta| {
+            let s_first = meta.query_selector(s_first);
+            let start = meta.query_advice(start, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+
+            vec![
+                s_first.clone() * bool_check(start.clone()),
+                s_first * (acc - start),
+            ]
+        });
+
+        meta.create_gate("suffix start step", |meta| {
+            let s_step = meta.query_selector(s_step);
+            let start = meta.query_advice(start, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+
+            vec![
+                s_step.clone() * bool_check(start.clone()),
+                s_step * (acc_cur - acc_prev - start),
+            ]
+        });
+
+        let window_suffix = suffix.clone();
+        meta.create_gate("suffix match", |meta| {
+            let s_window = meta.query_selector(s_window);
+            let start = meta.query_advice(start, Rotation::cur());
+
+            window_suffix
+                .iter()
+                .enumerate()
+                .map(|(j, expected)| {
+                    let byte = meta.query_advice(byte, Rotation(j as i32));
+                    s_window.clone() * start.clone() * (byte - Expression::Constant(F::from(*expected as u64)))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("suffix no start", |meta| {
+            let s_no_start = meta.query_selector(s_no_start);
+            let start = meta.query_advice(start, Rotation::cur());
+
+            vec![s_no_start * start]
+        });
+
+        let suffix_len = suffix.len() as i32;
+        meta.create_gate("suffix tail", |meta| {
+            let s_tail = meta.query_selector(s_tail);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let ended = meta.query_advice(acc, Rotation(-suffix_len));
+
+            vec![s_tail * ended * byte]
+        });
+
+        meta.create_gate("suffix result", |meta| {
+            let s_last = meta.query_selector(s_last);
+            let flag = meta.query_advice(flag, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+
+            vec![
+                s_last.clone() * bool_check(flag.clone()),
+                s_last * (acc - flag),
+            ]
+        });
+
+        SuffixMatchConfig {
+            byte,
+            start,
+            acc,
+            flag,
+            suffix,
+            s_first,
+            s_step,
+            s_window,
+            s_no_start,
+            s_tail,
+            s_last,
+        }
+    }
+
+    /// `inputs` is `[flag, byte_0, ..., byte_{n-1}]` with `n >= suffix.len()`.
+    /// Returns the copied flag.
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [flag, bytes @ ..] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        let config = &self.config;
+        let n = bytes.len();
+        let m = config.suffix.len();
+        if n < m {
+            return Err(Error::Synthesis);
+        }
+
+        let byte_values = bytes.iter().fold(Value::known(Vec::with_capacity(n)), |acc, cell| {
+            acc.zip(cell.value().copied()).map(|(mut acc, byte)| {
+                acc.push(fe_low_bits(&byte, 8) as u8);
+                acc
+            })
+        });
+        let suffix_start = byte_values
+            .zip(flag.value().copied())
+            .map(|(bytes, flag)| if flag == F::ONE { Self::suffix_start(&bytes, &config.suffix) } else { None });
+
+        layouter.assign_region(
+            || "suffix match",
+            |mut region| {
+                let mut acc = Value::known(F::ZERO);
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i == 0 {
+                        config.s_first.enable(&mut region, i)?;
+                    } else {
+                        config.s_step.enable(&mut region, i)?;
+                    }
+                    if i <= n - m {
+                        config.s_window.enable(&mut region, i)?;
+                    } else {
+                        config.s_no_start.enable(&mut region, i)?;
+                    }
+                    if i >= m {
+                        config.s_tail.enable(&mut region, i)?;
+                    }
+
+                    byte.copy_advice(|| format!("byte[{}]", i), &mut region, config.byte, i)?;
+                    let start = suffix_start.map(|start| if start == Some(i) { F::ONE } else { F::ZERO });
+                    region.assign_advice(|| format!("start[{}]", i), config.start, i, || start)?;
+                    acc = acc + start;
+                    region.assign_advice(|| format!("acc[{}]", i), config.acc, i, || acc)?;
+                }
+
+                config.s_last.enable(&mut region, n - 1)?;
+                flag.copy_advice(|| "flag", &mut region, config.flag, n - 1)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::gadgets::byte_string::{ByteStringChip, ByteStringConfig};
+    use crate::circuits::gadgets::gadget::load_private;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    const STRING_LEN: usize = 32;
+    const SUFFIX: &[u8] = b"@company.com";
+
+    #[derive(Default)]
+    struct EmailCircuit {
+        email: Vec<Value<u8>>,
+        flag: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for EmailCircuit {
+        type Config = (SuffixMatchConfig, ByteStringConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                email: vec![Value::unknown(); STRING_LEN],
+                flag: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let suffix_config = SuffixMatchChip::configure(meta, SuffixMatchColumns { advice, suffix: SUFFIX.to_vec() });
+            let bytes_config = ByteStringChip::configure(meta, advice);
+            (suffix_config, bytes_config)
+        }
+
+        fn synthesize(&self, (suffix_config, bytes_config): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let flag = load_private(layouter.namespace(|| "flag"), suffix_config.flag, self.flag)?;
+            let bytes_chip = ByteStringChip::construct(bytes_config);
+            let email = bytes_chip.assign_bytes(layouter.namespace(|| "email"), &self.email)?;
+
+            let mut inputs = vec![flag];
+            inputs.extend(email);
+            let chip = SuffixMatchChip::construct(suffix_config);
+            chip.assign(layouter.namespace(|| "ends with suffix"), &inputs)?;
+            Ok(())
+        }
+    }
+
+    fn run(email: &str, flag: u64) -> MockProver<Fp> {
+        let mut bytes: Vec<Value<u8>> = email.bytes().map(Value::known).collect();
+        bytes.resize(STRING_LEN, Value::known(0));
+        let circuit = EmailCircuit { email: bytes, flag: Value::known(Fp::from(flag)) };
+        MockProver::run(9, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_email_suffix_pass() {
+        run("alice@company.com", 1).assert_satisfied();
+        // Suffix exactly at the end of the slot
+        run("twenty.chars.long.xx@company.com", 1).assert_satisfied();
+    }
+
+    #[test]
+    fn test_email_suffix_fail() {
+        assert!(run("alice@company.co", 1).verify().is_err());
+        assert!(run("alice@company.com.evil.io", 1).verify().is_err());
+        assert!(run("alice@gmail.com", 1).verify().is_err());
+    }
+
+    #[test]
+    fn test_email_suffix_flag_off_pass() {
+        run("alice@gmail.com", 0).assert_satisfied();
+    }
+}
@@ -1,2 +1,5 @@
 pub const MAX_MEMBERSHIP_NUMBER: usize = 5;
-pub const MAX_RANGE: usize = 130;
\ No newline at end of file
+pub const MAX_RANGE: usize = 130;
+pub const RANGE_CHECK_LIMB_BITS: usize = 2; // Width of each limb in LimbRangeCheckChip
+pub const MAX_DIVISION_BITS: usize = 120; // q * b + r must stay far below the field modulus
+pub const BYTES_PER_FIELD: usize = 31; // 248 bits always fit below the pasta moduli
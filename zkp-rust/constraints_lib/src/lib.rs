@@ -1,2 +1,3 @@
 pub mod constants;
 pub mod circuits;
+pub mod utils;
\ No newline at end of file
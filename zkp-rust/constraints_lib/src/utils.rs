@@ -0,0 +1,48 @@
+use group::ff::PrimeField;
+
+/// Integer value of a field element, if it fits in 128 bits.
+/// Assumes a little-endian `Repr`, as used by the pasta fields.
+pub fn fe_to_u128<F: PrimeField>(value: &F) -> Option<u128> {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    if bytes[16..].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let mut low = [0u8; 16];
+    low.copy_from_slice(&bytes[..16]);
+    Some(u128::from_le_bytes(low))
+}
+
+/// Integer `(value / divisor, value % divisor)` of a field element, by long division over
+/// its little-endian `Repr`. `None` for a zero divisor.
+pub fn fe_div_rem<F: PrimeField>(value: &F, divisor: u128) -> Option<(F, u128)> {
+    if divisor == 0 {
+        return None;
+    }
+    let repr = value.to_repr();
+    let mut quotient = F::ZERO;
+    let mut remainder = 0u128;
+    for byte in repr.as_ref().iter().rev() {
+        for i in (0..8).rev() {
+            // remainder < divisor, so 2 * remainder + bit < 2 * divisor; `carry` is its 129th bit.
+            let carry = remainder >> 127 == 1;
+            remainder = (remainder << 1) | u128::from((byte >> i) & 1);
+            let subtract = carry || remainder >= divisor;
+            if subtract {
+                remainder = remainder.wrapping_sub(divisor);
+            }
+            quotient = quotient.double() + F::from(u64::from(subtract));
+        }
+    }
+    Some((quotient, remainder))
+}
+
+/// Lowest `bits` bits (at most 64) of a field element.
+pub fn fe_low_bits<F: PrimeField>(value: &F, bits: usize) -> u64 {
+    assert!(bits <= 64);
+    let repr = value.to_repr();
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&repr.as_ref()[..8]);
+    let low = u64::from_le_bytes(low);
+    if bits == 64 { low } else { low & ((1u64 << bits) - 1) }
+}
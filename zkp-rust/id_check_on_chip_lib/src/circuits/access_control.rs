@@ -9,6 +9,7 @@ use halo2_proofs::{
 };
 
 use group::ff::PrimeField;
+use constraints_lib::circuits::gadgets::gadget::{Gadget, load_instance, load_private};
 
 use crate::constants::MAX_COUNTRY_NUMBER;
 const DUMMY_VAL: i64 = -1;
@@ -119,9 +120,12 @@ impl <F: PrimeField> Circuit<F> for AccessControlCircuit<F> {
         meta.enable_equality(required_country_codes_advice);
 
         // === Configuration Structs for Each Check ===
-        let age_check_config =  AgeCheckChip::configure(meta);
-        let gender_check_config =  GenderCheckChip::configure(meta);
-        let nationality_check_config =   NationalityCheckChip::configure(meta);
+        let age_check_config =  AgeCheckChip::configure(meta, [age, age_check_flag_advice, required_age_advice]);
+        let gender_check_config =  GenderCheckChip::configure(meta, [gender, gender_check_flag_advice, required_gender_advice]);
+        let nationality_check_config =   NationalityCheckChip::configure(
+            meta,
+            [prover_country_code, nationality_check_flag_advice, required_country_codes_advice],
+        );
 
         AccessControlCircuitConfig {
             age_check_config,
@@ -146,124 +150,79 @@ impl <F: PrimeField> Circuit<F> for AccessControlCircuit<F> {
         let gender_check_chip: GenderCheckChip<F>  = GenderCheckChip::construct(config.gender_check_config.clone());
         let nationality_check_chip: NationalityCheckChip<F>  = NationalityCheckChip::construct(config.nationality_check_config.clone());
 
-        layouter.assign_region(
-            || "age check region",
-            |mut region| {
-                // Public inputs from instance to advice
-                let flag_cell = region.assign_advice_from_instance(
-                    || "flag",
-                    config.age_check_flag_instance,
-                    0,
-                    age_check_chip.config.age_check_flag_advice,
-                    0,
-                )?;
-        
-                let required_age_cell = region.assign_advice_from_instance(
-                    || "required_age",
-                    config.required_age_instance,
-                    0,
-                    age_check_chip.config.required_age_advice,
-                    0,
-                )?;
-        
-                // Private input
-                let age_cell = region.assign_advice(
-                    || "age",
-                    age_check_chip.config.age,
-                    0,
-                    || self.prover_age.resolve_or_dummy(F::from(DUMMY_VAL as u64)),
-                )?;
-        
-                // Call assign directly with region
-                age_check_chip.assign(
-                    &mut region,
-                    0,
-                    age_cell.value().copied(),
-                    flag_cell.value().copied(),
-                    required_age_cell.value().copied(),
-                )?;
-                Ok(())
-            }
+        // === Age Check ===
+        // Public inputs from instance to advice
+        let age_flag_cell = load_instance(
+            layouter.namespace(|| "age check flag"),
+            config.age_check_flag_instance,
+            0,
+            age_check_chip.config.age_check_flag_advice,
+        )?;
+        let required_age_cell = load_instance(
+            layouter.namespace(|| "required age"),
+            config.required_age_instance,
+            0,
+            age_check_chip.config.required_age_advice,
+        )?;
+        // Private input
+        let age_cell = load_private(
+            layouter.namespace(|| "age"),
+            age_check_chip.config.age,
+            self.prover_age.resolve_or_dummy(F::from(DUMMY_VAL as u64)),
+        )?;
+        age_check_chip.assign(
+            layouter.namespace(|| "age check"),
+            &[age_cell, age_flag_cell, required_age_cell],
         )?;
 
-        layouter.assign_region(
-            || "gender check", 
-           |mut region| {
-
-            let gender_cell = region.assign_advice(
-                || "gender",
-                gender_check_chip.config.gender,
-                0,
-                || self.prover_gender.resolve_or_dummy(F::from(DUMMY_VAL as u64)),
-            )?; 
-
-            let flag_cell = region.assign_advice_from_instance(
-                || "flag",
-                config.gender_check_flag_instance,
-                0,
-                gender_check_chip.config.gender_check_flag_advice,
-                0,
-            )?;
-
-            let required_cell = region.assign_advice_from_instance(
-                || "required gender",
-                config.required_gender_instance,
-                0, 
-                gender_check_chip.config.required_gender_advice,
-                0,
-            )?;
-
-            gender_check_chip.assign(
-                &mut region,
-                0,
-                gender_cell.value().copied(),
-                flag_cell.value().copied(),
-                required_cell.value().copied(),
-            )?;
-            Ok(())
-         }
+        // === Gender Check ===
+        let gender_flag_cell = load_instance(
+            layouter.namespace(|| "gender check flag"),
+            config.gender_check_flag_instance,
+            0,
+            gender_check_chip.config.gender_check_flag_advice,
+        )?;
+        let required_gender_cell = load_instance(
+            layouter.namespace(|| "required gender"),
+            config.required_gender_instance,
+            0,
+            gender_check_chip.config.required_gender_advice,
+        )?;
+        let gender_cell = load_private(
+            layouter.namespace(|| "gender"),
+            gender_check_chip.config.gender,
+            self.prover_gender.resolve_or_dummy(F::from(DUMMY_VAL as u64)),
+        )?;
+        gender_check_chip.assign(
+            layouter.namespace(|| "gender check"),
+            &[gender_cell, gender_flag_cell, required_gender_cell],
         )?;
 
-        layouter.assign_region(
-            || "nationality_check",
-            |mut region| {
-            let nationality_cell = region.assign_advice(
-                || "nationality",
-                nationality_check_chip.config.prover_country_code,
-                0,
-                || self.prover_country_code.resolve_or_dummy(F::from(DUMMY_VAL as u64)),
-            )?; 
-
-            let flag_cell = region.assign_advice_from_instance(
-                || "flag",
-                config.nationality_check_flag_instance,
-                0,
-                nationality_check_chip.config.nationality_check_flag_advice,
-                0,
-            )?;
-
-            let mut required_values = vec![];
-            for i in 0..MAX_COUNTRY_NUMBER {
-                let cell = region.assign_advice_from_instance(
-                    || format!("required_country_{}", i),
-                    config.required_country_codes_instance,
-                    i,
-                    nationality_check_chip.config.required_country_codes_advice,
-                    i,
-                )?;
-                required_values.push(cell.value().copied());
-            }
-
-            nationality_check_chip.assign(
-                &mut region,
-                0,
-                nationality_cell.value().copied(),
-                flag_cell.value().copied(),
-                required_values,
-            )?;
-            Ok(())
+        // === Nationality Check ===
+        let nationality_flag_cell = load_instance(
+            layouter.namespace(|| "nationality check flag"),
+            config.nationality_check_flag_instance,
+            0,
+            nationality_check_chip.config.nationality_check_flag_advice,
+        )?;
+        let nationality_cell = load_private(
+            layouter.namespace(|| "nationality"),
+            nationality_check_chip.config.prover_country_code,
+            self.prover_country_code.resolve_or_dummy(F::from(DUMMY_VAL as u64)),
+        )?;
+
+        let mut nationality_inputs = vec![nationality_cell, nationality_flag_cell];
+        for i in 0..MAX_COUNTRY_NUMBER {
+            nationality_inputs.push(load_instance(
+                layouter.namespace(|| format!("required_country_{}", i)),
+                config.required_country_codes_instance,
+                i,
+                nationality_check_chip.config.required_country_codes_advice,
+            )?);
         }
-       )
+        nationality_check_chip.assign(layouter.namespace(|| "nationality check"), &nationality_inputs)?;
+
+        Ok(())
     }
 }
 
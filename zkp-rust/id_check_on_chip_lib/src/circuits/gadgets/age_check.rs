@@ -1,10 +1,11 @@
 use halo2_proofs::{
-    circuit::{Value, Region},
+    circuit::{AssignedCell, Layouter},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
     poly::Rotation
 };
-use halo2_gadgets::utilities::range_check;
+use halo2_gadgets::utilities::{bool_check, range_check};
 use group::ff::PrimeField;
+use constraints_lib::circuits::gadgets::gadget::Gadget;
 
 use crate::constants::MAX_AGE;
 
@@ -22,20 +23,24 @@ pub struct AgeCheckChip<F: PrimeField> {
     pub _marker: std::marker::PhantomData<F>,
 }
 
-impl <F: PrimeField> AgeCheckChip<F> {
-    pub fn construct(config: AgeCheckConfig) -> Self {
+impl <F: PrimeField> Gadget<F> for AgeCheckChip<F> {
+    type Config = AgeCheckConfig;
+    /// `[age, age_check_flag_advice, required_age_advice]`
+    type Columns = [Column<Advice>; 3];
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: AgeCheckConfig) -> Self {
         Self {
             config,
             _marker: std::marker::PhantomData,
         }
     }
 
-    pub fn configure(
+    fn configure(
         meta: &mut ConstraintSystem<F>,
+        columns: [Column<Advice>; 3],
     ) -> AgeCheckConfig  {
-        let age = meta.advice_column();
-        let age_check_flag_advice = meta.advice_column();
-        let required_age_advice = meta.advice_column();  // To handle the required_age value in advice column.  // To handle the flag value in advice column. 
+        let [age, age_check_flag_advice, required_age_advice] = columns;
 
         let selector = meta.selector();
 
@@ -54,7 +59,10 @@ impl <F: PrimeField> AgeCheckChip<F> {
             let diff = age - required;
             let adjusted = Expression::Constant(F::from(MAX_AGE as u64)) - diff;
 
-            vec![sel * flag * range_check(adjusted.clone(), MAX_AGE)]
+            vec![
+                sel.clone() * bool_check(flag.clone()),
+                sel * flag * range_check(adjusted.clone(), MAX_AGE),
+            ]
             //vec![Expression::Constant(F::from(1)]
         });
 
@@ -65,21 +73,26 @@ impl <F: PrimeField> AgeCheckChip<F> {
             selector,
         }
     }
-    pub fn assign(
+    /// `inputs` is `[age, flag, required_age]`. Returns the copied flag.
+    fn assign(
         &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        age: Value<F>,
-        flag: Value<F>,
-        required_age: Value<F>,
-    ) -> Result<(), Error> {
-        self.config.selector.enable(region, offset)?;
-    
-        region.assign_advice(|| "age", self.config.age, offset, || age)?;
-        region.assign_advice(|| "flag", self.config.age_check_flag_advice, offset, || flag)?;
-        region.assign_advice(|| "required_age", self.config.required_age_advice, offset, || required_age)?;
-    
-        Ok(())
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [age, flag, required_age] = inputs else {
+            return Err(Error::Synthesis);
+        };
+
+        layouter.assign_region(
+            || "age check",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+
+                age.copy_advice(|| "age", &mut region, self.config.age, 0)?;
+                required_age.copy_advice(|| "required_age", &mut region, self.config.required_age_advice, 0)?;
+                flag.copy_advice(|| "flag", &mut region, self.config.age_check_flag_advice, 0)
+            },
+        )
     }
 }
 
@@ -88,6 +101,7 @@ impl <F: PrimeField> AgeCheckChip<F> {
 mod tests {
     use super::*; // chip 코드 가져오기
     use halo2_proofs::{dev::MockProver, pasta::Fp, circuit::{Layouter, Value, SimpleFloorPlanner}, plonk::{Circuit, ConstraintSystem, Error}};
+    use constraints_lib::circuits::gadgets::gadget::load_private;
 
     struct DummyCircuit<F: PrimeField> {
         pub age: Value<F>,
@@ -108,23 +122,17 @@ mod tests {
         }
 
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            AgeCheckChip::configure(meta) // 또는 필요한 인자 넘겨주기
+            let columns = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            AgeCheckChip::configure(meta, columns)
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
-            let chip = AgeCheckChip::construct(config);
+            let age = load_private(layouter.namespace(|| "age"), config.age, self.age)?;
+            let flag = load_private(layouter.namespace(|| "flag"), config.age_check_flag_advice, self.flag)?;
+            let required_age = load_private(layouter.namespace(|| "required_age"), config.required_age_advice, self.required_age)?;
 
-            layouter.assign_region(
-                || "age check",
-                |mut region| {
-                chip.assign(
-                    &mut region,
-                    0,
-                    self.age,
-                    self.flag,
-                    self.required_age,
-                )
-            })?;
+            let chip = AgeCheckChip::construct(config);
+            chip.assign(layouter.namespace(|| "age check"), &[age, flag, required_age])?;
             Ok(())
         }
     }
@@ -167,4 +175,15 @@ mod tests {
         assert!(prover.verify().is_err()); 
     }
 
+    #[test]
+    fn test_age_check_non_boolean_flag_fail() {
+        let circuit = DummyCircuit {
+            age: Value::known(Fp::from(20)),
+            flag: Value::known(Fp::from(2)),
+            required_age: Value::known(Fp::from(18)),
+        };
+
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
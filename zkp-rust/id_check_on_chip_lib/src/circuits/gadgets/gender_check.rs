@@ -1,9 +1,11 @@
 use halo2_proofs::{
-    circuit::{Value, Region},
+    circuit::{AssignedCell, Layouter},
     plonk::{Advice, Column, ConstraintSystem, Error, Selector},
     poly::Rotation,
 };
-use group::ff::Field;
+use group::ff::PrimeField;
+use halo2_gadgets::utilities::bool_check;
+use constraints_lib::circuits::gadgets::gadget::Gadget;
 
 #[derive(Debug, Clone)]
 pub struct GenderCheckConfig {
@@ -13,26 +15,30 @@ pub struct GenderCheckConfig {
     pub selector: Selector,
 }
 
-pub struct GenderCheckChip<F: Field> {
+pub struct GenderCheckChip<F: PrimeField> {
     pub config: GenderCheckConfig,
     pub _marker: std::marker::PhantomData<F>,
 }
 
-impl <F: Field> GenderCheckChip<F> {
-    pub fn construct(config: GenderCheckConfig) -> Self {
+impl <F: PrimeField> Gadget<F> for GenderCheckChip<F> {
+    type Config = GenderCheckConfig;
+    /// `[gender, gender_check_flag_advice, required_gender_advice]`
+    type Columns = [Column<Advice>; 3];
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: GenderCheckConfig) -> Self {
         Self{
             config,
             _marker: std::marker::PhantomData,
         }
     }
 
-    pub fn configure(
+    fn configure(
         meta: &mut ConstraintSystem<F>,
+        columns: [Column<Advice>; 3],
     ) -> GenderCheckConfig {
         let selector = meta.selector();
-        let gender = meta.advice_column();
-        let gender_check_flag_advice = meta.advice_column();
-        let required_gender_advice = meta.advice_column();
+        let [gender, gender_check_flag_advice, required_gender_advice] = columns;
 
         meta.enable_equality(gender);
         meta.enable_equality(gender_check_flag_advice);
@@ -45,7 +51,10 @@ impl <F: Field> GenderCheckChip<F> {
             let required = meta.query_advice(required_gender_advice, Rotation::cur());
 
             // When required is 0, the gate is always satisfied.
-            vec![sel * flag * required.clone() * (gender.clone() - required.clone())]
+            vec![
+                sel.clone() * bool_check(flag.clone()),
+                sel * flag * required.clone() * (gender.clone() - required.clone()),
+            ]
         });
 
         GenderCheckConfig{
@@ -56,20 +65,25 @@ impl <F: Field> GenderCheckChip<F> {
         }
     }
 
-    pub fn assign(
+    /// `inputs` is `[gender, flag, required_gender]`. Returns the copied flag.
+    fn assign(
         &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        gender: Value<F>,
-        flag: Value<F>,
-        required_gender: Value<F>,
-    ) -> Result<(), Error> {
-        self.config.selector.enable(region, 0)?;
-        region.assign_advice(|| "gender", self.config.gender, offset, || gender)?;
-        region.assign_advice(|| "flag", self.config.gender_check_flag_advice, offset, || flag)?;               
-        region.assign_advice(|| "required_gender", self.config.required_gender_advice, offset, || required_gender)?;
-    
-        Ok(())
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [gender, flag, required_gender] = inputs else {
+            return Err(Error::Synthesis);
+        };
+
+        layouter.assign_region(
+            || "gender check",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                gender.copy_advice(|| "gender", &mut region, self.config.gender, 0)?;
+                required_gender.copy_advice(|| "required_gender", &mut region, self.config.required_gender_advice, 0)?;
+                flag.copy_advice(|| "flag", &mut region, self.config.gender_check_flag_advice, 0)
+            },
+        )
     }
 }
 
@@ -78,14 +92,15 @@ impl <F: Field> GenderCheckChip<F> {
 mod tests {
     use super::*; // chip 코드 가져오기
     use halo2_proofs::{dev::MockProver, pasta::Fp, circuit::{Value, SimpleFloorPlanner}, plonk::{Circuit, ConstraintSystem, Error}};
+    use constraints_lib::circuits::gadgets::gadget::load_private;
 
-    struct DummyCircuit<F: Field> {
+    struct DummyCircuit<F: PrimeField> {
         pub gender: Value<F>,
         pub gender_check_flag_advice: Value<F>,
         pub required_gender_advice: Value<F>,
     }
 
-    impl<F: Field> Circuit<F> for DummyCircuit<F> {
+    impl<F: PrimeField> Circuit<F> for DummyCircuit<F> {
         type Config = GenderCheckConfig;
         type FloorPlanner = SimpleFloorPlanner;
 
@@ -98,23 +113,17 @@ mod tests {
         }
 
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            GenderCheckChip::configure(meta) // 또는 필요한 인자 넘겨주기
+            let columns = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            GenderCheckChip::configure(meta, columns)
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl halo2_proofs::circuit::Layouter<F>) -> Result<(), Error> {
-            let chip = GenderCheckChip::construct(config);
+            let gender = load_private(layouter.namespace(|| "gender"), config.gender, self.gender)?;
+            let flag = load_private(layouter.namespace(|| "flag"), config.gender_check_flag_advice, self.gender_check_flag_advice)?;
+            let required = load_private(layouter.namespace(|| "required"), config.required_gender_advice, self.required_gender_advice)?;
 
-            layouter.assign_region(
-                || "gender check",
-                |mut region| {
-                chip.assign(
-                    &mut region,
-                    0,
-                    self.gender,
-                    self.gender_check_flag_advice,
-                    self.required_gender_advice,
-                )
-            })?;
+            let chip = GenderCheckChip::construct(config);
+            chip.assign(layouter.namespace(|| "gender check"), &[gender, flag, required])?;
             Ok(())
 
         }
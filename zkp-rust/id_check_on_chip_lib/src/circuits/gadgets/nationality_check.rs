@@ -1,11 +1,13 @@
 // Poseidon-based Bloom filter nationality check circuit in Halo2
 
 use halo2_proofs::{
-    circuit::{Region, Value},
+    circuit::{AssignedCell, Layouter, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Selector, Expression},
     poly::Rotation,
 };
 use group::ff::PrimeField;
+use halo2_gadgets::utilities::bool_check;
+use constraints_lib::circuits::gadgets::gadget::Gadget;
 
 use crate::constants::MAX_COUNTRY_NUMBER;
 
@@ -22,20 +24,24 @@ pub struct NationalityCheckChip<F: PrimeField> {
     pub _marker: std::marker::PhantomData<F>,
 }
 
-impl<F: PrimeField> NationalityCheckChip<F> {
-    pub fn construct(config: NationalityCheckConfig) -> Self {
+impl<F: PrimeField> Gadget<F> for NationalityCheckChip<F> {
+    type Config = NationalityCheckConfig;
+    /// `[prover_country_code, nationality_check_flag_advice, required_country_codes_advice]`
+    type Columns = [Column<Advice>; 3];
+    type Output = AssignedCell<F, F>;
+
+    fn construct(config: NationalityCheckConfig) -> Self {
         Self {
             config,
             _marker: std::marker::PhantomData,
         }
     }
 
-    pub fn configure(
+    fn configure(
         meta: &mut ConstraintSystem<F>,
+        columns: [Column<Advice>; 3],
     ) -> NationalityCheckConfig {
-        let prover_country_code = meta.advice_column();
-        let flag = meta.advice_column();
-        let required = meta.advice_column();
+        let [prover_country_code, flag, required] = columns;
 
         meta.enable_equality(prover_country_code);
         meta.enable_equality(flag);
@@ -54,7 +60,10 @@ impl<F: PrimeField> NationalityCheckChip<F> {
                 product = product * (prover_code.clone() - code_i);
             }
 
-            vec![sel * flag * product]
+            vec![
+                sel.clone() * bool_check(flag.clone()),
+                sel * flag * product,
+            ]
         });
 
         NationalityCheckConfig {
@@ -65,44 +74,58 @@ impl<F: PrimeField> NationalityCheckChip<F> {
         }
     }
 
-    pub fn assign(
+    /// `inputs` is `[prover_country_code, flag, required_0, .., required_k]` with at most
+    /// `MAX_COUNTRY_NUMBER` required codes; missing codes are padded with zero. Returns the copied flag.
+    fn assign(
         &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        prover_country_code: Value<F>,
-        flag: Value<F>,
-        required_countries: Vec<Value<F>>,
-    ) -> Result<(), Error> {
-        self.config.selector.enable(region, offset)?;
-            region.assign_advice(
-                || "prover_country_code",
-                self.config.prover_country_code,
-                0,
-                || prover_country_code,
-            )?;
-
-            region.assign_advice(
-                || "check flag",
-                self.config.nationality_check_flag_advice,
-                0,
-                || flag,
-            )?;
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [prover_country_code, flag, required_countries @ ..] = inputs else {
+            return Err(Error::Synthesis);
+        };
+        if required_countries.len() > MAX_COUNTRY_NUMBER {
+            return Err(Error::Synthesis);
+        }
 
-            for i in 0..MAX_COUNTRY_NUMBER {
-                region.assign_advice(
-                    || format!("required country {}", i),
-                    self.config.required_country_codes_advice,
-                    i,
-                    || {
-                        if i < required_countries.len() {
-                            required_countries[i]
-                        } else {
-                            Value::known(F::ZERO)
-                        }
-                    },
+        layouter.assign_region(
+            || "nationality check",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                prover_country_code.copy_advice(
+                    || "prover_country_code",
+                    &mut region,
+                    self.config.prover_country_code,
+                    0,
                 )?;
-            }
-            Ok(())
+
+                let flag = flag.copy_advice(
+                    || "check flag",
+                    &mut region,
+                    self.config.nationality_check_flag_advice,
+                    0,
+                )?;
+
+                for i in 0..MAX_COUNTRY_NUMBER {
+                    if i < required_countries.len() {
+                        required_countries[i].copy_advice(
+                            || format!("required country {}", i),
+                            &mut region,
+                            self.config.required_country_codes_advice,
+                            i,
+                        )?;
+                    } else {
+                        region.assign_advice(
+                            || format!("required country {}", i),
+                            self.config.required_country_codes_advice,
+                            i,
+                            || Value::known(F::ZERO),
+                        )?;
+                    }
+                }
+                Ok(flag)
+            },
+        )
     }
 }
 
@@ -111,6 +134,7 @@ impl<F: PrimeField> NationalityCheckChip<F> {
 mod tests {
     use super::*; // chip 코드 가져오기
     use halo2_proofs::{dev::MockProver, pasta::Fp, circuit::{Layouter, Value, SimpleFloorPlanner}, plonk::{Circuit, ConstraintSystem, Error}};
+    use constraints_lib::circuits::gadgets::gadget::load_private;
 
     #[derive(Default)]
     struct DummyCircuit<F: PrimeField> {
@@ -128,23 +152,25 @@ mod tests {
         }
     
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            NationalityCheckChip::configure(meta)
+            let columns = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            NationalityCheckChip::configure(meta, columns)
         }
 
         fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let mut inputs = vec![
+                load_private(layouter.namespace(|| "country code"), config.prover_country_code, self.prover_country_code)?,
+                load_private(layouter.namespace(|| "flag"), config.nationality_check_flag_advice, self.flag)?,
+            ];
+            for (i, code) in self.required.iter().enumerate() {
+                inputs.push(load_private(
+                    layouter.namespace(|| format!("required {}", i)),
+                    config.required_country_codes_advice,
+                    *code,
+                )?);
+            }
+
             let chip = NationalityCheckChip::construct(config);
-            
-            layouter.assign_region(
-                || "age check",
-                |mut region| {
-                chip.assign(
-                    &mut region,
-                    0,
-                    self.prover_country_code,
-                    self.flag,
-                    self.required.clone(),
-                )
-            })?;
+            chip.assign(layouter.namespace(|| "nationality check"), &inputs)?;
             Ok(())
         }
     }
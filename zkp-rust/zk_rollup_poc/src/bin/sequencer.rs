@@ -0,0 +1,128 @@
+//! Sequencer node: submits a few signed transactions from demo users into the mempool,
+//! produces blocks until it is empty and writes each block, its proof and the new state
+//! root to the output directory.
+//!
+//! The account state is kept in a `StateDb` under `OUT_DIR/state`, so a second run
+//! continues from the last proven block. A block is committed there before it is proven
+//! and rolled back if proving fails, or with `--mock` if MockProver rejects it. The L1
+//! deposit queue is only simulated in memory.
+//!
+//! Usage: `sequencer [OUT_DIR] [--mock]`. With `--mock` the blocks are checked with
+//! MockProver instead of being proven, and no proof files are written.
+
+use halo2_proofs::{
+    dev::MockProver,
+    pasta::{pallas, EqAffine},
+    poly::commitment::Params,
+};
+
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use rand_core::OsRng;
+
+use zk_rollup_poc_lib::constants::ACCOUNT_TREE_DEPTH;
+use zk_rollup_poc_lib::sequencer::{keygen, prove_block, write_block, Operation, Sequencer};
+use zk_rollup_poc_lib::utils::{Account, AccountTree, BridgeEntry, DepositQueue, StateDb};
+use zk_rollup_poc_lib::utils::schnorr::SigningKey;
+
+const MAX_BATCH_SIZE: usize = 4;
+const K: u32 = 15;
+const FEE: u64 = 1;
+
+fn submit(sequencer: &mut Sequencer, key: &SigningKey, op: Operation) {
+    // Client side: sign the hash of the transaction on top of the current mempool
+    let result = sequencer
+        .signing_hash(&op)
+        .and_then(|hash| sequencer.submit(op, key.public_key(), key.sign(hash)));
+    match result {
+        Ok(()) => println!("accepted {:?}", op),
+        Err(e) => println!("rejected {:?}: {}", op, e),
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mock = args.iter().any(|arg| arg == "--mock");
+    args.retain(|arg| arg != "--mock");
+    let out_dir = PathBuf::from(args.first().map(String::as_str).unwrap_or("rollup_data"));
+
+    // Genesis state: 4 users and the operator account collecting the fees
+    let keys = (0..4u64)
+        .map(|index| SigningKey::new(pallas::Scalar::from(1000 + index)))
+        .collect::<Vec<_>>();
+    let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+    for (index, key) in keys.iter().enumerate() {
+        accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+    }
+    let operator = keys.len() as u64;
+    let operator_pubkey = SigningKey::new(pallas::Scalar::from(999)).public_key().address();
+    accounts.insert(operator, Account { pubkey: operator_pubkey, balance: 0, nonce: 0 });
+
+    let mut db = StateDb::open(&out_dir.join("state"), &accounts).expect("opening the state database should not fail");
+    let latest = db.latest().unwrap();
+    println!("Resuming after block {}, state root {:?}", latest.number, latest.state_root);
+    let mut sequencer = Sequencer::resume(MAX_BATCH_SIZE, db.accounts().unwrap(), operator, latest, DepositQueue::new());
+
+    // (sender, receiver, amount), each paying FEE
+    for (sender, receiver, amount) in [(0, 1, 3), (1, 2, 5), (2, 3, 1), (3, 0, 7), (0, 2, 2)] {
+        let nonce = sequencer.pending_accounts().account(sender).nonce;
+        let op = Operation::Transfer { sender, receiver, amount, fee: FEE, nonce };
+        submit(&mut sequencer, &keys[sender as usize], op);
+    }
+    // Spends more than account 1 has left, so it is rejected
+    submit(&mut sequencer, &keys[1], Operation::Transfer { sender: 1, receiver: 0, amount: 50, fee: FEE, nonce: 1 });
+    sequencer
+        .deposit(0, BridgeEntry { pubkey: keys[0].public_key().address(), amount: 5 })
+        .unwrap();
+    let nonce = sequencer.pending_accounts().account(3).nonce;
+    submit(&mut sequencer, &keys[3], Operation::Withdrawal { account: 3, amount: 2, fee: FEE, nonce });
+
+    let params: Params<EqAffine> = Params::new(K);
+    // 경로 비트가 witness라서 모든 블록에 키 하나
+    let pk = if mock {
+        None
+    } else {
+        Some(keygen(&params, MAX_BATCH_SIZE).expect("keygen should not fail"))
+    };
+    loop {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let Some(produced) = sequencer.produce_block(timestamp).unwrap() else {
+            break;
+        };
+        let header = produced.block.header;
+        println!(
+            "block {}: {} transactions, state root {:?}",
+            header.number,
+            produced.block.transactions.len(),
+            header.state_root,
+        );
+
+        db.commit_block(&header, sequencer.accounts(), &produced.touched).unwrap();
+
+        let proof = if let Some(pk) = &pk {
+            let start = Instant::now();
+            match prove_block(&params, pk, &produced, OsRng) {
+                Ok(proof) => {
+                    println!("Proof generated and verified in {:?}", start.elapsed());
+                    Some(proof)
+                }
+                Err(e) => {
+                    db.rollback(produced.previous_header.number).unwrap();
+                    eprintln!("block {} failed to prove, rolled back: {:?}", header.number, e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            let prover = MockProver::run(K, &produced.circuit, vec![produced.public_inputs.clone()]).unwrap();
+            if let Err(failures) = prover.verify() {
+                db.rollback(produced.previous_header.number).unwrap();
+                eprintln!("block {} is not satisfied, rolled back: {:?}", header.number, failures);
+                std::process::exit(1);
+            }
+            println!("MockProver is satisfied!");
+            None
+        };
+        write_block(&out_dir, &produced, proof.as_deref()).expect("writing the block should not fail");
+    }
+    println!("State root {:?} written to {}", sequencer.header().state_root, out_dir.display());
+}
@@ -1,63 +1,110 @@
 use halo2_proofs::{
-    pasta::{Fp, EqAffine},
+    pasta::{pallas, Fp, EqAffine},
     dev::MockProver,
     plonk::{keygen_vk, keygen_pk, create_proof, verify_proof},
     poly::commitment::Params,
     transcript::{Blake2bWrite, Blake2bRead, Challenge255},
-    circuit::Value,
 };
 
 use std::time::Instant;
 use rand_core::OsRng;
-use std::convert::TryInto;
 
-use zk_rollup_poc_lib::circuits::zk_rollup_circuit::ZKRollupCircuit;
-use zk_rollup_poc_lib::utils::build_merkle_tree;
+use zk_rollup_poc_lib::circuits::zk_rollup_circuit::{TxWitness, ZKRollupCircuit};
+use zk_rollup_poc_lib::types::{Block, Transaction};
+use zk_rollup_poc_lib::utils::{Account, AccountTree, BlockHeader, BridgeEntry, DepositQueue};
+use zk_rollup_poc_lib::utils::bridge::hash_chain;
+use zk_rollup_poc_lib::utils::schnorr::SigningKey;
 use zk_rollup_poc_lib::constants::*; // Importing const
 
+const FEE: u64 = 1;
+
 fn main() {
     // -----------------------------
-    // 1. Prepare test transactions
+    // 1. Prepare accounts and test transactions
     // -----------------------------
-    let tx_aggregation = vec![
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-        [10, 5, 3, 7, 8, 0, 0],
-    ];
-
-    let tx_fp_values_vec = tx_aggregation.clone()
-    .iter()
-    .map(|tx| tx.map(|v| Value::known(Fp::from(v)))
-    ).collect::<Vec<[Value<Fp>; TX_FIELDS_COUNT]>>();
-
-    let tx_fp_values: [[Value<Fp>; TX_FIELDS_COUNT]; TX_AGGREGATION_COUNT] = tx_fp_values_vec.try_into().unwrap();
-    
-    let tx_fp_vec = tx_aggregation.clone()
-    .iter()
-    .map(|tx| tx.map(|v| Fp::from(v)))
-    .collect::<Vec<[Fp; TX_FIELDS_COUNT]>>();
-
-    let tx_fp_hashes = tx_fp_vec.clone()
-    .iter()
-    .map(|tx| build_merkle_tree(tx).last().unwrap()[0])
-    .collect::<Vec<_>>();
-
-    let merkle_tree = build_merkle_tree(&tx_fp_hashes);
-    let root_hash = merkle_tree.last().unwrap()[0];
-    
-    let zk_rollup_circuit = ZKRollupCircuit {
-        tx_aggregations: tx_fp_values.clone(),
-    };
-    let public_inputs = vec![vec![root_hash]];
+    let keys = (0..4u64)
+        .map(|index| SigningKey::new(pallas::Scalar::from(1000 + index)))
+        .collect::<Vec<_>>();
+    let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+    for (index, key) in keys.iter().enumerate() {
+        accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+    }
+    // Operator account collecting the transaction fees
+    let operator = keys.len() as u64;
+    let operator_pubkey = SigningKey::new(pallas::Scalar::from(999)).public_key().address();
+    accounts.insert(operator, Account { pubkey: operator_pubkey, balance: 0, nonce: 0 });
+    let old_state_root = accounts.root();
+    let genesis = BlockHeader::genesis(old_state_root);
+
+    // (sender, receiver, amount), each paying FEE
+    let transfers = [(0, 1, 3), (1, 2, 5), (2, 3, 1), (3, 0, 7), (0, 2, 2)];
+    let witnesses = transfers
+        .iter()
+        .map(|(sender, receiver, amount)| {
+            let nonce = accounts.account(*sender).nonce;
+            accounts.transfer(*sender, *receiver, *amount, FEE, nonce).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let mut total_fees = witnesses.iter().map(|witness| witness.fee).sum::<u64>();
+
+    let mut block_transactions = witnesses.iter().map(Transaction::transfer).collect::<Vec<_>>();
+
+    // Each sender signs the hash of its transaction
+    let mut transactions = witnesses
+        .iter()
+        .zip(transfers.iter())
+        .map(|(witness, (sender, _, _))| TxWitness::transfer(witness, &keys[*sender as usize]))
+        .collect::<Vec<_>>();
+
+    // Deposit into account 0 from the L1 queue
+    let mut deposit_queue = DepositQueue::new();
+    deposit_queue.push(BridgeEntry { pubkey: keys[0].public_key().address(), amount: 5 });
+    let deposit_chain_before = deposit_queue.consumed_chain();
+    let deposit = accounts.deposit(0, &deposit_queue.consume(1)[0]).unwrap();
+    block_transactions.push(Transaction::deposit(&deposit));
+    transactions.push(TxWitness::deposit(&deposit));
+
+    // Withdrawal from account 3 to L1
+    let (withdrawer, withdrawn) = (3, 2);
+    let nonce = accounts.account(withdrawer).nonce;
+    let withdrawal = accounts.withdraw(withdrawer, withdrawn, FEE, nonce).unwrap();
+    block_transactions.push(Transaction::withdrawal(&withdrawal, withdrawn, FEE));
+    transactions.push(TxWitness::withdrawal(&withdrawal, withdrawn, FEE, &keys[withdrawer as usize]));
+    let withdrawals_hash = hash_chain(Fp::zero(), &[BridgeEntry { pubkey: withdrawal.before.pubkey, amount: withdrawn }]);
+    total_fees += FEE;
+
+    let operator_update = accounts.collect_fees(operator, total_fees).unwrap();
+    let new_state_root = accounts.root();
+    let actual_tx_count = Fp::from(transactions.len() as u64);
+    let timestamp = 1_700_000_000;
+    let mut block = Block { header: genesis, batch_size: TX_AGGREGATION_COUNT as u64, transactions: block_transactions };
+    let root_hash = block.tx_root();
+    block.header = genesis.next(timestamp, new_state_root, root_hash);
+
+    // Unused slots are filled with noop transactions
+    let zk_rollup_circuit = ZKRollupCircuit::new(
+        TX_AGGREGATION_COUNT,
+        transactions,
+        (&operator_update).into(),
+        &genesis,
+        timestamp,
+    );
+    let public_inputs = vec![vec![
+        root_hash,
+        old_state_root,
+        new_state_root,
+        actual_tx_count,
+        operator_pubkey,
+        deposit_chain_before,
+        deposit_queue.consumed_chain(),
+        withdrawals_hash,
+        genesis.hash(),
+        block.hash(),
+    ]];
     let public_inputs_refs: Vec<&[Fp]> = public_inputs.iter().map(|v| &**v).collect();
 
     // Create a proof
-    let k = 12;
+    let k = 15;
     let params: Params<EqAffine> = Params::new(k);
     
     let prover = MockProver::run(k, &zk_rollup_circuit, public_inputs).unwrap();
@@ -0,0 +1,216 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    pasta::Fp,
+    plonk::{Advice, Column, ConstraintSystem, Error},
+};
+use halo2_gadgets::poseidon::primitives::Spec;
+
+use crate::constants::{POSEIDON_INPUTS, POSEIDON_RATE, POSEIDON_WIDTH};
+use super::merkle_tree_chip::{MerkleTreeChip, MerkleTreeConfig};
+use super::poseidon_chip::PoseidonChip;
+use crate::utils::hash_domain::AccountLeaf;
+
+/// Account leaf fields `(pubkey, balance, nonce)` assigned in the circuit.
+#[derive(Clone, Debug)]
+pub struct AccountCells {
+    pub pubkey: AssignedCell<Fp, Fp>,
+    pub balance: AssignedCell<Fp, Fp>,
+    pub nonce: AssignedCell<Fp, Fp>,
+}
+
+/// Proves updates of the sparse Merkle account tree (see `utils::sparse_merkle`).
+/// The account leaf is `H(H(pubkey, balance), nonce)` in the `AccountLeaf` domain.
+pub struct AccountTreeChip<S: Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>> {
+    config: MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>,
+    _marker: std::marker::PhantomData<S>,
+}
+
+impl<S: Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>> AccountTreeChip<S> {
+    pub fn construct(config: MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>) -> Self {
+        Self { config, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        inputs: Vec<Column<Advice>>,
+    ) -> MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS> {
+        MerkleTreeChip::<S, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::configure(meta, inputs)
+    }
+
+    /// Assigns witness values (account fields, sibling hashes, ...) one per row.
+    pub fn load_private(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Value<Fp>],
+    ) -> Result<Vec<AssignedCell<Fp, Fp>>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        region.assign_advice(|| format!("value {}", i), self.config.inputs[0], i, || *value)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    pub fn account_leaf(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        account: &AccountCells,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let chip = PoseidonChip::<S, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(
+            self.config.poseidon_config.clone(),
+        );
+        let inner = chip.hash::<AccountLeaf>(
+            layouter.namespace(|| "pubkey, balance"),
+            &[account.pubkey.clone(), account.balance.clone()],
+        )?;
+        chip.hash::<AccountLeaf>(layouter.namespace(|| "nonce"), &[inner, account.nonce.clone()])
+    }
+
+    /// Proves that `before` is the leaf at `path_bits` under `old_root` and returns the
+    /// root obtained by replacing it with `after` along the same path.
+    pub fn update(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        old_root: &AssignedCell<Fp, Fp>,
+        before: &AccountCells,
+        after: &AccountCells,
+        siblings: &[AssignedCell<Fp, Fp>],
+        path_bits: &[AssignedCell<Fp, Fp>],
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let old_leaf = self.account_leaf(layouter.namespace(|| "old leaf"), before)?;
+        let new_leaf = self.account_leaf(layouter.namespace(|| "new leaf"), after)?;
+        self.merkle_chip()
+            .update_leaf(layouter.namespace(|| "update"), old_leaf, new_leaf, siblings, path_bits, old_root)
+    }
+
+    /// Roots of the tree holding `before` and `after` at `path_bits`, without checking
+    /// either of them. Lets the caller decide when the old root has to match.
+    pub fn compute_roots(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        before: &AccountCells,
+        after: &AccountCells,
+        siblings: &[AssignedCell<Fp, Fp>],
+        path_bits: &[AssignedCell<Fp, Fp>],
+    ) -> Result<(AssignedCell<Fp, Fp>, AssignedCell<Fp, Fp>), Error> {
+        let old_leaf = self.account_leaf(layouter.namespace(|| "old leaf"), before)?;
+        let new_leaf = self.account_leaf(layouter.namespace(|| "new leaf"), after)?;
+        self.merkle_chip()
+            .compute_update_roots(layouter.namespace(|| "roots"), old_leaf, new_leaf, siblings, path_bits)
+    }
+
+    fn merkle_chip(&self) -> MerkleTreeChip<S, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS> {
+        MerkleTreeChip::construct(self.config.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, Instance},
+    };
+    use halo2_gadgets::poseidon::primitives::P128Pow5T3;
+    use crate::utils::{Account, AccountTree};
+
+    const DEPTH: usize = 4;
+
+    #[derive(Default)]
+    struct AccountUpdateCircuit {
+        before: [Value<Fp>; 3],
+        new_balance: Value<Fp>,
+        siblings: Vec<Value<Fp>>,
+        path_bits: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for AccountUpdateCircuit {
+        type Config = (MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                before: [Value::unknown(); 3],
+                new_balance: Value::unknown(),
+                siblings: vec![Value::unknown(); self.siblings.len()],
+                path_bits: vec![Value::unknown(); self.path_bits.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let inputs = (0..3).map(|_| meta.advice_column()).collect();
+            (AccountTreeChip::<P128Pow5T3>::configure(meta, inputs), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = AccountTreeChip::<P128Pow5T3>::construct(config.clone());
+
+            // 계정 필드와 경로 할당
+            let fields = chip.load_private(layouter.namespace(|| "account"), &self.before)?;
+            let new_balance = chip.load_private(layouter.namespace(|| "new balance"), &[self.new_balance])?;
+            let siblings = chip.load_private(layouter.namespace(|| "siblings"), &self.siblings)?;
+            let path_bits = chip.load_private(layouter.namespace(|| "path bits"), &self.path_bits)?;
+            let old_root = layouter.assign_region(
+                || "old root",
+                |mut region| region.assign_advice_from_instance(|| "old root", instance, 0, config.inputs[0], 0),
+            )?;
+
+            let before = AccountCells {
+                pubkey: fields[0].clone(),
+                balance: fields[1].clone(),
+                nonce: fields[2].clone(),
+            };
+            let after = AccountCells { balance: new_balance[0].clone(), ..before.clone() };
+            let new_root = chip.update(
+                layouter.namespace(|| "update"),
+                &old_root,
+                &before,
+                &after,
+                &siblings,
+                &path_bits,
+            )?;
+            layouter.constrain_instance(new_root.cell(), instance, 1)
+        }
+    }
+
+    fn run(new_balance: u64, claimed_new_balance: u64) -> MockProver<Fp> {
+        let mut accounts = AccountTree::new(DEPTH);
+        accounts.insert(3, Account { pubkey: Fp::from(33), balance: 10, nonce: 0 });
+        accounts.insert(9, Account { pubkey: Fp::from(99), balance: 20, nonce: 1 });
+        let old_root = accounts.root();
+        let update = accounts.update(9, |account| account.balance = new_balance);
+
+        let circuit = AccountUpdateCircuit {
+            before: [
+                update.before.pubkey,
+                Fp::from(update.before.balance),
+                Fp::from(update.before.nonce),
+            ]
+            .map(Value::known),
+            new_balance: Value::known(Fp::from(claimed_new_balance)),
+            siblings: update.siblings.iter().copied().map(Value::known).collect(),
+            path_bits: update.path_bits.iter().map(|bit| Value::known(Fp::from(*bit as u64))).collect(),
+        };
+        MockProver::run(11, &circuit, vec![vec![old_root, accounts.root()]]).unwrap()
+    }
+
+    #[test]
+    fn test_account_update_pass() {
+        run(25, 25).assert_satisfied();
+    }
+
+    #[test]
+    fn test_account_update_wrong_new_root_fail() {
+        // 회로가 계산한 새 루트가 공개된 루트와 다름
+        assert!(run(25, 26).verify().is_err());
+    }
+}
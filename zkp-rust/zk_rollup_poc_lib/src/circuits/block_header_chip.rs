@@ -0,0 +1,146 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    pasta::Fp,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use halo2_gadgets::poseidon::primitives::Spec;
+
+use crate::constants::{POSEIDON_INPUTS, POSEIDON_RATE, POSEIDON_WIDTH};
+use crate::utils::BlockHeader;
+use super::poseidon_chip::{PoseidonChip, PoseidonConfig};
+use crate::utils::hash_domain::HeaderHash;
+
+/// Block header fields (see `utils::BlockHeader`) as circuit witnesses.
+#[derive(Default, Clone, Debug)]
+pub struct BlockHeaderWitness {
+    pub number: Value<Fp>,
+    pub timestamp: Value<Fp>,
+    pub prev_hash: Value<Fp>,
+    pub state_root: Value<Fp>,
+    pub tx_root: Value<Fp>,
+}
+
+impl From<&BlockHeader> for BlockHeaderWitness {
+    fn from(header: &BlockHeader) -> Self {
+        Self {
+            number: Value::known(Fp::from(header.number)),
+            timestamp: Value::known(Fp::from(header.timestamp)),
+            prev_hash: Value::known(header.prev_hash),
+            state_root: Value::known(header.state_root),
+            tx_root: Value::known(header.tx_root),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BlockHeaderCells {
+    pub number: AssignedCell<Fp, Fp>,
+    pub timestamp: AssignedCell<Fp, Fp>,
+    pub prev_hash: AssignedCell<Fp, Fp>,
+    pub state_root: AssignedCell<Fp, Fp>,
+    pub tx_root: AssignedCell<Fp, Fp>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BlockHeaderConfig {
+    pub advice: Column<Advice>,
+    /// `number` on the next row is `number` on this row plus one
+    pub s_successor: Selector,
+    pub poseidon_config: PoseidonConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>,
+}
+
+/// Hashes block headers and checks that one header follows another.
+pub struct BlockHeaderChip<S: Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>> {
+    config: BlockHeaderConfig,
+    _marker: std::marker::PhantomData<S>,
+}
+
+impl<S: Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>> BlockHeaderChip<S> {
+    pub fn construct(config: BlockHeaderConfig) -> Self {
+        Self { config, _marker: std::marker::PhantomData }
+    }
+
+    /// Reuses the caller's Poseidon config, so headers cost no extra hash columns.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: Column<Advice>,
+        poseidon_config: PoseidonConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>,
+    ) -> BlockHeaderConfig {
+        meta.enable_equality(advice);
+        let s_successor = meta.selector();
+
+        meta.create_gate("block number successor", |meta| {
+            let s_successor = meta.query_selector(s_successor);
+            let number = meta.query_advice(advice, Rotation::cur());
+            let next_number = meta.query_advice(advice, Rotation::next());
+
+            vec![s_successor * (number + Expression::Constant(Fp::one()) - next_number)]
+        });
+
+        BlockHeaderConfig { advice, s_successor, poseidon_config }
+    }
+
+    /// Assigns witness values one per row.
+    pub fn load_private(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Value<Fp>],
+    ) -> Result<Vec<AssignedCell<Fp, Fp>>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| region.assign_advice(|| format!("value {}", i), self.config.advice, i, || *value))
+                    .collect()
+            },
+        )
+    }
+
+    pub fn load(&self, layouter: impl Layouter<Fp>, header: &BlockHeaderWitness) -> Result<BlockHeaderCells, Error> {
+        let cells = self.load_private(
+            layouter,
+            &[header.number, header.timestamp, header.prev_hash, header.state_root, header.tx_root],
+        )?;
+        let [number, timestamp, prev_hash, state_root, tx_root]: [AssignedCell<Fp, Fp>; 5] =
+            cells.try_into().unwrap();
+        Ok(BlockHeaderCells { number, timestamp, prev_hash, state_root, tx_root })
+    }
+
+    /// `H(H(H(H(number, timestamp), prev_hash), state_root), tx_root)`, as `BlockHeader::hash`.
+    pub fn hash(&self, mut layouter: impl Layouter<Fp>, header: &BlockHeaderCells) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let chip = PoseidonChip::<S, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(
+            self.config.poseidon_config.clone(),
+        );
+        let mut hash = chip.hash::<HeaderHash>(
+            layouter.namespace(|| "number, timestamp"),
+            &[header.number.clone(), header.timestamp.clone()],
+        )?;
+        for (name, field) in [("prev hash", &header.prev_hash), ("state root", &header.state_root), ("tx root", &header.tx_root)] {
+            hash = chip.hash::<HeaderHash>(layouter.namespace(|| name), &[hash, field.clone()])?;
+        }
+        Ok(hash)
+    }
+
+    /// Constrains `next` to be the header after the one hashing to `prev_hash`: it links
+    /// to that hash and its number is one more than `prev.number`.
+    pub fn check_successor(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        prev: &BlockHeaderCells,
+        prev_hash: &AssignedCell<Fp, Fp>,
+        next: &BlockHeaderCells,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "block successor",
+            |mut region| {
+                self.config.s_successor.enable(&mut region, 0)?;
+                prev.number.copy_advice(|| "number", &mut region, self.config.advice, 0)?;
+                next.number.copy_advice(|| "next number", &mut region, self.config.advice, 1)?;
+                region.constrain_equal(next.prev_hash.cell(), prev_hash.cell())
+            },
+        )
+    }
+}
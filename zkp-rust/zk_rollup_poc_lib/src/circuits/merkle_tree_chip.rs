@@ -1,16 +1,21 @@
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
     pasta::Fp,
-    plonk::{Advice, Column, ConstraintSystem, Error, Instance}
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
 };
 use halo2_gadgets::poseidon::primitives::Spec;
 
 use super::poseidon_chip::{PoseidonChip, PoseidonConfig};
+use crate::utils::{check_multi_proof_indices, fold_multi_proof};
+use crate::utils::hash_domain::MerkleNode;
 
 #[derive(Debug, Clone)]
 pub struct MerkleTreeConfig<const WIDTH: usize, const RATE: usize, const L: usize> {
     pub inputs: Vec<Column<Advice>>,
     pub poseidon_config: PoseidonConfig<WIDTH, RATE, L>,
+    /// Conditional swap of a path node and its sibling by the path bit
+    pub s_swap: Selector,
 }
 
 pub struct MerkleTreeChip<
@@ -29,13 +34,34 @@ MerkleTreeChip<S, WIDTH, RATE, L> {
         Self { config, _marker: std::marker::PhantomData }
     }
 
+    /// `inputs` needs at least three columns, for the swap gate.
     pub fn configure(meta: &mut ConstraintSystem<Fp>, inputs: Vec<Column<Advice>>) -> MerkleTreeConfig<WIDTH, RATE, L> {
+        assert!(inputs.len() >= 3, "MerkleTreeChip needs three advice columns");
         let poseidon_config = PoseidonChip::<S, WIDTH, RATE, L>::configure(meta);
         for input in &inputs {
             meta.enable_equality(*input);
         }
 
-        MerkleTreeConfig { inputs, poseidon_config }
+        // | current | sibling | bit |
+        // | left    | right   |     |
+        let s_swap = meta.selector();
+        meta.create_gate("conditional swap", |meta| {
+            let s_swap = meta.query_selector(s_swap);
+            let current = meta.query_advice(inputs[0], Rotation::cur());
+            let sibling = meta.query_advice(inputs[1], Rotation::cur());
+            let bit = meta.query_advice(inputs[2], Rotation::cur());
+            let left = meta.query_advice(inputs[0], Rotation::next());
+            let right = meta.query_advice(inputs[1], Rotation::next());
+
+            // bit = 1 이면 현재 노드가 오른쪽 자식
+            vec![
+                s_swap.clone() * bit.clone() * (Expression::Constant(Fp::one()) - bit.clone()),
+                s_swap.clone() * (left.clone() - current.clone() - bit * (sibling.clone() - current.clone())),
+                s_swap * (left + right - current - sibling),
+            ]
+        });
+
+        MerkleTreeConfig { inputs, poseidon_config, s_swap }
     }
 
     pub fn assign(
@@ -55,6 +81,7 @@ MerkleTreeChip<S, WIDTH, RATE, L> {
         Ok(())
     }
 
+    /// Root of the tree over `leaves`, internal nodes hashed in the `MerkleNode` domain.
     pub fn compute_merkle_root(
         &self,
         layouter: &mut impl Layouter<Fp>,
@@ -71,7 +98,7 @@ MerkleTreeChip<S, WIDTH, RATE, L> {
                 let left = current_layer[i].clone();
                 let right = current_layer[i + 1].clone();
 
-                let parent = chip.hash(
+                let parent = chip.hash::<MerkleNode>(
                     layouter.namespace(|| format!("hash({i})")),
                     &[left, right]
                 )?;
@@ -83,27 +110,71 @@ MerkleTreeChip<S, WIDTH, RATE, L> {
         Ok(current_layer[0].clone()) // Merkle Root
     }
 
-    pub fn verify_inclusion_proof(
+    /// Orders `current` and `sibling` as `[left, right]` children: swapped when `bit` is
+    /// 1, i.e. when the current node is the right child. `bit` is constrained boolean.
+    pub fn swap(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        current: &AssignedCell<Fp, Fp>,
+        sibling: &AssignedCell<Fp, Fp>,
+        bit: &AssignedCell<Fp, Fp>,
+    ) -> Result<[AssignedCell<Fp, Fp>; 2], Error> {
+        layouter.assign_region(
+            || "conditional swap",
+            |mut region| {
+                self.config.s_swap.enable(&mut region, 0)?;
+                let current = current.copy_advice(|| "current", &mut region, self.config.inputs[0], 0)?;
+                let sibling = sibling.copy_advice(|| "sibling", &mut region, self.config.inputs[1], 0)?;
+                let bit = bit.copy_advice(|| "bit", &mut region, self.config.inputs[2], 0)?;
+
+                let values = current.value().copied().zip(sibling.value().copied()).zip(bit.value().copied());
+                let left = region.assign_advice(
+                    || "left",
+                    self.config.inputs[0],
+                    1,
+                    || values.map(|((current, sibling), bit)| current + bit * (sibling - current)),
+                )?;
+                let right = region.assign_advice(
+                    || "right",
+                    self.config.inputs[1],
+                    1,
+                    || values.map(|((current, sibling), bit)| sibling + bit * (current - sibling)),
+                )?;
+                Ok([left, right])
+            },
+        )
+    }
+
+    /// Hashes `leaf` up its authentication path. `path_bits[i]` is 1 when the current
+    /// node is the right child at level `i`. The bits are witnesses, so the layout is
+    /// the same for every leaf index.
+    pub fn compute_root_from_path(
         &self,
         mut layouter: impl Layouter<Fp>,
         leaf: AssignedCell<Fp, Fp>,
         proof: &[AssignedCell<Fp, Fp>],
-        path_bits: &[bool],
-        expected_root: AssignedCell<Fp, Fp>,
-    ) -> Result<(), Error> {
+        path_bits: &[AssignedCell<Fp, Fp>],
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
         assert_eq!(proof.len(), path_bits.len());
         let chip = PoseidonChip::<S, WIDTH, RATE, L>::construct(self.config.poseidon_config.clone());
-        let mut current = leaf.clone();
+        let mut current = leaf;
 
         for (i, (sibling, bit)) in proof.iter().zip(path_bits.iter()).enumerate() {
-            let inputs = if *bit {
-                [sibling.clone(), current]
-            } else {
-                [current, sibling.clone()]
-            };
-
-            current = chip.hash(layouter.namespace(|| format!("path hash {}", i)), &inputs)?;
+            let inputs = self.swap(layouter.namespace(|| format!("path swap {}", i)), &current, sibling, bit)?;
+            current = chip.hash::<MerkleNode>(layouter.namespace(|| format!("path hash {}", i)), &inputs)?;
         }
+        Ok(current)
+    }
+
+    pub fn verify_inclusion_proof(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        leaf: AssignedCell<Fp, Fp>,
+        proof: &[AssignedCell<Fp, Fp>],
+        path_bits: &[AssignedCell<Fp, Fp>],
+        expected_root: AssignedCell<Fp, Fp>,
+    ) -> Result<(), Error> {
+        let current = self.compute_root_from_path(layouter.namespace(|| "path"), leaf, proof, path_bits)?;
         layouter.assign_region(
             || "verify root",
             |mut region| -> Result<(), Error> {
@@ -114,6 +185,93 @@ MerkleTreeChip<S, WIDTH, RATE, L> {
         Ok(())
     }
 
+    /// Root of the tree of `depth` levels holding `leaves` at `indices` (sorted,
+    /// distinct), with `proof` the siblings of `utils::get_multi_proof`. Nodes shared by
+    /// the paths are hashed once. Which nodes are shared depends on `indices`, so unlike
+    /// the path bits of `compute_root_from_path` they are part of the circuit layout.
+    /// Malformed indices or a proof with missing or extra siblings are
+    /// `Error::Synthesis`.
+    pub fn compute_multi_root(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        depth: usize,
+        indices: &[usize],
+        leaves: &[AssignedCell<Fp, Fp>],
+        proof: &[AssignedCell<Fp, Fp>],
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        check_multi_proof_indices(depth, indices, leaves.len()).map_err(|_| Error::Synthesis)?;
+        let chip = PoseidonChip::<S, WIDTH, RATE, L>::construct(self.config.poseidon_config.clone());
+        let mut siblings = proof.iter();
+        let root = fold_multi_proof(
+            depth,
+            indices,
+            leaves.to_vec(),
+            |_, _| siblings.next().cloned().ok_or(Error::Synthesis),
+            |level, index, left, right| {
+                chip.hash::<MerkleNode>(layouter.namespace(|| format!("level {level} hash {index}")), &[left, right])
+            },
+        )?;
+        if siblings.next().is_some() {
+            // 사용되지 않은 형제: 증명 형식 오류
+            return Err(Error::Synthesis);
+        }
+        Ok(root)
+    }
+
+    /// Proves that `leaves` are at `indices` in the tree of `expected_root` (see
+    /// `compute_multi_root`).
+    pub fn verify_multi_inclusion(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        depth: usize,
+        indices: &[usize],
+        leaves: &[AssignedCell<Fp, Fp>],
+        proof: &[AssignedCell<Fp, Fp>],
+        expected_root: &AssignedCell<Fp, Fp>,
+    ) -> Result<(), Error> {
+        let root = self.compute_multi_root(layouter.namespace(|| "multi root"), depth, indices, leaves, proof)?;
+        layouter.assign_region(
+            || "verify multi root",
+            |mut region| region.constrain_equal(root.cell(), expected_root.cell()),
+        )
+    }
+
+    /// Roots of the tree holding `old_leaf` and `new_leaf` at the same position. Both
+    /// paths are hashed from the one set of `proof` and `path_bits` cells, so the
+    /// leaves are proven to share every sibling. Neither root is checked.
+    pub fn compute_update_roots(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        old_leaf: AssignedCell<Fp, Fp>,
+        new_leaf: AssignedCell<Fp, Fp>,
+        proof: &[AssignedCell<Fp, Fp>],
+        path_bits: &[AssignedCell<Fp, Fp>],
+    ) -> Result<(AssignedCell<Fp, Fp>, AssignedCell<Fp, Fp>), Error> {
+        let old_root = self.compute_root_from_path(layouter.namespace(|| "old path"), old_leaf, proof, path_bits)?;
+        let new_root = self.compute_root_from_path(layouter.namespace(|| "new path"), new_leaf, proof, path_bits)?;
+        Ok((old_root, new_root))
+    }
+
+    /// Proves that `old_leaf` is the leaf at `path_bits` under `old_root` and returns
+    /// the root obtained by replacing it with `new_leaf`.
+    pub fn update_leaf(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        old_leaf: AssignedCell<Fp, Fp>,
+        new_leaf: AssignedCell<Fp, Fp>,
+        proof: &[AssignedCell<Fp, Fp>],
+        path_bits: &[AssignedCell<Fp, Fp>],
+        old_root: &AssignedCell<Fp, Fp>,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let (computed_old_root, new_root) =
+            self.compute_update_roots(layouter.namespace(|| "roots"), old_leaf, new_leaf, proof, path_bits)?;
+        layouter.assign_region(
+            || "verify old root",
+            |mut region| region.constrain_equal(computed_old_root.cell(), old_root.cell()),
+        )?;
+        Ok(new_root)
+    }
+
     pub fn expose_public(
         &self,
         layouter: &mut impl Layouter<Fp>,
@@ -131,11 +289,13 @@ mod tests {
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        pasta::Fp,
-        plonk::{Circuit, ConstraintSystem, Error},
+        pasta::{EqAffine, Fp},
+        plonk::{keygen_vk, Circuit, Column, ConstraintSystem, Error, Instance},
+        poly::commitment::Params,
     };
     use halo2_gadgets::poseidon::primitives::P128Pow5T3;
-    use crate::utils::{build_merkle_tree, get_merkle_proof};
+    use crate::utils::hash_domain::MerkleNode;
+    use crate::utils::{build_merkle_tree, get_merkle_proof, get_multi_proof};
 
     const WIDTH: usize = 3;
     const RATE: usize = 2;
@@ -145,7 +305,7 @@ mod tests {
     struct MerkleInclusionCircuit {
         leaf: Value<Fp>,
         proof: Vec<Value<Fp>>,
-        path_bits: Vec<bool>,
+        path_bits: Vec<Value<Fp>>,
         expected_root: Value<Fp>,
     }
 
@@ -157,13 +317,13 @@ mod tests {
             Self {
                 leaf: Value::unknown(),
                 proof: vec![Value::unknown(); self.proof.len()],
-                path_bits: self.path_bits.clone(),
+                path_bits: vec![Value::unknown(); self.path_bits.len()],
                 expected_root: Value::unknown(),
             }
         }
 
         fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
-            let inputs = (0..2).map(|_| meta.advice_column()).collect();
+            let inputs = (0..3).map(|_| meta.advice_column()).collect();
             MerkleTreeChip::<P128Pow5T3, WIDTH, RATE, L>::configure(meta, inputs)
         }
 
@@ -187,7 +347,7 @@ mod tests {
                 },
             )?;
 
-            // Proof 할당
+            // Proof, 경로 비트 할당
             let assigned_proof: Vec<AssignedCell<Fp, Fp>> = self.proof
                 .iter()
                 .enumerate()
@@ -205,6 +365,16 @@ mod tests {
                     )
                 })
                 .collect::<Result<_, _>>()?;
+            let assigned_bits: Vec<AssignedCell<Fp, Fp>> = self.path_bits
+                .iter()
+                .enumerate()
+                .map(|(i, bit)| {
+                    layouter.assign_region(
+                        || format!("assign path bit {}", i),
+                        |mut region| region.assign_advice(|| format!("bit {}", i), chip.config.inputs[2], 0, || *bit),
+                    )
+                })
+                .collect::<Result<_, _>>()?;
 
             // Root 할당
             let assigned_root = layouter.assign_region(
@@ -223,29 +393,233 @@ mod tests {
                 layouter,
                 assigned_leaf,
                 &assigned_proof,
-                &self.path_bits,
+                &assigned_bits,
                 assigned_root,
             )
         }
     }
 
-    #[test]
-    fn test_merkle_inclusion_proof() {
+    #[derive(Default)]
+    struct LeafUpdateCircuit {
+        old_leaf: Value<Fp>,
+        new_leaf: Value<Fp>,
+        proof: Vec<Value<Fp>>,
+        path_bits: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for LeafUpdateCircuit {
+        type Config = (MerkleTreeConfig<WIDTH, RATE, L>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                old_leaf: Value::unknown(),
+                new_leaf: Value::unknown(),
+                proof: vec![Value::unknown(); self.proof.len()],
+                path_bits: vec![Value::unknown(); self.path_bits.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let inputs = (0..3).map(|_| meta.advice_column()).collect();
+            (MerkleTreeChip::<P128Pow5T3, WIDTH, RATE, L>::configure(meta, inputs), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = MerkleTreeChip::<P128Pow5T3, WIDTH, RATE, L>::construct(config.clone());
+
+            // 행 단위로 leaf, proof, 경로 비트 할당 (old root는 instance에서)
+            let (leaves, proof, path_bits, old_root) = layouter.assign_region(
+                || "load witnesses",
+                |mut region| {
+                    let mut assign = |row: usize, column: usize, value: Value<Fp>| {
+                        region.assign_advice(|| format!("value {row}"), config.inputs[column], row, || value)
+                    };
+                    let leaves = [assign(0, 0, self.old_leaf)?, assign(0, 1, self.new_leaf)?];
+                    let proof = self.proof
+                        .iter()
+                        .enumerate()
+                        .map(|(i, sibling)| assign(i + 1, 0, *sibling))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let path_bits = self.path_bits
+                        .iter()
+                        .enumerate()
+                        .map(|(i, bit)| assign(i + 1, 1, *bit))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let old_root = region.assign_advice_from_instance(|| "old root", instance, 0, config.inputs[2], 0)?;
+                    Ok((leaves, proof, path_bits, old_root))
+                },
+            )?;
+
+            let [old_leaf, new_leaf] = leaves;
+            let new_root = chip.update_leaf(
+                layouter.namespace(|| "update leaf"),
+                old_leaf,
+                new_leaf,
+                &proof,
+                &path_bits,
+                &old_root,
+            )?;
+            chip.expose_public(&mut layouter, &new_root, instance, 1)
+        }
+    }
+
+    #[derive(Default)]
+    struct MultiInclusionCircuit {
+        // 레이아웃을 결정하므로 without_witnesses()에서도 유지
+        indices: Vec<usize>,
+        leaves: Vec<Value<Fp>>,
+        proof: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for MultiInclusionCircuit {
+        type Config = (MerkleTreeConfig<WIDTH, RATE, L>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                indices: self.indices.clone(),
+                leaves: vec![Value::unknown(); self.leaves.len()],
+                proof: vec![Value::unknown(); self.proof.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let inputs = (0..3).map(|_| meta.advice_column()).collect();
+            (MerkleTreeChip::<P128Pow5T3, WIDTH, RATE, L>::configure(meta, inputs), instance)
+        }
+
+        fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = MerkleTreeChip::<P128Pow5T3, WIDTH, RATE, L>::construct(config.clone());
+
+            let (leaves, proof, root) = layouter.assign_region(
+                || "load witnesses",
+                |mut region| {
+                    let mut load = |column: usize, values: &[Value<Fp>]| {
+                        values
+                            .iter()
+                            .enumerate()
+                            .map(|(row, value)| region.assign_advice(|| format!("value {row}"), config.inputs[column], row, || *value))
+                            .collect::<Result<Vec<_>, _>>()
+                    };
+                    let leaves = load(0, &self.leaves)?;
+                    let proof = load(1, &self.proof)?;
+                    let root = region.assign_advice_from_instance(|| "root", instance, 0, config.inputs[2], 0)?;
+                    Ok((leaves, proof, root))
+                },
+            )?;
+
+            chip.verify_multi_inclusion(layouter.namespace(|| "multi inclusion"), 3, &self.indices, &leaves, &proof, &root)
+        }
+    }
+
+    fn inclusion_circuit(leaf_index: usize) -> MerkleInclusionCircuit {
         let leaves: Vec<Fp> = (0..8).map(|i| Fp::from(i as u64)).collect(); // leaf 8개
-        let tree = build_merkle_tree(&leaves);
+        let tree = build_merkle_tree::<MerkleNode>(&leaves, Fp::zero());
         let root = tree.last().unwrap()[0];
-        let leaf_index = 3;
-        let leaf = leaves[leaf_index];
         let (proof, path_bits) = get_merkle_proof(&tree, leaf_index);
 
-        let circuit = MerkleInclusionCircuit {
-            leaf: Value::known(leaf),
+        MerkleInclusionCircuit {
+            leaf: Value::known(leaves[leaf_index]),
             proof: proof.iter().cloned().map(Value::known).collect(),
-            path_bits,
+            path_bits: path_bits.into_iter().map(|bit| Value::known(Fp::from(bit as u64))).collect(),
             expected_root: Value::known(root),
+        }
+    }
+
+    #[test]
+    fn test_merkle_inclusion_proof() {
+        for leaf_index in [0, 3, 6] {
+            let prover = MockProver::run(10, &inclusion_circuit(leaf_index), vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // 잘못된 위치의 경로 비트
+        let mut circuit = inclusion_circuit(3);
+        circuit.path_bits[0] = Value::known(Fp::zero());
+        assert!(MockProver::run(10, &circuit, vec![]).unwrap().verify().is_err());
+
+        // 불리언이 아닌 비트: left = current + 2 * (sibling - current) 로도 통과 못함
+        let mut circuit = inclusion_circuit(3);
+        circuit.path_bits[1] = Value::known(Fp::from(2));
+        assert!(MockProver::run(10, &circuit, vec![]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_one_vk_for_any_index() {
+        let params: Params<EqAffine> = Params::new(10);
+        let pinned = |leaf_index| {
+            let vk = keygen_vk(&params, &inclusion_circuit(leaf_index)).unwrap();
+            format!("{:?}", vk.pinned())
         };
+        assert_eq!(pinned(0), pinned(5));
+    }
 
-        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+    #[test]
+    fn test_update_leaf() {
+        let mut leaves: Vec<Fp> = (0..8).map(|i| Fp::from(i as u64)).collect();
+        let old_root = build_merkle_tree::<MerkleNode>(&leaves, Fp::zero()).last().unwrap()[0];
+        let leaf_index = 5;
+        let (proof, path_bits) = get_merkle_proof(&build_merkle_tree::<MerkleNode>(&leaves, Fp::zero()), leaf_index);
+        leaves[leaf_index] = Fp::from(50);
+        let new_root = build_merkle_tree::<MerkleNode>(&leaves, Fp::zero()).last().unwrap()[0];
+
+        let circuit = |old_leaf: u64| LeafUpdateCircuit {
+            old_leaf: Value::known(Fp::from(old_leaf)),
+            new_leaf: Value::known(Fp::from(50)),
+            proof: proof.iter().cloned().map(Value::known).collect(),
+            path_bits: path_bits.iter().map(|bit| Value::known(Fp::from(*bit as u64))).collect(),
+        };
+
+        let prover = MockProver::run(10, &circuit(5), vec![vec![old_root, new_root]]).unwrap();
         prover.assert_satisfied();
+
+        // old leaf가 트리에 없음
+        let prover = MockProver::run(10, &circuit(4), vec![vec![old_root, new_root]]).unwrap();
+        assert!(prover.verify().is_err());
+        // 다른 new root 주장
+        let prover = MockProver::run(10, &circuit(5), vec![vec![old_root, old_root]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_multi_inclusion() {
+        let leaves: Vec<Fp> = (0..8).map(|i| Fp::from(i as u64)).collect();
+        let tree = build_merkle_tree::<MerkleNode>(&leaves, Fp::zero());
+        let root = tree.last().unwrap()[0];
+        let indices = vec![1, 2, 3, 6];
+        let proof = get_multi_proof(&tree, &indices);
+        // 개별 경로 4 * 3 = 12개 대신 형제 3개 (0, 7, 2번 노드)
+        assert_eq!(proof, vec![tree[0][0], tree[0][7], tree[1][2]]);
+
+        let circuit = |leaf_values: Vec<Fp>| MultiInclusionCircuit {
+            indices: indices.clone(),
+            leaves: leaf_values.into_iter().map(Value::known).collect(),
+            proof: proof.iter().copied().map(Value::known).collect(),
+        };
+        let values = indices.iter().map(|&i| leaves[i]).collect::<Vec<_>>();
+        let prover = MockProver::run(10, &circuit(values.clone()), vec![vec![root]]).unwrap();
+        prover.assert_satisfied();
+
+        let mut wrong = values;
+        wrong[2] = Fp::from(42);
+        let prover = MockProver::run(10, &circuit(wrong), vec![vec![root]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // 형식이 잘못된 증명, 인덱스는 synthesize 에러
+        let values = indices.iter().map(|&i| leaves[i]).collect::<Vec<_>>();
+        let mut short = circuit(values.clone());
+        short.proof.pop();
+        assert!(MockProver::run(10, &short, vec![vec![root]]).is_err());
+        let mut long = circuit(values.clone());
+        long.proof.push(Value::known(Fp::one()));
+        assert!(MockProver::run(10, &long, vec![vec![root]]).is_err());
+        let mut unsorted = circuit(values);
+        unsorted.indices.swap(0, 1);
+        assert!(MockProver::run(10, &unsorted, vec![vec![root]]).is_err());
     }
 }
@@ -1,5 +1,8 @@
 pub mod transaction_chips;
 pub mod poseidon_chip;
 pub mod merkle_tree_chip;
+pub mod account_tree_chip;
+pub mod block_header_chip;
+pub mod schnorr_chip;
 pub mod state_transition_circuit;
 pub mod zk_rollup_circuit;
\ No newline at end of file
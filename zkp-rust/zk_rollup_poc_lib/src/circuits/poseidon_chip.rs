@@ -3,8 +3,8 @@ use halo2_proofs::{
     plonk::{Advice, Column, ConstraintSystem, Instance, Error},
     pasta::Fp,
 };
-use halo2_gadgets::poseidon::{Hash, Pow5Chip, Pow5Config};
-use halo2_gadgets::poseidon::primitives::{Spec, ConstantLength};
+use halo2_gadgets::poseidon::{PaddedWord, Pow5Chip, Pow5Config, Sponge};
+use halo2_gadgets::poseidon::primitives::{Absorbing, Domain, Spec};
 use std::convert::TryInto;
 
 use crate::constants::POSEIDON_RATE;
@@ -59,7 +59,9 @@ impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize, const L: u
         }
     }
 
-    pub fn hash(
+    /// Poseidon hash of `words` in domain `D` (see `utils::hash_domain`), matching
+    /// `utils::poseidon_hash::<D>` off-circuit.
+    pub fn hash<D: Domain<Fp, RATE>>(
         &self,
         mut layouter: impl Layouter<Fp>,
         words: &[AssignedCell<Fp, Fp>; POSEIDON_RATE],
@@ -84,11 +86,18 @@ impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize, const L: u
             },
         )?;
 
-        let hasher = Hash::<_, _, S, ConstantLength<L>, WIDTH, RATE>::init(
+        // ConstantLength 전용인 Hash::hash 대신 sponge를 직접 사용해 domain의 capacity 적용
+        let mut sponge = Sponge::<_, _, S, Absorbing<PaddedWord<Fp>, RATE>, D, WIDTH, RATE>::new(
             pow5_chip,
-            layouter.namespace(|| "hasher"),
+            layouter.namespace(|| "sponge"),
         )?;
-        hasher.hash(layouter.namespace(|| "hash"), word_cells)
+        let padding = D::padding(L).into_iter().map(PaddedWord::Padding);
+        for (i, word) in word_cells.into_iter().map(PaddedWord::Message).chain(padding).enumerate() {
+            sponge.absorb(layouter.namespace(|| format!("absorb {}", i)), word)?;
+        }
+        sponge
+            .finish_absorbing(layouter.namespace(|| "finish absorbing"))?
+            .squeeze(layouter.namespace(|| "squeeze"))
     }
 
     pub fn expose_public(
@@ -126,11 +135,12 @@ impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize, const L: u
         layouter.assign_region(
             || "assign constant",
             |mut region| {
-                region.assign_advice(
+                // Fixed by the constants column enabled in configure()
+                region.assign_advice_from_constant(
                     || "constant value",
                     self.config.inputs[0], // 아무 컬럼
                     0,
-                    || Value::known(value),
+                    value,
                 )
             },
         )
@@ -146,7 +156,9 @@ mod tests {
         pasta::Fp,
         plonk::{Circuit, ConstraintSystem, Error, Instance},
     };
-    use halo2_gadgets::poseidon::primitives::{P128Pow5T3, ConstantLength, Hash as PoseidonPrimitiveHash};
+    use halo2_gadgets::poseidon::primitives::P128Pow5T3;
+    use crate::utils::hash_domain::{MerkleNode, Tagged};
+    use crate::utils::poseidon_hash;
 
     const WIDTH: usize = 3;
     const RATE: usize = 2;
@@ -201,7 +213,7 @@ mod tests {
                 },
             )?;
 
-            let hash_result = chip.hash(layouter.namespace(|| "poseidon hash"), &message_cells)?;
+            let hash_result = chip.hash::<MerkleNode>(layouter.namespace(|| "poseidon hash"), &message_cells)?;
             chip.expose_public(&mut layouter, &hash_result, instance, 0)?;
             Ok(())
         }
@@ -210,10 +222,7 @@ mod tests {
     #[test]
     fn test_poseidon_hash_works() {
         let inputs_fp: [Fp; L] = [Fp::from(100), Fp::from(0)];
-        let expected_hash = {
-            let hasher = PoseidonPrimitiveHash::<_, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init();
-            hasher.hash(inputs_fp)
-        };
+        let expected_hash = poseidon_hash::<MerkleNode>(&inputs_fp);
 
         let circuit = DummyPoseidonCircuit {
             inputs: inputs_fp.map(Value::known),
@@ -228,5 +237,10 @@ mod tests {
             }
             Err(e) => panic!("MockProver failed with error: {:?}", e),
         }
+
+        // 다른 domain(ConstantLength<2>와 같은 Tagged<0>)의 해시는 통과하면 안 됨
+        let plain_hash = poseidon_hash::<Tagged<0>>(&inputs_fp);
+        let prover = MockProver::run(10, &circuit, vec![vec![plain_hash]]).unwrap();
+        assert!(prover.verify().is_err());
     }
 }
\ No newline at end of file
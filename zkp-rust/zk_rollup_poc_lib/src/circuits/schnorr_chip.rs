@@ -0,0 +1,471 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    pasta::Fp,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+use halo2_gadgets::poseidon::primitives::Spec;
+use halo2_gadgets::utilities::bool_check;
+use group::ff::{Field, PrimeField};
+use group::prime::PrimeCurveAffine;
+use pasta_curves::pallas;
+
+use crate::constants::{POSEIDON_INPUTS, POSEIDON_RATE, POSEIDON_WIDTH};
+use crate::utils::schnorr::{offset_point, point_coordinates, SCALAR_BITS};
+use super::poseidon_chip::PoseidonChip;
+use crate::utils::hash_domain::{Address, Challenge};
+
+/// Affine Pallas point `(x, y)` assigned in the circuit.
+#[derive(Clone, Debug)]
+pub struct PointCells {
+    pub x: AssignedCell<Fp, Fp>,
+    pub y: AssignedCell<Fp, Fp>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SignatureCells {
+    pub r: PointCells,
+    pub s: AssignedCell<Fp, Fp>,
+}
+
+/*
+Schnorr verification (see utils::schnorr): [s]G == R + [e]PK
+
+Scalar multiplication is MSB-first double-and-add over SCALAR_BITS rows, starting from
+a fixed offset point T instead of the identity:
+
+    acc_0 = T,  acc_{i+1} = 2 * acc_i + b_i * P,  k_{i+1} = 2 * k_i + b_i,  k_n = k
+
+so each chain ends at [2^n]T + [k]P. Both sides of the equation carry the same offset,
+which therefore cancels in [2^n]T + [s]G == R + ([2^n]T + [e]PK).
+ */
+#[derive(Clone, Debug)]
+pub struct SchnorrConfig {
+    pub bit: Column<Advice>,
+    pub scalar: Column<Advice>,
+    pub x_a: Column<Advice>,
+    pub y_a: Column<Advice>,
+    pub x_p: Column<Advice>,
+    pub y_p: Column<Advice>,
+    pub lambda_d: Column<Advice>,
+    pub x_d: Column<Advice>,
+    pub y_d: Column<Advice>,
+    pub lambda_s: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub q_mul: Selector,
+    pub q_add: Selector,
+    pub q_point: Selector,
+}
+
+pub struct SchnorrChip {
+    pub config: SchnorrConfig,
+}
+
+enum Base<'a> {
+    Generator,
+    Point(&'a PointCells),
+}
+
+/// Witness of one double-and-add row.
+#[derive(Clone, Copy, Default)]
+struct MulRow {
+    bit: Fp,
+    scalar: Fp,
+    x_a: Fp,
+    y_a: Fp,
+    lambda_d: Fp,
+    x_d: Fp,
+    y_d: Fp,
+    lambda_s: Fp,
+    inv: Fp,
+}
+
+fn invert(value: Fp) -> Fp {
+    // Exceptional cases leave the constraint unsatisfied
+    Option::<Fp>::from(value.invert()).unwrap_or(Fp::zero())
+}
+
+/// Native mirror of the "scalar mul step" gate, including the final accumulator row.
+fn mul_rows(scalar: Fp, (x_p, y_p): (Fp, Fp)) -> Vec<MulRow> {
+    let repr = scalar.to_repr();
+    let (mut x_a, mut y_a) = point_coordinates(&offset_point());
+    let mut acc = Fp::zero();
+    let mut rows = Vec::with_capacity(SCALAR_BITS + 1);
+
+    for i in (0..SCALAR_BITS).rev() {
+        let bit = (repr.as_ref()[i / 8] >> (i % 8)) & 1 == 1;
+        let lambda_d = x_a.square() * Fp::from(3) * invert(y_a.double());
+        let x_d = lambda_d.square() - x_a.double();
+        let y_d = lambda_d * (x_a - x_d) - y_a;
+        let mut row = MulRow { bit: Fp::from(bit as u64), scalar: acc, x_a, y_a, lambda_d, x_d, y_d, ..MulRow::default() };
+
+        (x_a, y_a) = (x_d, y_d);
+        if bit {
+            row.inv = invert(x_p - x_d);
+            row.lambda_s = (y_p - y_d) * row.inv;
+            x_a = row.lambda_s.square() - x_d - x_p;
+            y_a = row.lambda_s * (x_d - x_a) - y_d;
+        }
+        acc = acc.double() + row.bit;
+        rows.push(row);
+    }
+    rows.push(MulRow { scalar: acc, x_a, y_a, ..MulRow::default() });
+    rows
+}
+
+impl SchnorrChip {
+    pub fn construct(config: SchnorrConfig) -> Self {
+        Self { config }
+    }
+
+    /// `advice` is `[bit, scalar, x_a, y_a, x_p, y_p, lambda_d, x_d, y_d, lambda_s, inv]`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 11],
+        constants: Column<Fixed>,
+    ) -> SchnorrConfig {
+        let [bit, scalar, x_a, y_a, x_p, y_p, lambda_d, x_d, y_d, lambda_s, inv] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constants);
+
+        let q_mul = meta.selector();
+        let q_add = meta.selector();
+        let q_point = meta.selector();
+
+        meta.create_gate("scalar mul step", |meta| {
+            let q = meta.query_selector(q_mul);
+            let b = meta.query_advice(bit, Rotation::cur());
+            let k = meta.query_advice(scalar, Rotation::cur());
+            let k_next = meta.query_advice(scalar, Rotation::next());
+            let x_a_cur = meta.query_advice(x_a, Rotation::cur());
+            let y_a_cur = meta.query_advice(y_a, Rotation::cur());
+            let x_a_next = meta.query_advice(x_a, Rotation::next());
+            let y_a_next = meta.query_advice(y_a, Rotation::next());
+            let x_p_cur = meta.query_advice(x_p, Rotation::cur());
+            let y_p_cur = meta.query_advice(y_p, Rotation::cur());
+            let x_p_next = meta.query_advice(x_p, Rotation::next());
+            let y_p_next = meta.query_advice(y_p, Rotation::next());
+            let lambda_d = meta.query_advice(lambda_d, Rotation::cur());
+            let x_d = meta.query_advice(x_d, Rotation::cur());
+            let y_d = meta.query_advice(y_d, Rotation::cur());
+            let lambda_s = meta.query_advice(lambda_s, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+
+            let one = Expression::Constant(Fp::one());
+            let two = Expression::Constant(Fp::from(2));
+            let three = Expression::Constant(Fp::from(3));
+
+            // D = 2 * acc, S = D + P, acc' = b ? S : D
+            let x_s = lambda_s.clone() * lambda_s.clone() - x_d.clone() - x_p_cur.clone();
+            let y_s = lambda_s.clone() * (x_d.clone() - x_s.clone()) - y_d.clone();
+
+            vec![
+                q.clone() * bool_check(b.clone()),
+                q.clone() * (k_next - two.clone() * k - b.clone()),
+                q.clone() * (two.clone() * y_a_cur.clone() * lambda_d.clone() - three * x_a_cur.clone() * x_a_cur.clone()),
+                q.clone() * (x_d.clone() - (lambda_d.clone() * lambda_d.clone() - two * x_a_cur.clone())),
+                q.clone() * (y_d.clone() - (lambda_d * (x_a_cur - x_d.clone()) - y_a_cur)),
+                q.clone() * b.clone() * (lambda_s * (x_p_cur.clone() - x_d.clone()) - (y_p_cur.clone() - y_d.clone())),
+                q.clone() * b.clone() * (inv * (x_p_cur.clone() - x_d.clone()) - one),
+                q.clone() * (x_a_next - (x_d.clone() + b.clone() * (x_s - x_d))),
+                q.clone() * (y_a_next - (y_d.clone() + b * (y_s - y_d))),
+                q.clone() * (x_p_next - x_p_cur),
+                q * (y_p_next - y_p_cur),
+            ]
+        });
+
+        meta.create_gate("point add", |meta| {
+            let q = meta.query_selector(q_add);
+            let x_1 = meta.query_advice(x_a, Rotation::cur());
+            let y_1 = meta.query_advice(y_a, Rotation::cur());
+            let x_2 = meta.query_advice(x_p, Rotation::cur());
+            let y_2 = meta.query_advice(y_p, Rotation::cur());
+            let lambda = meta.query_advice(lambda_s, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            let x_3 = meta.query_advice(x_d, Rotation::cur());
+            let y_3 = meta.query_advice(y_d, Rotation::cur());
+
+            vec![
+                q.clone() * (lambda.clone() * (x_2.clone() - x_1.clone()) - (y_2 - y_1.clone())),
+                q.clone() * (inv * (x_2.clone() - x_1.clone()) - Expression::Constant(Fp::one())),
+                q.clone() * (x_3.clone() - (lambda.clone() * lambda.clone() - x_1.clone() - x_2)),
+                q * (y_3.clone() - (lambda * (x_1 - x_3) - y_1)),
+            ]
+        });
+
+        meta.create_gate("point on curve", |meta| {
+            let q = meta.query_selector(q_point);
+            let x = meta.query_advice(x_a, Rotation::cur());
+            let y = meta.query_advice(y_a, Rotation::cur());
+
+            // Pallas: y^2 = x^3 + 5
+            vec![q * (y.clone() * y - x.clone() * x.clone() * x - Expression::Constant(Fp::from(5)))]
+        });
+
+        SchnorrConfig {
+            bit,
+            scalar,
+            x_a,
+            y_a,
+            x_p,
+            y_p,
+            lambda_d,
+            x_d,
+            y_d,
+            lambda_s,
+            inv,
+            q_mul,
+            q_add,
+            q_point,
+        }
+    }
+
+    /// Witnesses a point and checks that it lies on the curve.
+    pub fn load_point(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: Value<Fp>,
+        y: Value<Fp>,
+    ) -> Result<PointCells, Error> {
+        layouter.assign_region(
+            || "load point",
+            |mut region| {
+                self.config.q_point.enable(&mut region, 0)?;
+                Ok(PointCells {
+                    x: region.assign_advice(|| "x", self.config.x_a, 0, || x)?,
+                    y: region.assign_advice(|| "y", self.config.y_a, 0, || y)?,
+                })
+            },
+        )
+    }
+
+    pub fn load_private(&self, mut layouter: impl Layouter<Fp>, value: Value<Fp>) -> Result<AssignedCell<Fp, Fp>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| region.assign_advice(|| "value", self.config.scalar, 0, || value),
+        )
+    }
+
+    /// Incomplete addition `p + q`; fails when `p.x == q.x`.
+    pub fn add(&self, mut layouter: impl Layouter<Fp>, p: &PointCells, q: &PointCells) -> Result<PointCells, Error> {
+        let config = &self.config;
+        let p_value = p.x.value().copied().zip(p.y.value().copied());
+        let q_value = q.x.value().copied().zip(q.y.value().copied());
+        let sum = p_value.zip(q_value).map(|((x_1, y_1), (x_2, y_2))| {
+            let inv = invert(x_2 - x_1);
+            let lambda = (y_2 - y_1) * inv;
+            let x_3 = lambda.square() - x_1 - x_2;
+            (lambda, inv, x_3, lambda * (x_1 - x_3) - y_1)
+        });
+
+        layouter.assign_region(
+            || "point add",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                p.x.copy_advice(|| "x_1", &mut region, config.x_a, 0)?;
+                p.y.copy_advice(|| "y_1", &mut region, config.y_a, 0)?;
+                q.x.copy_advice(|| "x_2", &mut region, config.x_p, 0)?;
+                q.y.copy_advice(|| "y_2", &mut region, config.y_p, 0)?;
+                region.assign_advice(|| "lambda", config.lambda_s, 0, || sum.map(|sum| sum.0))?;
+                region.assign_advice(|| "inv", config.inv, 0, || sum.map(|sum| sum.1))?;
+                Ok(PointCells {
+                    x: region.assign_advice(|| "x_3", config.x_d, 0, || sum.map(|sum| sum.2))?,
+                    y: region.assign_advice(|| "y_3", config.y_d, 0, || sum.map(|sum| sum.3))?,
+                })
+            },
+        )
+    }
+
+    /// `[2^SCALAR_BITS]T + [scalar]G` for the Pallas generator `G`.
+    pub fn mul_generator(&self, layouter: impl Layouter<Fp>, scalar: &AssignedCell<Fp, Fp>) -> Result<PointCells, Error> {
+        self.scalar_mul(layouter, Base::Generator, scalar)
+    }
+
+    /// `[2^SCALAR_BITS]T + [scalar]point`
+    pub fn mul(
+        &self,
+        layouter: impl Layouter<Fp>,
+        point: &PointCells,
+        scalar: &AssignedCell<Fp, Fp>,
+    ) -> Result<PointCells, Error> {
+        self.scalar_mul(layouter, Base::Point(point), scalar)
+    }
+
+    fn scalar_mul(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        base: Base<'_>,
+        scalar: &AssignedCell<Fp, Fp>,
+    ) -> Result<PointCells, Error> {
+        let config = &self.config;
+        let generator = point_coordinates(&pallas::Affine::generator());
+        let base_value = match &base {
+            Base::Generator => Value::known(generator),
+            Base::Point(point) => point.x.value().copied().zip(point.y.value().copied()),
+        };
+        let rows = scalar.value().copied().zip(base_value).map(|(scalar, base)| mul_rows(scalar, base));
+        let row = |i: usize, f: fn(&MulRow) -> Fp| rows.as_ref().map(|rows| f(&rows[i]));
+        let (offset_x, offset_y) = point_coordinates(&offset_point());
+
+        layouter.assign_region(
+            || "scalar mul",
+            |mut region| {
+                match &base {
+                    Base::Generator => {
+                        region.assign_advice_from_constant(|| "G.x", config.x_p, 0, generator.0)?;
+                        region.assign_advice_from_constant(|| "G.y", config.y_p, 0, generator.1)?;
+                    }
+                    Base::Point(point) => {
+                        point.x.copy_advice(|| "P.x", &mut region, config.x_p, 0)?;
+                        point.y.copy_advice(|| "P.y", &mut region, config.y_p, 0)?;
+                    }
+                }
+                for i in 1..=SCALAR_BITS {
+                    region.assign_advice(|| "P.x", config.x_p, i, || base_value.map(|base| base.0))?;
+                    region.assign_advice(|| "P.y", config.y_p, i, || base_value.map(|base| base.1))?;
+                }
+
+                region.assign_advice_from_constant(|| "T.x", config.x_a, 0, offset_x)?;
+                region.assign_advice_from_constant(|| "T.y", config.y_a, 0, offset_y)?;
+                region.assign_advice_from_constant(|| "k_0", config.scalar, 0, Fp::zero())?;
+
+                for i in 0..SCALAR_BITS {
+                    config.q_mul.enable(&mut region, i)?;
+                    region.assign_advice(|| format!("bit {}", i), config.bit, i, || row(i, |r| r.bit))?;
+                    region.assign_advice(|| "lambda_d", config.lambda_d, i, || row(i, |r| r.lambda_d))?;
+                    region.assign_advice(|| "x_d", config.x_d, i, || row(i, |r| r.x_d))?;
+                    region.assign_advice(|| "y_d", config.y_d, i, || row(i, |r| r.y_d))?;
+                    region.assign_advice(|| "lambda_s", config.lambda_s, i, || row(i, |r| r.lambda_s))?;
+                    region.assign_advice(|| "inv", config.inv, i, || row(i, |r| r.inv))?;
+                    if i > 0 {
+                        region.assign_advice(|| "k", config.scalar, i, || row(i, |r| r.scalar))?;
+                        region.assign_advice(|| "acc.x", config.x_a, i, || row(i, |r| r.x_a))?;
+                        region.assign_advice(|| "acc.y", config.y_a, i, || row(i, |r| r.y_a))?;
+                    }
+                }
+
+                let n = SCALAR_BITS;
+                let k = region.assign_advice(|| "k", config.scalar, n, || row(n, |r| r.scalar))?;
+                region.constrain_equal(k.cell(), scalar.cell())?;
+                Ok(PointCells {
+                    x: region.assign_advice(|| "acc.x", config.x_a, n, || row(n, |r| r.x_a))?,
+                    y: region.assign_advice(|| "acc.y", config.y_a, n, || row(n, |r| r.y_a))?,
+                })
+            },
+        )
+    }
+
+    /// Verifies `signature` on `msg` under `pubkey` (loaded with `load_point`) and returns
+    /// the signer's address `H(PK.x, PK.y)`.
+    pub fn verify(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        poseidon: &PoseidonChip<impl Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>,
+        pubkey: &PointCells,
+        msg: &AssignedCell<Fp, Fp>,
+        signature: &SignatureCells,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let address = poseidon.hash::<Address>(layouter.namespace(|| "address"), &[pubkey.x.clone(), pubkey.y.clone()])?;
+        let r_hash = poseidon.hash::<Challenge>(layouter.namespace(|| "R"), &[signature.r.x.clone(), signature.r.y.clone()])?;
+        let r_address = poseidon.hash::<Challenge>(layouter.namespace(|| "R, address"), &[r_hash, address.clone()])?;
+        let e = poseidon.hash::<Challenge>(layouter.namespace(|| "challenge"), &[r_address, msg.clone()])?;
+
+        let lhs = self.mul_generator(layouter.namespace(|| "[s]G"), &signature.s)?;
+        let e_pk = self.mul(layouter.namespace(|| "[e]PK"), pubkey, &e)?;
+        let rhs = self.add(layouter.namespace(|| "R + [e]PK"), &signature.r, &e_pk)?;
+
+        layouter.assign_region(
+            || "signature equation",
+            |mut region| {
+                region.constrain_equal(lhs.x.cell(), rhs.x.cell())?;
+                region.constrain_equal(lhs.y.cell(), rhs.y.cell())
+            },
+        )?;
+        Ok(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, plonk::Circuit};
+    use halo2_gadgets::poseidon::primitives::P128Pow5T3;
+    use crate::circuits::poseidon_chip::PoseidonConfig;
+    use crate::utils::schnorr::{Signature, SigningKey};
+
+    #[derive(Default)]
+    struct SignatureCircuit {
+        pubkey: [Value<Fp>; 2],
+        r: [Value<Fp>; 2],
+        s: Value<Fp>,
+        msg: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for SignatureCircuit {
+        type Config = (SchnorrConfig, PoseidonConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 11].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let schnorr_config = SchnorrChip::configure(meta, advice, constants);
+            let poseidon_config = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::configure(meta);
+            (schnorr_config, poseidon_config)
+        }
+
+        fn synthesize(&self, (schnorr_config, poseidon_config): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = SchnorrChip::construct(schnorr_config);
+            let poseidon = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(poseidon_config);
+
+            let pubkey = chip.load_point(layouter.namespace(|| "pubkey"), self.pubkey[0], self.pubkey[1])?;
+            let signature = SignatureCells {
+                r: chip.load_point(layouter.namespace(|| "R"), self.r[0], self.r[1])?,
+                s: chip.load_private(layouter.namespace(|| "s"), self.s)?,
+            };
+            let msg = chip.load_private(layouter.namespace(|| "msg"), self.msg)?;
+            chip.verify(layouter.namespace(|| "verify"), &poseidon, &pubkey, &msg, &signature)?;
+            Ok(())
+        }
+    }
+
+    fn run(key: &SigningKey, msg: Fp, signature: &Signature) -> MockProver<Fp> {
+        let (pk_x, pk_y) = key.public_key().coordinates();
+        let (r_x, r_y) = point_coordinates(&signature.r);
+        let circuit = SignatureCircuit {
+            pubkey: [Value::known(pk_x), Value::known(pk_y)],
+            r: [Value::known(r_x), Value::known(r_y)],
+            s: Value::known(signature.s),
+            msg: Value::known(msg),
+        };
+        MockProver::run(11, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_signature_pass() {
+        let key = SigningKey::new(pallas::Scalar::from(42));
+        let msg = Fp::from(2024);
+        run(&key, msg, &key.sign(msg)).assert_satisfied();
+    }
+
+    #[test]
+    fn test_signature_forged_fail() {
+        let key = SigningKey::new(pallas::Scalar::from(42));
+        let other = SigningKey::new(pallas::Scalar::from(43));
+        let msg = Fp::from(2024);
+
+        // 다른 메시지에 대한 서명
+        assert!(run(&key, msg, &key.sign(msg + Fp::one())).verify().is_err());
+        // 다른 키의 서명
+        assert!(run(&key, msg, &other.sign(msg)).verify().is_err());
+        // s 조작
+        let mut signature = key.sign(msg);
+        signature.s += Fp::one();
+        assert!(run(&key, msg, &signature).verify().is_err());
+    }
+}
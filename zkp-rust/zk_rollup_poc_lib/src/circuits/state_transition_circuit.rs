@@ -1,29 +1,38 @@
 use halo2_proofs::{
-    circuit::{Layouter, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    circuit::Layouter,
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
     pasta::Fp,
 };
 use halo2_gadgets::poseidon::primitives::P128Pow5T3;
 
 use crate::constants::{POSEIDON_RATE, POSEIDON_WIDTH, POSEIDON_INPUTS};
 use crate::circuits::poseidon_chip::{PoseidonConfig, PoseidonChip};
-
-
+use crate::circuits::block_header_chip::{BlockHeaderChip, BlockHeaderConfig, BlockHeaderWitness};
+
+/*
+Public inputs
+- previous_block_hash_instance: hash of the previous header
+- current_block_hash_instance: hash of the current header
+- current_roots_instance: [state_root, tx_root] of the current header, as exposed by
+  ZKRollupCircuit for the same block
+ */
+pub const STATE_ROOT_ROW: usize = 0;
+pub const TX_ROOT_ROW: usize = 1;
 
 #[derive(Clone)]
 pub struct StateTransitionConfig {
     pub previous_block_hash_instance: Column<Instance>,
     pub current_block_hash_instance: Column<Instance>,
-    pub combined_block_hash_instance: Column<Instance>,
-    
-    pub previous_block_hash_advice: Column<Advice>,
-    pub current_block_hash_advice: Column<Advice>,
+    pub current_roots_instance: Column<Instance>,
+
+    pub header_config: BlockHeaderConfig,
 }
 
+/// Proves that the current block header directly follows the previous one.
 #[derive(Default, Clone)]
 pub struct StateTransitionCircuit {
-    pub previous_block_hash: Value<Fp>,
-    pub current_block_hash: Value<Fp>,    
+    pub previous_header: BlockHeaderWitness,
+    pub current_header: BlockHeaderWitness,
 }
 
 impl Circuit<Fp> for StateTransitionCircuit {
@@ -37,26 +46,22 @@ impl Circuit<Fp> for StateTransitionCircuit {
     fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
         let previous_block_hash_instance = meta.instance_column();
         let current_block_hash_instance = meta.instance_column();
-        let combined_block_hash_instance = meta.instance_column();
+        let current_roots_instance = meta.instance_column();
         meta.enable_equality(previous_block_hash_instance);
         meta.enable_equality(current_block_hash_instance);
-        meta.enable_equality(combined_block_hash_instance);
+        meta.enable_equality(current_roots_instance);
 
-        let previous_block_hash_advice = meta.advice_column();
-        let current_block_hash_advice = meta.advice_column();
-
-        meta.enable_equality(previous_block_hash_advice);
-        meta.enable_equality(current_block_hash_advice);
+        let header_advice = meta.advice_column();
 
         let poseidon_config = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::configure(meta);
+        let header_config = BlockHeaderChip::<P128Pow5T3>::configure(meta, header_advice, poseidon_config.clone());
         (
             StateTransitionConfig{
                 previous_block_hash_instance,
                 current_block_hash_instance,
-                combined_block_hash_instance,
+                current_roots_instance,
 
-                previous_block_hash_advice,
-                current_block_hash_advice,
+                header_config,
             },
             poseidon_config
         )
@@ -64,39 +69,21 @@ impl Circuit<Fp> for StateTransitionCircuit {
 
 
     fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
-        let poseidon_chip = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(config.1);
-        let previous_block_hash_cell = layouter.assign_region(
-            || "assign instance",
-            |mut region| {
-                let cell  = region.assign_advice_from_instance(
-                    || "previous block hash",
-                    config.0.previous_block_hash_instance,
-                    0,
-                    config.0.previous_block_hash_advice,
-                    0,
-                )?;
-                Ok(cell)
-            }
-        )?;
-
-        let current_block_hash_cell = layouter.assign_region(
-            || "assign instance",
-            |mut region| {
-                let cell  = region.assign_advice_from_instance(
-                    || "current block hash",
-                    config.0.current_block_hash_instance,
-                    0,
-                    config.0.current_block_hash_advice,
-                    0,
-                )?;
-                Ok(cell)
-            }
-        )?;
-        let inputs = [previous_block_hash_cell, current_block_hash_cell];
-        let compressed_hash_cell = poseidon_chip.hash(
-            layouter.namespace(|| "compress"), &inputs
-        )?;
-        layouter.constrain_instance(compressed_hash_cell.cell(), config.0.combined_block_hash_instance, 0)?;
+        let header_chip = BlockHeaderChip::<P128Pow5T3>::construct(config.0.header_config.clone());
+
+        let previous = header_chip.load(layouter.namespace(|| "previous header"), &self.previous_header)?;
+        let current = header_chip.load(layouter.namespace(|| "current header"), &self.current_header)?;
+
+        let previous_hash = header_chip.hash(layouter.namespace(|| "previous header hash"), &previous)?;
+        let current_hash = header_chip.hash(layouter.namespace(|| "current header hash"), &current)?;
+
+        // 블록 번호 +1, prev_hash 연결
+        header_chip.check_successor(layouter.namespace(|| "successor"), &previous, &previous_hash, &current)?;
+
+        layouter.constrain_instance(previous_hash.cell(), config.0.previous_block_hash_instance, 0)?;
+        layouter.constrain_instance(current_hash.cell(), config.0.current_block_hash_instance, 0)?;
+        layouter.constrain_instance(current.state_root.cell(), config.0.current_roots_instance, STATE_ROOT_ROW)?;
+        layouter.constrain_instance(current.tx_root.cell(), config.0.current_roots_instance, TX_ROOT_ROW)?;
 
         Ok(())
     }
@@ -105,56 +92,55 @@ impl Circuit<Fp> for StateTransitionCircuit {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use halo2_proofs::{
-        circuit::Value,
-        dev::MockProver,
-    };
-
-    use crate::utils::poseidon_hash;
+    use halo2_proofs::dev::MockProver;
 
-    #[test]
-    fn test_state_transition_passes() {
-        // 1. input 값 정의
-        let prev = Fp::from(123);
-        let curr = Fp::from(456);
+    use crate::utils::BlockHeader;
 
-        // 2. expected hash 계산 (off-circuit)
-        let expected_hash = poseidon_hash(&[prev, curr]);
+    fn headers() -> (BlockHeader, BlockHeader) {
+        let previous = BlockHeader::genesis(Fp::from(123)).next(10, Fp::from(456), Fp::from(789));
+        let current = previous.next(20, Fp::from(1000), Fp::from(2000));
+        (previous, current)
+    }
 
-        // 3. circuit 생성
+    fn run(previous: &BlockHeader, current: &BlockHeader, public_inputs: Vec<Vec<Fp>>) -> MockProver<Fp> {
         let circuit = StateTransitionCircuit {
-            previous_block_hash: Value::known(prev),
-            current_block_hash: Value::known(curr),
+            previous_header: previous.into(),
+            current_header: current.into(),
         };
+        MockProver::run(10, &circuit, public_inputs).unwrap()
+    }
 
-        // 4. public inputs: [prev, curr, expected_hash]
-        let public_inputs = vec![vec![prev], vec![curr], vec![expected_hash]];
+    fn public_inputs(previous: &BlockHeader, current: &BlockHeader) -> Vec<Vec<Fp>> {
+        vec![vec![previous.hash()], vec![current.hash()], vec![current.state_root, current.tx_root]]
+    }
 
-        // 5. prover
-        let prover = MockProver::run(8, &circuit, public_inputs).unwrap();
-        prover.assert_satisfied();
+    #[test]
+    fn test_state_transition_passes() {
+        let (previous, current) = headers();
+        run(&previous, &current, public_inputs(&previous, &current)).assert_satisfied();
     }
 
     #[test]
     fn test_state_transition_fails_with_wrong_root() {
-        // 1. input 값 정의
-        let prev = Fp::from(123);
-        let curr = Fp::from(456);
-
-        // 2. 잘못된 expected hash
-        let wrong_hash = Fp::from(999);
-
-        // 3. circuit 생성
-        let circuit = StateTransitionCircuit {
-            previous_block_hash: Value::known(prev),
-            current_block_hash: Value::known(curr),
-        };
+        let (previous, current) = headers();
+        let mut public_inputs = public_inputs(&previous, &current);
+        // 잘못된 상태 루트
+        public_inputs[2][STATE_ROOT_ROW] = Fp::from(999);
+        assert!(run(&previous, &current, public_inputs).verify().is_err());
+    }
 
-        // 4. public inputs: [prev, curr, wrong_hash]
-        let public_inputs = vec![vec![prev], vec![curr], vec![wrong_hash]];
+    #[test]
+    fn test_state_transition_fails_with_skipped_number() {
+        let (previous, mut current) = headers();
+        current.number += 1;
+        assert!(run(&previous, &current, public_inputs(&previous, &current)).verify().is_err());
+    }
 
-        // 5. prover
-        let prover = MockProver::run(8, &circuit, public_inputs).unwrap();
-        assert!(prover.verify().is_err());
+    #[test]
+    fn test_state_transition_fails_with_unlinked_header() {
+        // 이전 헤더를 가리키지 않는 헤더
+        let (previous, mut current) = headers();
+        current.prev_hash = Fp::from(1);
+        assert!(run(&previous, &current, public_inputs(&previous, &current)).verify().is_err());
     }
 }
@@ -1,136 +1,1058 @@
 use halo2_proofs::{
-    circuit::{Layouter, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
     pasta::Fp,
 };
 use halo2_gadgets::poseidon::primitives::P128Pow5T3;
-use array_init::array_init;
+use constraints_lib::circuits::gadgets::boolean::{BooleanChip, BooleanConfig};
+use constraints_lib::circuits::gadgets::gadget::Gadget;
 
-use crate::constants::{POSEIDON_RATE, POSEIDON_WIDTH, POSEIDON_INPUTS, TX_FIELDS_COUNT, TX_AGGREGATION_COUNT};
+use crate::constants::{ACCOUNT_TREE_DEPTH, BALANCE_BITS, POSEIDON_RATE, POSEIDON_WIDTH, POSEIDON_INPUTS, TX_FIELDS_COUNT};
 use crate::circuits::transaction_chips::{TransactionChip, TransactionConfig};
 use crate::circuits::merkle_tree_chip::{MerkleTreeChip, MerkleTreeConfig};
+use crate::circuits::account_tree_chip::{AccountCells, AccountTreeChip};
+use crate::circuits::block_header_chip::{BlockHeaderCells, BlockHeaderChip, BlockHeaderConfig, BlockHeaderWitness};
 use crate::circuits::poseidon_chip::PoseidonChip;
+use crate::circuits::schnorr_chip::{SchnorrChip, SchnorrConfig, SignatureCells};
+use constraints_lib::circuits::gadgets::limb_range_check::LimbRangeCheckChip;
+use crate::types::{padding_key, Transaction, PADDING_TX_LEAF};
+use crate::utils::{AccountUpdate, BlockHeader, TransferWitness};
+use crate::utils::hash_domain::{BridgeChain, BridgeEntryHash};
+use crate::utils::schnorr::{point_coordinates, PublicKey, Signature, SigningKey};
+
+/*
+Public inputs (single instance column)
+- row 0: Merkle root of the transaction hashes (noop slots hold PADDING_TX_LEAF)
+- row 1: account state root before the batch
+- row 2: account state root after the batch
+- row 3: number of non-noop transactions in the batch
+- row 4: pubkey of the operator account credited with the fees
+- row 5: L1 deposit queue hash chain of the deposits consumed before the batch
+- row 6: L1 deposit queue hash chain after the deposits of the batch
+- row 7: hash chain of the withdrawals emitted by the batch (from zero)
+- row 8: hash of the previous block header, whose state root is row 1
+- row 9: hash of this block's header (number + 1, state root of row 2, tx root of row 0)
+ */
+pub const TX_ROOT_ROW: usize = 0;
+pub const OLD_STATE_ROOT_ROW: usize = 1;
+pub const NEW_STATE_ROOT_ROW: usize = 2;
+pub const ACTUAL_TX_COUNT_ROW: usize = 3;
+pub const OPERATOR_ROW: usize = 4;
+pub const DEPOSIT_CHAIN_BEFORE_ROW: usize = 5;
+pub const DEPOSIT_CHAIN_AFTER_ROW: usize = 6;
+pub const WITHDRAWALS_ROW: usize = 7;
+pub const PREV_HEADER_HASH_ROW: usize = 8;
+pub const HEADER_HASH_ROW: usize = 9;
 
 #[derive(Clone)]
 pub struct ZKRollupConfig{
     pub root_hash_instance: Column<Instance>,
 
     /*
-    Number of transaciton fields: 7
+    Number of transaciton fields: 10
     - sender_balance_before
     - receiver_balance_before
     - transaction_amount
     - sender_balance_after
     - receiver_balance_after
-    - sender_address -> Currently commented in transaciton_chip.rs
-    - receiver_address -> Currently commented in transaciton_chip.rs
+    - sender_address: H(PK.x, PK.y) of the signing key, also the sender account's pubkey
+    - receiver_address: receiver account's pubkey
+    - nonce: sender's nonce before the transaction (incremented by one)
+    - fee: paid by the sender on top of the amount, credited to the operator
+    - kind: `TxKind` (0 transfer, 1 deposit, 2 withdrawal), equal to deposit + 2 * withdrawal
+
+    Deposits only apply the receiver side (the sender is the padding key), withdrawals
+    only the sender side. The kind is signed with the other fields, so the operator
+    cannot apply a signed transaction as another operation.
      */
-    pub tx_aggregation_advice: [[Column<Advice>; TX_FIELDS_COUNT]; TX_AGGREGATION_COUNT],
+    pub tx_fields_advice: [Column<Advice>; TX_FIELDS_COUNT],
+
+    /// Running count of active transactions and of the operator balance:
+    /// `count_next = count + active`, `fees_next = fees + charged * fee`, where
+    /// `charged` is set when the sender side is applied
+    pub s_accumulate: Selector,
+    /// Signed kind field of an active transaction: `kind = deposit + 2 * withdrawal`
+    pub s_kind: Selector,
+    pub boolean_config: BooleanConfig,
+    pub header_config: BlockHeaderConfig,
+}
+
+/// Account-tree witness of one side of a transfer. Balances, pubkeys and the sender's
+/// nonce are taken from the transaction fields, the rest of the leaf and the sibling
+/// path from here (`nonce` is only used for the receiver). The path bits are witnesses
+/// too, so the circuit layout does not depend on the account index.
+#[derive(Clone, Debug)]
+pub struct AccountUpdateWitness {
+    pub nonce: Value<Fp>,
+    pub siblings: Vec<Value<Fp>>,
+    pub path_bits: Vec<Value<Fp>>,
+}
+
+impl Default for AccountUpdateWitness {
+    fn default() -> Self {
+        Self {
+            nonce: Value::unknown(),
+            siblings: vec![Value::unknown(); ACCOUNT_TREE_DEPTH],
+            path_bits: vec![Value::unknown(); ACCOUNT_TREE_DEPTH],
+        }
+    }
+}
+
+impl AccountUpdateWitness {
+    /// Witness of a side the circuit does not apply (noops, one side of deposits and
+    /// withdrawals). Any values will do, but they have to be known.
+    pub fn skipped() -> Self {
+        Self {
+            nonce: Value::known(Fp::zero()),
+            siblings: vec![Value::known(Fp::zero()); ACCOUNT_TREE_DEPTH],
+            path_bits: vec![Value::known(Fp::zero()); ACCOUNT_TREE_DEPTH],
+        }
+    }
+}
+
+impl From<&AccountUpdate> for AccountUpdateWitness {
+    fn from(update: &AccountUpdate) -> Self {
+        Self {
+            nonce: Value::known(Fp::from(update.before.nonce)),
+            siblings: update.siblings.iter().copied().map(Value::known).collect(),
+            path_bits: update.path_bits.iter().map(|bit| Value::known(Fp::from(*bit as u64))).collect(),
+        }
+    }
+}
+
+/// Sender's public key and signature over the transaction hash.
+#[derive(Default, Clone, Debug)]
+pub struct SignatureWitness {
+    pub pubkey: [Value<Fp>; 2],
+    pub r: [Value<Fp>; 2],
+    pub s: Value<Fp>,
+}
+
+impl SignatureWitness {
+    pub fn new(pubkey: &PublicKey, signature: &Signature) -> Self {
+        let (pk_x, pk_y) = pubkey.coordinates();
+        let (r_x, r_y) = point_coordinates(&signature.r);
+        Self {
+            pubkey: [Value::known(pk_x), Value::known(pk_y)],
+            r: [Value::known(r_x), Value::known(r_y)],
+            s: Value::known(signature.s),
+        }
+    }
+}
+
+/// One transaction slot of the batch.
+#[derive(Clone, Debug)]
+pub struct TxWitness {
+    /// 1 for a transfer, deposit or withdrawal, 0 for a noop
+    pub active: Value<Fp>,
+    /// 1 for a deposit from the L1 queue: only the receiver side is applied. Has to
+    /// match the `kind` field.
+    pub deposit: Value<Fp>,
+    /// 1 for a withdrawal to L1: only the sender side is applied. Has to match the
+    /// `kind` field.
+    pub withdrawal: Value<Fp>,
+    pub fields: [Value<Fp>; TX_FIELDS_COUNT],
+    /// `[sender, receiver]` account updates, applied in order
+    pub account_updates: [AccountUpdateWitness; 2],
+    pub signature: SignatureWitness,
+}
+
+impl TxWitness {
+    /// Transfer recorded by `AccountTree::transfer`, signed by the sender's `key`.
+    pub fn transfer(witness: &TransferWitness, key: &SigningKey) -> Self {
+        let signature = key.sign(Transaction::transfer(witness).hash());
+        Self::signed_transfer(witness, &key.public_key(), &signature)
+    }
+
+    /// Transfer with a `signature` the sender made over its `Transaction::hash`.
+    pub fn signed_transfer(witness: &TransferWitness, pubkey: &PublicKey, signature: &Signature) -> Self {
+        let updates = [(&witness.sender).into(), (&witness.receiver).into()];
+        Self::new([1, 0, 0], Transaction::transfer(witness), updates, SignatureWitness::new(pubkey, signature))
+    }
+
+    /// Deposit recorded by `AccountTree::deposit`.
+    pub fn deposit(update: &AccountUpdate) -> Self {
+        let updates = [AccountUpdateWitness::skipped(), update.into()];
+        Self::padding_signed([1, 1, 0], Transaction::deposit(update), updates)
+    }
+
+    /// Withdrawal recorded by `AccountTree::withdraw`, signed by the account's `key`.
+    pub fn withdrawal(update: &AccountUpdate, amount: u64, fee: u64, key: &SigningKey) -> Self {
+        let signature = key.sign(Transaction::withdrawal(update, amount, fee).hash());
+        Self::signed_withdrawal(update, amount, fee, &key.public_key(), &signature)
+    }
+
+    /// Withdrawal with a `signature` the account owner made over its `Transaction::hash`.
+    pub fn signed_withdrawal(
+        update: &AccountUpdate,
+        amount: u64,
+        fee: u64,
+        pubkey: &PublicKey,
+        signature: &Signature,
+    ) -> Self {
+        let updates = [update.into(), AccountUpdateWitness::skipped()];
+        let tx = Transaction::withdrawal(update, amount, fee);
+        Self::new([1, 0, 1], tx, updates, SignatureWitness::new(pubkey, signature))
+    }
+
+    /// Padding transaction. The circuit ignores its account paths, keeps the state root
+    /// and puts `PADDING_TX_LEAF` in its slot; it only has to pass the transfer gate and
+    /// carry a valid signature of `padding_key`.
+    pub fn noop() -> Self {
+        let updates = [AccountUpdateWitness::skipped(), AccountUpdateWitness::skipped()];
+        Self::padding_signed([0, 0, 0], Transaction::noop(), updates)
+    }
+
+    fn padding_signed(flags: [u64; 3], tx: Transaction, account_updates: [AccountUpdateWitness; 2]) -> Self {
+        let key = padding_key();
+        let signature = SignatureWitness::new(&key.public_key(), &key.sign(tx.hash()));
+        Self::new(flags, tx, account_updates, signature)
+    }
+
+    /// `[active, deposit, withdrawal]` flags, with `signature` over `tx`.
+    fn new(
+        flags: [u64; 3],
+        tx: Transaction,
+        account_updates: [AccountUpdateWitness; 2],
+        signature: SignatureWitness,
+    ) -> Self {
+        let [active, deposit, withdrawal] = flags.map(|flag| Value::known(Fp::from(flag)));
+        Self {
+            active,
+            deposit,
+            withdrawal,
+            fields: tx.fields().map(Value::known),
+            account_updates,
+            signature,
+        }
+    }
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            active: Value::unknown(),
+            deposit: Value::unknown(),
+            withdrawal: Value::unknown(),
+            fields: [Value::unknown(); TX_FIELDS_COUNT],
+            account_updates: Default::default(),
+            signature: SignatureWitness::default(),
+        }
+    }
+}
+
+/// Operator account, updated after the last transaction to collect the fees.
+#[derive(Clone, Debug, Default)]
+pub struct OperatorWitness {
+    pub balance: Value<Fp>,
+    pub update: AccountUpdateWitness,
+}
+
+impl From<&AccountUpdate> for OperatorWitness {
+    fn from(update: &AccountUpdate) -> Self {
+        Self {
+            balance: Value::known(Fp::from(update.before.balance)),
+            update: update.into(),
+        }
+    }
 }
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct ZKRollupCircuit {
-    pub tx_aggregations: [[Value<Fp>; TX_FIELDS_COUNT]; TX_AGGREGATION_COUNT],
+    /// Number of transaction slots. The circuit layout depends on it, so it is fixed
+    /// per verifying key; unused slots are filled with noops.
+    pub max_batch_size: usize,
+    /// `max_batch_size` transactions, noops only after the last transfer
+    pub transactions: Vec<TxWitness>,
+    pub operator: OperatorWitness,
+    /// Header of the block this batch builds on
+    pub previous_header: BlockHeaderWitness,
+    pub timestamp: Value<Fp>,
+}
+
+impl ZKRollupCircuit {
+    /// Pads `transactions` with noops up to `max_batch_size`, which has to be a power of two.
+    /// The block follows `previous_header` (see `BlockHeader::next`).
+    pub fn new(
+        max_batch_size: usize,
+        mut transactions: Vec<TxWitness>,
+        operator: OperatorWitness,
+        previous_header: &BlockHeader,
+        timestamp: u64,
+    ) -> Self {
+        assert!(max_batch_size.is_power_of_two(), "max batch size must be a power of two");
+        assert!(transactions.len() <= max_batch_size, "too many transactions for the batch");
+        transactions.resize(max_batch_size, TxWitness::noop());
+        Self {
+            max_batch_size,
+            transactions,
+            operator,
+            previous_header: previous_header.into(),
+            timestamp: Value::known(Fp::from(timestamp)),
+        }
+    }
+
+    /// Circuit without witnesses for key generation. The layout only depends on
+    /// `max_batch_size`, so its keys prove every block of that size.
+    pub fn blank(max_batch_size: usize) -> Self {
+        Self::new(max_batch_size, vec![], OperatorWitness::default(), &BlockHeader::default(), 0).without_witnesses()
+    }
 }
 
 impl Circuit<Fp> for ZKRollupCircuit {
-    type Config = (ZKRollupConfig, TransactionConfig, MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>);
+    type Config = (ZKRollupConfig, TransactionConfig, MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>, SchnorrConfig);
     type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
  
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            max_batch_size: self.max_batch_size,
+            transactions: self.transactions.iter().map(TxWitness::without_witnesses).collect(),
+            operator: OperatorWitness::default(),
+            previous_header: BlockHeaderWitness::default(),
+            timestamp: Value::unknown(),
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
         let root_hash_instance = meta.instance_column();
         meta.enable_equality(root_hash_instance);
 
-
-        // compile-time 배열 생성
-        let tx_aggregation_advice: [[Column<Advice>; TX_FIELDS_COUNT]; TX_AGGREGATION_COUNT] =
-        array_init(|_| {
-            array_init(|_| {
-                let col = meta.advice_column();
-                meta.enable_equality(col);
-                col
-            })
+        // 모든 트랜잭션이 같은 컬럼을 행 단위로 나눠 씀
+        let tx_fields_advice = [(); TX_FIELDS_COUNT].map(|_| {
+            let col = meta.advice_column();
+            meta.enable_equality(col);
+            col
         });
 
         // 트랜잭션 관련 config (넣을 컬럼들 지정해야 함)
-        let sender = meta.advice_column();
-        let receiver = meta.advice_column();
-        let amount = meta.advice_column();
-        let sender_after = meta.advice_column();
-        let receiver_after = meta.advice_column();
+        let tx_advice = [(); TX_FIELDS_COUNT].map(|_| meta.advice_column());
+        let nonce_after = meta.advice_column();
+        let tx_config = TransactionChip::<Fp>::configure(meta, tx_advice, nonce_after);
 
-        let tx_config = TransactionChip::<Fp>::configure(
-            meta,
-            sender,
-            receiver,
-            amount,
-            sender_after,
-            receiver_after,
-        );
+        // 서명 검증 (Schnorr) config
+        let schnorr_advice = [(); 11].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let schnorr_config = SchnorrChip::configure(meta, schnorr_advice, constants);
 
             // MerkleTree config 준비
         let mt_config = MerkleTreeChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::configure(
             meta,
-            tx_aggregation_advice[0].to_vec(),
+            tx_fields_advice.to_vec(),
         );
 
+        // noop 처리: active 비트, select
+        let boolean_config = BooleanChip::<Fp>::configure(
+            meta,
+            [tx_fields_advice[0], tx_fields_advice[1], tx_fields_advice[2], tx_fields_advice[3]],
+        );
+
+        let header_config = BlockHeaderChip::<P128Pow5T3>::configure(
+            meta,
+            tx_fields_advice[0],
+            mt_config.poseidon_config.clone(),
+        );
+
+        let s_accumulate = meta.selector();
+        let [active, count, fee, fees, charged] = [0, 1, 2, 3, 4].map(|i| tx_fields_advice[i]);
+        meta.create_gate("tx accumulate", |meta| {
+            let s_accumulate = meta.query_selector(s_accumulate);
+            let active = meta.query_advice(active, Rotation::cur());
+            let count = meta.query_advice(count, Rotation::cur());
+            let count_next = meta.query_advice(count, Rotation::next());
+            let fee = meta.query_advice(fee, Rotation::cur());
+            let fees = meta.query_advice(fees, Rotation::cur());
+            let fees_next = meta.query_advice(fees, Rotation::next());
+            let charged = meta.query_advice(charged, Rotation::cur());
+
+            // noop, 입금의 fee는 더하지 않음
+            vec![
+                s_accumulate.clone() * (count_next - count - active),
+                s_accumulate * (fees_next - fees - charged * fee),
+            ]
+        });
+
+        let s_kind = meta.selector();
+        let [kind, deposit, withdrawal] = [0, 1, 2].map(|i| tx_fields_advice[i]);
+        meta.create_gate("tx kind", |meta| {
+            let s_kind = meta.query_selector(s_kind);
+            let kind = meta.query_advice(kind, Rotation::cur());
+            let deposit = meta.query_advice(deposit, Rotation::cur());
+            let withdrawal = meta.query_advice(withdrawal, Rotation::cur());
+
+            vec![s_kind * (kind - deposit - withdrawal * Expression::Constant(Fp::from(2)))]
+        });
+
         let zk_config = ZKRollupConfig {
             root_hash_instance,
-            tx_aggregation_advice,
+            tx_fields_advice,
+            s_accumulate,
+            s_kind,
+            boolean_config,
+            header_config,
         };
                 
-        (zk_config, tx_config, mt_config)
+        (zk_config, tx_config, mt_config, schnorr_config)
     }
 
     fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
-        let (zk_config, tx_config, mt_config) = config;
+        if self.transactions.len() != self.max_batch_size {
+            return Err(Error::Synthesis);
+        }
+        let (zk_config, tx_config, mt_config, schnorr_config) = config;
+        let range_chip = LimbRangeCheckChip::<Fp>::construct(tx_config.range_check_config.clone());
         let tx_chip = TransactionChip::<Fp>::construct(tx_config);
         let mt_chip: MerkleTreeChip<P128Pow5T3, 3, 2, 2> = MerkleTreeChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(mt_config.clone());
+        let account_chip = AccountTreeChip::<P128Pow5T3>::construct(mt_config.clone());
+        let schnorr_chip = SchnorrChip::construct(schnorr_config);
+        let poseidon_chip = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, 2>::construct(mt_config.poseidon_config.clone());
+        let bool_chip = BooleanChip::<Fp>::construct(zk_config.boolean_config.clone());
 
-        let mut tx_hashes = vec![];
-        for (i, tx_fields) in self.tx_aggregations.iter().enumerate() {
-            let assigned_fields = layouter.assign_region(
+        let mut state_root = layouter.assign_region(
+            || "old state root",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "old state root",
+                    zk_config.root_hash_instance,
+                    OLD_STATE_ROOT_ROW,
+                    mt_config.inputs[0],
+                    0,
+                )
+            },
+        )?;
+        let old_state_root = state_root.clone();
+        let padding_leaf = poseidon_chip.assign_constant(&mut layouter, PADDING_TX_LEAF)?;
+        let zero = poseidon_chip.assign_constant(&mut layouter, Fp::zero())?;
+        let mut tx_count = zero.clone();
+        let mut deposit_chain = layouter.assign_region(
+            || "deposit chain before",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "deposit chain before",
+                    zk_config.root_hash_instance,
+                    DEPOSIT_CHAIN_BEFORE_ROW,
+                    mt_config.inputs[0],
+                    0,
+                )
+            },
+        )?;
+        let mut withdrawal_chain = zero.clone();
+        // 수수료는 운영자 잔액에 바로 누적
+        let operator = account_chip.load_private(
+            layouter.namespace(|| "operator account"),
+            &[self.operator.balance, self.operator.update.nonce],
+        )?;
+        let mut operator_balance = operator[0].clone();
+        let mut prev_active: Option<AssignedCell<Fp, Fp>> = None;
+
+        let mut tx_leaves = vec![];
+        for (i, tx) in self.transactions.iter().enumerate() {
+            let (assigned_fields, flags) = layouter.assign_region(
                 || format!("tx {i} fields"),
                 |mut region| {
                     let mut cells = vec![];
                     for j in 0.. TX_FIELDS_COUNT {
                         let cell = region.assign_advice(
                             || format!("tx_{i} foeld_{j}"),
-                            zk_config.tx_aggregation_advice[i][j],
-                            j,
-                            || tx_fields[j],
+                            zk_config.tx_fields_advice[j],
+                            0,
+                            || tx.fields[j],
                         )?;
                         cells.push(cell);
-                }
-                tx_chip.assign(
-                    &mut region,
-                    0,
-                    tx_fields[0],
-                    tx_fields[1],
-                    tx_fields[2],
-                    tx_fields[3],
-                    tx_fields[4],
+                    }
+                    let fields: [AssignedCell<Fp, Fp>; TX_FIELDS_COUNT] = cells.try_into().unwrap();
+
+                    let flags = [("active", tx.active), ("deposit", tx.deposit), ("withdrawal", tx.withdrawal)];
+                    let mut flag_cells = vec![];
+                    for (j, (name, flag)) in flags.into_iter().enumerate() {
+                        flag_cells.push(region.assign_advice(|| name, zk_config.tx_fields_advice[j], 1, || flag)?);
+                    }
+                    Ok((fields, flag_cells))
+                },
+            )?;
+
+            // 1. 연산 종류: active, 입금, 출금 비트
+            let active = bool_chip.assert_bit(layouter.namespace(|| format!("tx {i} active bit")), &flags[0])?;
+            // noop 뒤에는 noop만 올 수 있음: active_i = active_{i-1} AND active_i
+            if let Some(prev_active) = &prev_active {
+                let both = bool_chip.and(layouter.namespace(|| format!("tx {i} after active")), prev_active, &active)?;
+                layouter.assign_region(
+                    || format!("tx {i} active prefix"),
+                    |mut region| region.constrain_equal(both.cell(), active.cell()),
+                )?;
+            }
+            prev_active = Some(active.clone());
+            // noop의 입금/출금 비트는 무시
+            let deposit = bool_chip.and(layouter.namespace(|| format!("tx {i} deposit")), &active, &flags[1])?;
+            let withdrawal = bool_chip.and(layouter.namespace(|| format!("tx {i} withdrawal")), &active, &flags[2])?;
+            let both = bool_chip.and(layouter.namespace(|| format!("tx {i} deposit and withdrawal")), &deposit, &withdrawal)?;
+            layouter.assign_region(
+                || format!("tx {i} single kind"),
+                |mut region| region.constrain_equal(both.cell(), zero.cell()),
+            )?;
+            // 서명된 kind 필드와 연산 비트 일치 (noop은 kind 0)
+            layouter.assign_region(
+                || format!("tx {i} kind"),
+                |mut region| {
+                    zk_config.s_kind.enable(&mut region, 0)?;
+                    let [kind_col, deposit_col, withdrawal_col] = [0, 1, 2].map(|j| zk_config.tx_fields_advice[j]);
+                    assigned_fields[9].copy_advice(|| "kind", &mut region, kind_col, 0)?;
+                    deposit.copy_advice(|| "deposit", &mut region, deposit_col, 0)?;
+                    withdrawal.copy_advice(|| "withdrawal", &mut region, withdrawal_col, 0)?;
+                    Ok(())
+                },
+            )?;
+            // 송신자 쪽: 송금, 출금 / 수신자 쪽: 송금, 입금
+            let not_deposit = bool_chip.not(layouter.namespace(|| format!("tx {i} not deposit")), &deposit)?;
+            let not_withdrawal = bool_chip.not(layouter.namespace(|| format!("tx {i} not withdrawal")), &withdrawal)?;
+            let applied = [
+                bool_chip.and(layouter.namespace(|| format!("tx {i} sender applied")), &active, &not_deposit)?,
+                bool_chip.and(layouter.namespace(|| format!("tx {i} receiver applied")), &active, &not_withdrawal)?,
+            ];
+
+            // 트랜잭션 개수, 수수료 합계 (송신자가 낸 fee만)
+            let (next_count, next_balance) = layouter.assign_region(
+                || format!("tx {i} accumulate"),
+                |mut region| {
+                    zk_config.s_accumulate.enable(&mut region, 0)?;
+                    let [active_col, count_col, fee_col, fees_col, charged_col] =
+                        [0, 1, 2, 3, 4].map(|j| zk_config.tx_fields_advice[j]);
+                    let active = active.copy_advice(|| "active", &mut region, active_col, 0)?;
+                    let charged = applied[0].copy_advice(|| "charged", &mut region, charged_col, 0)?;
+                    let count = tx_count.copy_advice(|| "count", &mut region, count_col, 0)?;
+                    let fee = assigned_fields[8].copy_advice(|| "fee", &mut region, fee_col, 0)?;
+                    let fees = operator_balance.copy_advice(|| "operator balance", &mut region, fees_col, 0)?;
+                    let next_count = region.assign_advice(
+                        || "next count",
+                        count_col,
+                        1,
+                        || count.value().zip(active.value()).map(|(count, active)| *count + active),
+                    )?;
+                    let next_balance = region.assign_advice(
+                        || "next operator balance",
+                        fees_col,
+                        1,
+                        || {
+                            fees.value()
+                                .zip(fee.value())
+                                .zip(charged.value())
+                                .map(|((fees, fee), charged)| *fees + *charged * fee)
+                        },
+                    )?;
+                    Ok((next_count, next_balance))
+                },
+            )?;
+            tx_count = next_count;
+            operator_balance = next_balance;
+
+            // 2. 송금 제약 (잔액 계산 + 범위 검사)
+            let nonce_after = tx_chip.assign(layouter.namespace(|| format!("tx {i} transfer")), &assigned_fields)?;
+
+            // 송신자, 수신자 순서로 계정 트리 갱신 (적용되지 않는 쪽은 상태 유지)
+            let sides = [
+                (&assigned_fields[5], &assigned_fields[0], &assigned_fields[3]),
+                (&assigned_fields[6], &assigned_fields[1], &assigned_fields[4]),
+            ];
+            for (side, (update, (pubkey, balance_before, balance_after))) in tx.account_updates.iter().zip(sides).enumerate() {
+                let loaded = account_chip.load_private(
+                    layouter.namespace(|| format!("tx {i} account {side}")),
+                    &[update.nonce],
+                )?;
+                let siblings = account_chip.load_private(
+                    layouter.namespace(|| format!("tx {i} account {side} siblings")),
+                    &update.siblings,
+                )?;
+                let path_bits = account_chip.load_private(
+                    layouter.namespace(|| format!("tx {i} account {side} path bits")),
+                    &update.path_bits,
+                )?;
+                // 송신자 nonce는 트랜잭션 필드에서, 1 증가
+                let (nonce_before, nonce_after) = if side == 0 {
+                    (assigned_fields[7].clone(), nonce_after.clone())
+                } else {
+                    (loaded[0].clone(), loaded[0].clone())
+                };
+                let before = AccountCells {
+                    pubkey: pubkey.clone(),
+                    balance: balance_before.clone(),
+                    nonce: nonce_before,
+                };
+                let after = AccountCells {
+                    pubkey: pubkey.clone(),
+                    balance: balance_after.clone(),
+                    nonce: nonce_after,
+                };
+                let (old_root, new_root) = account_chip.compute_roots(
+                    layouter.namespace(|| format!("tx {i} account {side} update")),
+                    &before,
+                    &after,
+                    &siblings,
+                    &path_bits,
+                )?;
+                // applied ? old_root : state_root == state_root
+                let checked_root = bool_chip.select(
+                    layouter.namespace(|| format!("tx {i} account {side} old root")),
+                    &applied[side],
+                    &old_root,
+                    &state_root,
+                )?;
+                layouter.assign_region(
+                    || format!("tx {i} account {side} inclusion"),
+                    |mut region| region.constrain_equal(checked_root.cell(), state_root.cell()),
+                )?;
+                state_root = bool_chip.select(
+                    layouter.namespace(|| format!("tx {i} account {side} new root")),
+                    &applied[side],
+                    &new_root,
+                    &state_root,
+                )?;
+            }
+
+            // 3. tx_compress()로 Poseidon 해시 계산 + 송신자 서명 검증
+            let pubkey = schnorr_chip.load_point(
+                layouter.namespace(|| format!("tx {i} pubkey")),
+                tx.signature.pubkey[0],
+                tx.signature.pubkey[1],
+            )?;
+            let signature = SignatureCells {
+                r: schnorr_chip.load_point(layouter.namespace(|| format!("tx {i} R")), tx.signature.r[0], tx.signature.r[1])?,
+                s: schnorr_chip.load_private(layouter.namespace(|| format!("tx {i} s")), tx.signature.s)?,
+            };
+            let tx_hash = tx_chip.tx_compress_signed(
+                &mut layouter,
+                &poseidon_chip,
+                &schnorr_chip,
+                &assigned_fields,
+                &pubkey,
+                &signature,
+            )?;
+            let tx_leaf = bool_chip.select(layouter.namespace(|| format!("tx {i} leaf")), &active, &tx_hash, &padding_leaf)?;
+            tx_leaves.push(tx_leaf);
+
+            // 4. L1 입금 큐 / 출금 목록 해시 체인: chain' = H(chain, H(pubkey, amount))
+            let chains = [
+                (&mut deposit_chain, &deposit, &assigned_fields[6], "deposit"),
+                (&mut withdrawal_chain, &withdrawal, &assigned_fields[5], "withdrawal"),
+            ];
+            for (chain, enabled, pubkey, name) in chains {
+                let entry = poseidon_chip.hash::<BridgeEntryHash>(
+                    layouter.namespace(|| format!("tx {i} {name} entry")),
+                    &[pubkey.clone(), assigned_fields[2].clone()],
                 )?;
+                let next = poseidon_chip.hash::<BridgeChain>(
+                    layouter.namespace(|| format!("tx {i} {name} chain")),
+                    &[chain.clone(), entry],
+                )?;
+                *chain = bool_chip.select(layouter.namespace(|| format!("tx {i} {name} chain select")), enabled, &next, chain)?;
+            }
+        };
 
-                Ok(cells.try_into().unwrap())
-            })?;
-            // 3. tx_compress()로 Poseidon 해시 계산
-            let poseidon_chip = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, 2>::construct(mt_config.poseidon_config.clone());
-            let tx_hash = tx_chip.tx_compress(&mut layouter, &poseidon_chip, &assigned_fields)?;
-            tx_hashes.push(tx_hash);
+        // 5. 운영자 계정에 수수료 반영 (잔액은 BALANCE_BITS 범위)
+        range_chip.check(layouter.namespace(|| "operator balance"), &operator_balance, BALANCE_BITS)?;
+        let operator_pubkey = layouter.assign_region(
+            || "operator pubkey",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "operator pubkey",
+                    zk_config.root_hash_instance,
+                    OPERATOR_ROW,
+                    mt_config.inputs[0],
+                    0,
+                )
+            },
+        )?;
+        let operator_siblings = account_chip.load_private(
+            layouter.namespace(|| "operator siblings"),
+            &self.operator.update.siblings,
+        )?;
+        let operator_path_bits = account_chip.load_private(
+            layouter.namespace(|| "operator path bits"),
+            &self.operator.update.path_bits,
+        )?;
+        let operator_before = AccountCells {
+            pubkey: operator_pubkey,
+            balance: operator[0].clone(),
+            nonce: operator[1].clone(),
         };
+        let operator_after = AccountCells { balance: operator_balance, ..operator_before.clone() };
+        state_root = account_chip.update(
+            layouter.namespace(|| "operator update"),
+            &state_root,
+            &operator_before,
+            &operator_after,
+            &operator_siblings,
+            &operator_path_bits,
+        )?;
 
-        // 4. Merkle 루트 계산
-        let root_cell = mt_chip.compute_merkle_root(&mut layouter, &tx_hashes)?;
+        // 6. Merkle 루트 계산
+        let root_cell = mt_chip.compute_merkle_root(&mut layouter, &tx_leaves)?;
+
+        // 7. 블록 헤더: 이전 헤더의 상태 루트에서 시작, 번호 +1, 계산한 루트들
+        let header_chip = BlockHeaderChip::<P128Pow5T3>::construct(zk_config.header_config.clone());
+        let previous = header_chip.load(layouter.namespace(|| "previous header"), &self.previous_header)?;
+        let previous_hash = header_chip.hash(layouter.namespace(|| "previous header hash"), &previous)?;
+        layouter.assign_region(
+            || "previous state root",
+            |mut region| region.constrain_equal(previous.state_root.cell(), old_state_root.cell()),
+        )?;
+        let header_fields = header_chip.load_private(
+            layouter.namespace(|| "header"),
+            &[self.previous_header.number.map(|number| number + Fp::one()), self.timestamp],
+        )?;
+        let header = BlockHeaderCells {
+            number: header_fields[0].clone(),
+            timestamp: header_fields[1].clone(),
+            prev_hash: previous_hash.clone(),
+            state_root: state_root.clone(),
+            tx_root: root_cell.clone(),
+        };
+        header_chip.check_successor(layouter.namespace(|| "successor"), &previous, &previous_hash, &header)?;
+        let header_hash = header_chip.hash(layouter.namespace(|| "header hash"), &header)?;
 
-        // 5. Merkle 루트를 공개 인스턴스와 비교
-        mt_chip.expose_public(&mut layouter, &root_cell, zk_config.root_hash_instance, 0)?;
+        // 8. Merkle 루트와 해시 체인을 공개 인스턴스와 비교
+        mt_chip.expose_public(&mut layouter, &root_cell, zk_config.root_hash_instance, TX_ROOT_ROW)?;
+        mt_chip.expose_public(&mut layouter, &state_root, zk_config.root_hash_instance, NEW_STATE_ROOT_ROW)?;
+        mt_chip.expose_public(&mut layouter, &tx_count, zk_config.root_hash_instance, ACTUAL_TX_COUNT_ROW)?;
+        mt_chip.expose_public(&mut layouter, &deposit_chain, zk_config.root_hash_instance, DEPOSIT_CHAIN_AFTER_ROW)?;
+        mt_chip.expose_public(&mut layouter, &withdrawal_chain, zk_config.root_hash_instance, WITHDRAWALS_ROW)?;
+        mt_chip.expose_public(&mut layouter, &previous_hash, zk_config.root_hash_instance, PREV_HEADER_HASH_ROW)?;
+        mt_chip.expose_public(&mut layouter, &header_hash, zk_config.root_hash_instance, HEADER_HASH_ROW)?;
 
         Ok(())
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::pallas};
+    use crate::types::tx_root;
+    use crate::utils::{build_merkle_tree, Account, AccountTree, BridgeEntry, DepositQueue};
+    use crate::utils::bridge::hash_chain;
+    use crate::utils::hash_domain::MerkleNode;
+
+    const MAX_BATCH_SIZE: usize = 2;
+    const OPERATOR: u64 = 7;
+    const EMPTY: u64 = 5;
+
+    const TIMESTAMP: u64 = 1_700_000_000;
+
+    struct Block {
+        previous_header: BlockHeader,
+        transactions: Vec<TxWitness>,
+        operator: OperatorWitness,
+        public_inputs: Vec<Fp>,
+        tx_hash: Fp,
+        // 수수료 반영 전 상태 루트
+        root_before_fees: Fp,
+    }
+
+    /// One transfer from account 0 to 1 with a fee of 2, padded with a noop.
+    fn block() -> Block {
+        let keys = [11u64, 12].map(|secret| SigningKey::new(pallas::Scalar::from(secret)));
+        let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+        for (index, key) in keys.iter().enumerate() {
+            accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+        }
+        let operator_pubkey = Fp::from(77);
+        accounts.insert(OPERATOR, Account { pubkey: operator_pubkey, balance: 5, nonce: 3 });
+        let old_root = accounts.root();
+        let witness = accounts.transfer(0, 1, 4, 2, 0).unwrap();
+        let tx_hash = Transaction::transfer(&witness).hash();
+        let root_before_fees = accounts.root();
+        let operator = accounts.collect_fees(OPERATOR, witness.fee).unwrap();
+
+        let previous_header = BlockHeader::genesis(old_root);
+        Block {
+            transactions: vec![TxWitness::transfer(&witness, &keys[0])],
+            operator: (&operator).into(),
+            public_inputs: with_headers(&previous_header, vec![
+                tx_root(&[tx_hash], MAX_BATCH_SIZE),
+                old_root,
+                accounts.root(),
+                Fp::one(),
+                operator_pubkey,
+                Fp::zero(),
+                Fp::zero(),
+                Fp::zero(),
+            ]),
+            previous_header,
+            tx_hash,
+            root_before_fees,
+        }
+    }
+
+    /// A deposit to account 1 from the L1 queue, then a withdrawal of 3 (fee 1) from account 0.
+    fn bridge_block() -> Block {
+        let keys = [11u64, 12].map(|secret| SigningKey::new(pallas::Scalar::from(secret)));
+        let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+        for (index, key) in keys.iter().enumerate() {
+            accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+        }
+        let operator_pubkey = Fp::from(77);
+        accounts.insert(OPERATOR, Account { pubkey: operator_pubkey, balance: 5, nonce: 3 });
+        let old_root = accounts.root();
+
+        // 이전 배치에서 소비된 입금 하나
+        let mut queue = DepositQueue::new();
+        queue.push(BridgeEntry { pubkey: Fp::from(99), amount: 1 });
+        queue.consume(1);
+        queue.push(BridgeEntry { pubkey: keys[1].public_key().address(), amount: 6 });
+        let deposit_chain_before = queue.consumed_chain();
+        let entry = queue.consume(1)[0];
+
+        let deposit = accounts.deposit(1, &entry).unwrap();
+        let withdrawal = accounts.withdraw(0, 3, 1, 0).unwrap();
+        let tx_hashes = [Transaction::deposit(&deposit).hash(), Transaction::withdrawal(&withdrawal, 3, 1).hash()];
+        let root_before_fees = accounts.root();
+        let operator = accounts.collect_fees(OPERATOR, 1).unwrap();
+        let withdrawn = BridgeEntry { pubkey: keys[0].public_key().address(), amount: 3 };
+
+        let previous_header = BlockHeader {
+            number: 41,
+            timestamp: TIMESTAMP - 10,
+            prev_hash: Fp::from(40),
+            state_root: old_root,
+            tx_root: Fp::from(123),
+        };
+        Block {
+            transactions: vec![TxWitness::deposit(&deposit), TxWitness::withdrawal(&withdrawal, 3, 1, &keys[0])],
+            operator: (&operator).into(),
+            public_inputs: with_headers(&previous_header, vec![
+                tx_root(&tx_hashes, MAX_BATCH_SIZE),
+                old_root,
+                accounts.root(),
+                Fp::from(2),
+                operator_pubkey,
+                deposit_chain_before,
+                queue.consumed_chain(),
+                hash_chain(Fp::zero(), &[withdrawn]),
+            ]),
+            previous_header,
+            tx_hash: tx_hashes[0],
+            root_before_fees,
+        }
+    }
+
+    /// A signed withdrawal of 3 (fee 1) from account 0, applied as a transfer to the empty
+    /// slot `EMPTY`: the receiver side proves against the empty leaf and no withdrawal is
+    /// emitted. Every witness is consistent except the kind.
+    fn withdrawal_as_transfer_block() -> Block {
+        let keys = [11u64, 12].map(|secret| SigningKey::new(pallas::Scalar::from(secret)));
+        let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+        for (index, key) in keys.iter().enumerate() {
+            accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+        }
+        let operator_pubkey = Fp::from(77);
+        accounts.insert(OPERATOR, Account { pubkey: operator_pubkey, balance: 5, nonce: 3 });
+        let old_root = accounts.root();
+
+        let withdrawal = accounts.withdraw(0, 3, 1, 0).unwrap();
+        let credit = accounts.update(EMPTY, |account| account.balance += 3);
+        let tx_hash = Transaction::withdrawal(&withdrawal, 3, 1).hash();
+        let root_before_fees = accounts.root();
+        let operator = accounts.collect_fees(OPERATOR, 1).unwrap();
+
+        let mut tx = TxWitness::withdrawal(&withdrawal, 3, 1, &keys[0]);
+        tx.withdrawal = Value::known(Fp::zero());
+        tx.account_updates[1] = (&credit).into();
+
+        let previous_header = BlockHeader::genesis(old_root);
+        Block {
+            transactions: vec![tx],
+            operator: (&operator).into(),
+            public_inputs: with_headers(&previous_header, vec![
+                tx_root(&[tx_hash], MAX_BATCH_SIZE),
+                old_root,
+                accounts.root(),
+                Fp::one(),
+                operator_pubkey,
+                Fp::zero(),
+                Fp::zero(),
+                Fp::zero(),
+            ]),
+            previous_header,
+            tx_hash,
+            root_before_fees,
+        }
+    }
+
+    /// A signed transfer of 4 (fee 2) from account 0 to 1, applied as a withdrawal: the
+    /// receiver is never credited and the amount is paid out on L1 instead. Every
+    /// witness is consistent except the kind.
+    fn transfer_as_withdrawal_block() -> Block {
+        let keys = [11u64, 12].map(|secret| SigningKey::new(pallas::Scalar::from(secret)));
+        let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+        for (index, key) in keys.iter().enumerate() {
+            accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+        }
+        let operator_pubkey = Fp::from(77);
+        accounts.insert(OPERATOR, Account { pubkey: operator_pubkey, balance: 5, nonce: 3 });
+        let old_root = accounts.root();
+
+        let transfer = accounts.clone().transfer(0, 1, 4, 2, 0).unwrap();
+        let debit = accounts.withdraw(0, 4, 2, 0).unwrap();
+        let tx_hash = Transaction::transfer(&transfer).hash();
+        let root_before_fees = accounts.root();
+        let operator = accounts.collect_fees(OPERATOR, 2).unwrap();
+        let withdrawn = BridgeEntry { pubkey: keys[0].public_key().address(), amount: 4 };
+
+        let mut tx = TxWitness::transfer(&transfer, &keys[0]);
+        tx.withdrawal = Value::known(Fp::one());
+        tx.account_updates = [(&debit).into(), AccountUpdateWitness::skipped()];
+
+        let previous_header = BlockHeader::genesis(old_root);
+        Block {
+            transactions: vec![tx],
+            operator: (&operator).into(),
+            public_inputs: with_headers(&previous_header, vec![
+                tx_root(&[tx_hash], MAX_BATCH_SIZE),
+                old_root,
+                accounts.root(),
+                Fp::one(),
+                operator_pubkey,
+                Fp::zero(),
+                Fp::zero(),
+                hash_chain(Fp::zero(), &[withdrawn]),
+            ]),
+            previous_header,
+            tx_hash,
+            root_before_fees,
+        }
+    }
+
+    /// Appends the header hashes to the first eight public inputs.
+    fn with_headers(previous_header: &BlockHeader, mut public_inputs: Vec<Fp>) -> Vec<Fp> {
+        let header = previous_header.next(
+            TIMESTAMP,
+            public_inputs[NEW_STATE_ROOT_ROW],
+            public_inputs[TX_ROOT_ROW],
+        );
+        public_inputs.extend([previous_header.hash(), header.hash()]);
+        public_inputs
+    }
+
+    fn run(block: Block) -> MockProver<Fp> {
+        let circuit = ZKRollupCircuit::new(
+            MAX_BATCH_SIZE,
+            block.transactions,
+            block.operator,
+            &block.previous_header,
+            TIMESTAMP,
+        );
+        MockProver::run(14, &circuit, vec![block.public_inputs]).unwrap()
+    }
+
+    #[test]
+    fn test_noop_padding_pass() {
+        run(block()).assert_satisfied();
+    }
+
+    #[test]
+    fn test_wrong_batch_size_error() {
+        let block = block();
+        let mut circuit = ZKRollupCircuit::new(
+            MAX_BATCH_SIZE,
+            block.transactions,
+            block.operator,
+            &block.previous_header,
+            TIMESTAMP,
+        );
+        circuit.transactions.pop();
+        assert!(MockProver::run(14, &circuit, vec![block.public_inputs]).is_err());
+    }
+
+    #[test]
+    fn test_wrong_tx_count_fail() {
+        let mut block = block();
+        block.public_inputs[ACTUAL_TX_COUNT_ROW] = Fp::from(2);
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_noop_state_change_fail() {
+        // 송금을 noop으로 표시해도 상태 변경은 허용되지 않음
+        let mut block = block();
+        block.transactions[0].active = Value::known(Fp::zero());
+        block.public_inputs[TX_ROOT_ROW] = tx_root(&[], MAX_BATCH_SIZE);
+        block.public_inputs[ACTUAL_TX_COUNT_ROW] = Fp::zero();
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_noop_before_transfer_fail() {
+        let mut block = block();
+        block.transactions.insert(0, TxWitness::noop());
+        block.transactions.truncate(MAX_BATCH_SIZE);
+        block.public_inputs[TX_ROOT_ROW] = build_merkle_tree::<MerkleNode>(&[PADDING_TX_LEAF, block.tx_hash], PADDING_TX_LEAF).last().unwrap()[0];
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_fees_not_collected_fail() {
+        let mut block = block();
+        block.public_inputs[NEW_STATE_ROOT_ROW] = block.root_before_fees;
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_wrong_operator_fail() {
+        let mut block = block();
+        block.public_inputs[OPERATOR_ROW] = Fp::from(78);
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_deposit_withdrawal_pass() {
+        run(bridge_block()).assert_satisfied();
+    }
+
+    #[test]
+    fn test_deposit_not_in_queue_fail() {
+        // 입금 큐를 소비하지 않았다고 주장
+        let mut block = bridge_block();
+        block.public_inputs[DEPOSIT_CHAIN_AFTER_ROW] = block.public_inputs[DEPOSIT_CHAIN_BEFORE_ROW];
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_not_emitted_fail() {
+        let mut block = bridge_block();
+        block.public_inputs[WITHDRAWALS_ROW] = Fp::zero();
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_deposit_and_withdrawal_flags_fail() {
+        let mut block = bridge_block();
+        block.transactions[0].withdrawal = Value::known(Fp::one());
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_relabelled_as_transfer_fail() {
+        assert!(run(withdrawal_as_transfer_block()).verify().is_err());
+    }
+
+    #[test]
+    fn test_transfer_relabelled_as_withdrawal_fail() {
+        assert!(run(transfer_as_withdrawal_block()).verify().is_err());
+    }
+
+    #[test]
+    fn test_header_not_following_previous_fail() {
+        // 이전 헤더의 상태 루트가 배치 시작 루트와 다름
+        let mut block = block();
+        block.previous_header.state_root = Fp::from(5);
+        block.public_inputs.truncate(PREV_HEADER_HASH_ROW);
+        block.public_inputs = with_headers(&block.previous_header, block.public_inputs);
+        assert!(run(block).verify().is_err());
+    }
+
+    #[test]
+    fn test_header_wrong_tx_root_fail() {
+        let mut block = block();
+        let header = block.previous_header.next(TIMESTAMP, block.public_inputs[NEW_STATE_ROOT_ROW], Fp::from(1));
+        block.public_inputs[HEADER_HASH_ROW] = header.hash();
+        assert!(run(block).verify().is_err());
+    }
+}
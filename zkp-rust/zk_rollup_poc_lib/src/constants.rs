@@ -1,7 +1,9 @@
-pub const MAX_BALANCE: usize = 1000;
+pub const BALANCE_BITS: usize = 64; // Balances and amounts are range-checked to u64
 
 pub const POSEIDON_WIDTH: usize = 3;
 pub const POSEIDON_RATE: usize = 2;
 pub const POSEIDON_INPUTS: usize = 2; //POSEIDON_RATE;
-pub const TX_FIELDS_COUNT: usize = 7; // Number of transactions to process in the circuit
-pub const TX_AGGREGATION_COUNT: usize = 8; // Number of transactions to process in the circuit
+pub const TX_FIELDS_COUNT: usize = 10; // Number of fields in a transaction
+pub const TX_AGGREGATION_COUNT: usize = 8; // Maximum batch size (transaction slots) of the rollup circuit
+
+pub const ACCOUNT_TREE_DEPTH: usize = 8; // Sparse Merkle tree of 2^8 account slots
@@ -1,3 +1,5 @@
 pub mod constants;
 pub mod circuits;
+pub mod sequencer;
+pub mod types;
 pub mod utils;
\ No newline at end of file
@@ -0,0 +1,494 @@
+//! Rollup sequencer: validates signed transactions into a mempool, orders them into
+//! batches and turns each batch into a block proven by `ZKRollupCircuit`.
+//!
+//! Users sign the `Transaction::hash` of their transaction, which covers its kind and the
+//! balances before and after it. The mempool is applied in arrival order on top of the
+//! last block, so a client asks `Sequencer::signing_hash` for the hash of its transaction
+//! at the end of the current mempool and signs that.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Error, ProvingKey, SingleVerifier},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::RngCore;
+
+use crate::circuits::zk_rollup_circuit::{
+    TxWitness, ZKRollupCircuit, ACTUAL_TX_COUNT_ROW, DEPOSIT_CHAIN_AFTER_ROW, DEPOSIT_CHAIN_BEFORE_ROW, HEADER_HASH_ROW, NEW_STATE_ROOT_ROW,
+    OLD_STATE_ROOT_ROW, OPERATOR_ROW, PREV_HEADER_HASH_ROW, TX_ROOT_ROW, WITHDRAWALS_ROW,
+};
+use crate::types::{Block, Transaction};
+use crate::utils::bridge::hash_chain;
+use crate::utils::encoding::Encode;
+use crate::utils::schnorr::{PublicKey, Signature};
+use crate::utils::{AccountTree, AccountUpdate, BlockHeader, BridgeEntry, DepositQueue, TransferWitness};
+
+/// Transaction as submitted to the sequencer, before it is applied to the account tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Transfer { sender: u64, receiver: u64, amount: u64, fee: u64, nonce: u64 },
+    Withdrawal { account: u64, amount: u64, fee: u64, nonce: u64 },
+    /// Credit of an L1 deposit to `account`, unsigned: it comes from the deposit queue
+    Deposit { account: u64, entry: BridgeEntry },
+}
+
+impl Operation {
+    /// Account whose owner signs the operation, `None` for deposits.
+    pub fn signer(&self) -> Option<u64> {
+        match self {
+            Operation::Transfer { sender, .. } => Some(*sender),
+            Operation::Withdrawal { account, .. } => Some(*account),
+            Operation::Deposit { .. } => None,
+        }
+    }
+
+    pub fn fee(&self) -> u64 {
+        match self {
+            Operation::Transfer { fee, .. } | Operation::Withdrawal { fee, .. } => *fee,
+            Operation::Deposit { .. } => 0,
+        }
+    }
+
+    /// Accounts whose leaves the operation updates.
+    pub fn accounts(&self) -> Vec<u64> {
+        match self {
+            Operation::Transfer { sender, receiver, .. } => vec![*sender, *receiver],
+            Operation::Withdrawal { account, .. } | Operation::Deposit { account, .. } => vec![*account],
+        }
+    }
+}
+
+/// Operation applied to the account tree, with the witness the circuit needs for it.
+enum Applied {
+    Transfer(TransferWitness),
+    Withdrawal { update: AccountUpdate, amount: u64, fee: u64 },
+    Deposit(AccountUpdate),
+}
+
+impl Applied {
+    fn apply(accounts: &mut AccountTree, op: &Operation) -> Result<Self, String> {
+        Ok(match *op {
+            Operation::Transfer { sender, receiver, amount, fee, nonce } => {
+                Applied::Transfer(accounts.transfer(sender, receiver, amount, fee, nonce)?)
+            }
+            Operation::Withdrawal { account, amount, fee, nonce } => Applied::Withdrawal {
+                update: accounts.withdraw(account, amount, fee, nonce)?,
+                amount,
+                fee,
+            },
+            Operation::Deposit { account, entry } => Applied::Deposit(accounts.deposit(account, &entry)?),
+        })
+    }
+
+    fn transaction(&self) -> Transaction {
+        match self {
+            Applied::Transfer(witness) => Transaction::transfer(witness),
+            Applied::Withdrawal { update, amount, fee } => Transaction::withdrawal(update, *amount, *fee),
+            Applied::Deposit(update) => Transaction::deposit(update),
+        }
+    }
+
+    fn tx_witness(&self, signature: Option<&(PublicKey, Signature)>) -> TxWitness {
+        match (self, signature) {
+            (Applied::Transfer(witness), Some((pubkey, signature))) => {
+                TxWitness::signed_transfer(witness, pubkey, signature)
+            }
+            (Applied::Withdrawal { update, amount, fee }, Some((pubkey, signature))) => {
+                TxWitness::signed_withdrawal(update, *amount, *fee, pubkey, signature)
+            }
+            (Applied::Deposit(update), None) => TxWitness::deposit(update),
+            _ => unreachable!("deposits are the only unsigned operations"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MempoolEntry {
+    op: Operation,
+    /// `None` for deposits
+    signature: Option<(PublicKey, Signature)>,
+}
+
+/// Block produced from a batch of the mempool, ready to be proven.
+#[derive(Clone)]
+pub struct ProducedBlock {
+    pub previous_header: BlockHeader,
+    pub block: Block,
+    /// Withdrawals to be paid out on L1
+    pub withdrawals: Vec<BridgeEntry>,
+    /// Indices of the accounts the block updates, operator included
+    pub touched: Vec<u64>,
+    pub circuit: ZKRollupCircuit,
+    /// Instance column of `circuit`, indexed by the `*_ROW` constants
+    pub public_inputs: Vec<Fp>,
+}
+
+pub struct Sequencer {
+    max_batch_size: usize,
+    /// Account credited with the fees of every block
+    operator: u64,
+    /// State after the last block
+    accounts: AccountTree,
+    /// `accounts` with the mempool applied, to validate new transactions against
+    pending: AccountTree,
+    mempool: VecDeque<MempoolEntry>,
+    deposits: DepositQueue,
+    header: BlockHeader,
+}
+
+impl Sequencer {
+    /// Starts from the genesis block of `accounts`.
+    pub fn new(max_batch_size: usize, accounts: AccountTree, operator: u64) -> Self {
+        let header = BlockHeader::genesis(accounts.root());
+        Self::resume(max_batch_size, accounts, operator, header, DepositQueue::new())
+    }
+
+    /// Continues after the block `header`, whose state is `accounts`, e.g. loaded from a
+    /// `StateDb`. `deposits` is the L1 deposit queue as consumed up to that block.
+    pub fn resume(
+        max_batch_size: usize,
+        accounts: AccountTree,
+        operator: u64,
+        header: BlockHeader,
+        deposits: DepositQueue,
+    ) -> Self {
+        assert!(max_batch_size.is_power_of_two(), "max batch size must be a power of two");
+        assert_eq!(header.state_root, accounts.root(), "accounts do not match the block header");
+        Self {
+            max_batch_size,
+            operator,
+            header,
+            pending: accounts.clone(),
+            accounts,
+            mempool: VecDeque::new(),
+            deposits,
+        }
+    }
+
+    /// Header of the last block.
+    pub fn header(&self) -> &BlockHeader {
+        &self.header
+    }
+
+    /// State after the last block.
+    pub fn accounts(&self) -> &AccountTree {
+        &self.accounts
+    }
+
+    /// State after the mempool, e.g. for the next nonce of an account.
+    pub fn pending_accounts(&self) -> &AccountTree {
+        &self.pending
+    }
+
+    pub fn mempool_len(&self) -> usize {
+        self.mempool.len()
+    }
+
+    /// Hash the signer of `op` has to sign for it to be appended to the mempool now.
+    pub fn signing_hash(&self, op: &Operation) -> Result<Fp, String> {
+        self.check_accounts(op)?;
+        let mut accounts = self.pending.clone();
+        Ok(Applied::apply(&mut accounts, op)?.transaction().hash())
+    }
+
+    /// Validates a signed transfer or withdrawal against the pending state and appends it
+    /// to the mempool.
+    pub fn submit(&mut self, op: Operation, pubkey: PublicKey, signature: Signature) -> Result<(), String> {
+        let signer = op.signer().ok_or("deposits come from the L1 deposit queue")?;
+        if self.pending.account(signer).pubkey != pubkey.address() {
+            return Err(format!("account {signer} is not owned by the signing key"));
+        }
+        self.check_accounts(&op)?;
+        let mut accounts = self.pending.clone();
+        let applied = Applied::apply(&mut accounts, &op)?;
+        if !pubkey.verify(applied.transaction().hash(), &signature) {
+            return Err("invalid signature".to_string());
+        }
+        self.pending = accounts;
+        self.mempool.push_back(MempoolEntry { op, signature: Some((pubkey, signature)) });
+        Ok(())
+    }
+
+    /// Queues an L1 deposit to `account` and appends it to the mempool. Deposits have to
+    /// be consumed in queue order, so one that cannot be credited is refused here rather
+    /// than blocking the queue.
+    pub fn deposit(&mut self, account: u64, entry: BridgeEntry) -> Result<(), String> {
+        let op = Operation::Deposit { account, entry };
+        self.check_accounts(&op)?;
+        let mut accounts = self.pending.clone();
+        Applied::apply(&mut accounts, &op)?;
+        self.pending = accounts;
+        self.deposits.push(entry);
+        self.mempool.push_back(MempoolEntry { op, signature: None });
+        Ok(())
+    }
+
+    fn check_accounts(&self, op: &Operation) -> Result<(), String> {
+        for account in op.accounts() {
+            if account >= self.accounts.tree().capacity() {
+                return Err(format!("account {account} is out of range"));
+            }
+            // Fee collection changes the operator balance between blocks, which would
+            // invalidate signatures made over it
+            if account == self.operator {
+                return Err(format!("account {account} is the operator account"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes up to `max_batch_size` transactions from the front of the mempool and builds
+    /// the next block, or returns `None` when the mempool is empty.
+    pub fn produce_block(&mut self, timestamp: u64) -> Result<Option<ProducedBlock>, String> {
+        let count = self.mempool.len().min(self.max_batch_size);
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let mut accounts = self.accounts.clone();
+        let old_state_root = accounts.root();
+        let mut witnesses = vec![];
+        let mut transactions = vec![];
+        let mut withdrawals = vec![];
+        let mut touched = vec![self.operator];
+        let mut deposit_count = 0;
+        let mut fees = 0u64;
+        for entry in self.mempool.iter().take(count) {
+            let applied = Applied::apply(&mut accounts, &entry.op)?;
+            transactions.push(applied.transaction());
+            witnesses.push(applied.tx_witness(entry.signature.as_ref()));
+            fees = fees.checked_add(entry.op.fee()).ok_or("fees of the block overflow")?;
+            touched.extend(entry.op.accounts());
+            match applied {
+                Applied::Withdrawal { update, amount, .. } => {
+                    withdrawals.push(BridgeEntry { pubkey: update.before.pubkey, amount })
+                }
+                Applied::Deposit(_) => deposit_count += 1,
+                Applied::Transfer(_) => {}
+            }
+        }
+        let operator_update = accounts.collect_fees(self.operator, fees)?;
+        touched.sort_unstable();
+        touched.dedup();
+
+        let previous_header = self.header;
+        let mut block = Block { header: previous_header, batch_size: self.max_batch_size as u64, transactions };
+        block.header = previous_header.next(timestamp, accounts.root(), block.tx_root());
+        let header = block.header;
+        let mut deposits = self.deposits.clone();
+        let deposit_chain_before = deposits.consumed_chain();
+        deposits.consume(deposit_count);
+
+        let mut public_inputs = vec![Fp::zero(); HEADER_HASH_ROW + 1];
+        public_inputs[TX_ROOT_ROW] = header.tx_root;
+        public_inputs[OLD_STATE_ROOT_ROW] = old_state_root;
+        public_inputs[NEW_STATE_ROOT_ROW] = header.state_root;
+        public_inputs[ACTUAL_TX_COUNT_ROW] = Fp::from(count as u64);
+        public_inputs[OPERATOR_ROW] = operator_update.before.pubkey;
+        public_inputs[DEPOSIT_CHAIN_BEFORE_ROW] = deposit_chain_before;
+        public_inputs[DEPOSIT_CHAIN_AFTER_ROW] = deposits.consumed_chain();
+        public_inputs[WITHDRAWALS_ROW] = hash_chain(Fp::zero(), &withdrawals);
+        public_inputs[PREV_HEADER_HASH_ROW] = previous_header.hash();
+        public_inputs[HEADER_HASH_ROW] = header.hash();
+
+        let circuit = ZKRollupCircuit::new(
+            self.max_batch_size,
+            witnesses,
+            (&operator_update).into(),
+            &previous_header,
+            timestamp,
+        );
+
+        // The rest of the mempool only touches non-operator accounts, so replaying it on
+        // the new state gives back the same balances and signed hashes
+        let mut pending = accounts.clone();
+        for entry in self.mempool.iter().skip(count) {
+            Applied::apply(&mut pending, &entry.op)?;
+        }
+        self.mempool.drain(..count);
+        self.accounts = accounts;
+        self.pending = pending;
+        self.deposits = deposits;
+        self.header = header;
+
+        Ok(Some(ProducedBlock {
+            previous_header,
+            block,
+            withdrawals,
+            touched,
+            circuit,
+            public_inputs,
+        }))
+    }
+}
+
+/// Proving key for blocks of `max_batch_size` transactions. The account paths are
+/// witnesses, so the same key (and verifying key) proves every such block.
+pub fn keygen(params: &Params<EqAffine>, max_batch_size: usize) -> Result<ProvingKey<EqAffine>, Error> {
+    let circuit = ZKRollupCircuit::blank(max_batch_size);
+    let vk = keygen_vk(params, &circuit)?;
+    keygen_pk(params, vk, &circuit)
+}
+
+/// Proves `block` with a `keygen` key and checks the proof.
+pub fn prove_block(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    block: &ProducedBlock,
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error> {
+    let instances: &[&[Fp]] = &[&block.public_inputs];
+
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(params, pk, &[block.circuit.clone()], &[instances], rng, &mut transcript)?;
+    let proof = transcript.finalize();
+
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+    verify_proof(params, pk.get_vk(), SingleVerifier::new(params), &[instances], &mut transcript)?;
+    Ok(proof)
+}
+
+/// Writes `block_<number>.bin` (canonical encoding of the block), a readable summary in
+/// `block_<number>.txt`, `block_<number>.proof` when there is a proof, and overwrites
+/// `state_root` with the root after the block.
+pub fn write_block(dir: &Path, produced: &ProducedBlock, proof: Option<&[u8]>) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let header = &produced.block.header;
+    fs::write(dir.join(format!("block_{}.bin", header.number)), produced.block.to_bytes())?;
+    let mut text = format!(
+        "number: {}\ntimestamp: {}\nprev_hash: {:?}\nstate_root: {:?}\ntx_root: {:?}\nhash: {:?}\n",
+        header.number,
+        header.timestamp,
+        header.prev_hash,
+        header.state_root,
+        header.tx_root,
+        header.hash(),
+    );
+    text += "transactions:\n";
+    for tx in &produced.block.transactions {
+        text += &format!("  {:?}\n", tx.hash());
+    }
+    text += "withdrawals:\n";
+    for withdrawal in &produced.withdrawals {
+        text += &format!("  {:?} {}\n", withdrawal.pubkey, withdrawal.amount);
+    }
+    text += "public_inputs:\n";
+    for input in &produced.public_inputs {
+        text += &format!("  {:?}\n", input);
+    }
+    fs::write(dir.join(format!("block_{}.txt", header.number)), text)?;
+
+    if let Some(proof) = proof {
+        fs::write(dir.join(format!("block_{}.proof", header.number)), proof)?;
+    }
+    fs::write(dir.join("state_root"), format!("{:?}\n", header.state_root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::pallas};
+
+    use crate::utils::schnorr::SigningKey;
+    use crate::utils::Account;
+
+    const MAX_BATCH_SIZE: usize = 2;
+    const OPERATOR: u64 = 7;
+
+    fn setup() -> (Sequencer, Vec<SigningKey>) {
+        let keys = (0..3u64)
+            .map(|index| SigningKey::new(pallas::Scalar::from(1000 + index)))
+            .collect::<Vec<_>>();
+        let mut accounts = AccountTree::new(crate::constants::ACCOUNT_TREE_DEPTH);
+        for (index, key) in keys.iter().enumerate() {
+            accounts.insert(index as u64, Account { pubkey: key.public_key().address(), balance: 10, nonce: 0 });
+        }
+        accounts.insert(OPERATOR, Account { pubkey: Fp::from(77), balance: 0, nonce: 0 });
+        (Sequencer::new(MAX_BATCH_SIZE, accounts, OPERATOR), keys)
+    }
+
+    fn submit(sequencer: &mut Sequencer, key: &SigningKey, op: Operation) -> Result<(), String> {
+        let signature = key.sign(sequencer.signing_hash(&op)?);
+        sequencer.submit(op, key.public_key(), signature)
+    }
+
+    #[test]
+    fn test_mempool_validation() {
+        let (mut sequencer, keys) = setup();
+        let transfer = Operation::Transfer { sender: 0, receiver: 1, amount: 4, fee: 1, nonce: 0 };
+
+        // Signed by the wrong key, or over the wrong hash
+        assert!(submit(&mut sequencer, &keys[1], transfer).is_err());
+        let signature = keys[0].sign(Fp::from(1));
+        assert!(sequencer.submit(transfer, keys[0].public_key(), signature).is_err());
+
+        submit(&mut sequencer, &keys[0], transfer).unwrap();
+        // Replayed nonce, balance spent by the pending transfer, operator account
+        assert!(submit(&mut sequencer, &keys[0], transfer).is_err());
+        let overspend = Operation::Withdrawal { account: 0, amount: 6, fee: 0, nonce: 1 };
+        assert!(submit(&mut sequencer, &keys[0], overspend).is_err());
+        let to_operator = Operation::Transfer { sender: 1, receiver: OPERATOR, amount: 1, fee: 0, nonce: 0 };
+        assert!(submit(&mut sequencer, &keys[1], to_operator).is_err());
+
+        assert_eq!(sequencer.mempool_len(), 1);
+        assert_eq!(sequencer.pending_accounts().account(0).balance, 5);
+        assert_eq!(sequencer.accounts().account(0).balance, 10);
+    }
+
+    #[test]
+    fn test_produce_blocks() {
+        let (mut sequencer, keys) = setup();
+        let genesis = *sequencer.header();
+        submit(&mut sequencer, &keys[0], Operation::Transfer { sender: 0, receiver: 1, amount: 4, fee: 1, nonce: 0 })
+            .unwrap();
+        let entry = BridgeEntry { pubkey: keys[2].public_key().address(), amount: 3 };
+        sequencer.deposit(2, entry).unwrap();
+        submit(&mut sequencer, &keys[2], Operation::Withdrawal { account: 2, amount: 12, fee: 1, nonce: 0 })
+            .unwrap();
+
+        let first = sequencer.produce_block(1).unwrap().unwrap();
+        assert_eq!(first.previous_header, genesis);
+        first.block.check().unwrap();
+        assert_eq!(first.touched, vec![0, 1, 2, OPERATOR]);
+        assert_eq!(sequencer.mempool_len(), 1);
+        assert_eq!(sequencer.accounts().account(OPERATOR).balance, 1);
+        let prover = MockProver::run(14, &first.circuit, vec![first.public_inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        // The withdrawal, signed before the first block, is still valid on top of it
+        let second = sequencer.produce_block(2).unwrap().unwrap();
+        assert_eq!(second.previous_header, first.block.header);
+        assert_eq!(second.withdrawals, vec![BridgeEntry { pubkey: entry.pubkey, amount: 12 }]);
+        assert_eq!(sequencer.accounts().account(2).balance, 0);
+        let prover = MockProver::run(14, &second.circuit, vec![second.public_inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        assert!(sequencer.produce_block(3).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_one_vk_for_every_block() {
+        let (mut sequencer, keys) = setup();
+        submit(&mut sequencer, &keys[0], Operation::Transfer { sender: 0, receiver: 1, amount: 4, fee: 1, nonce: 0 })
+            .unwrap();
+        let first = sequencer.produce_block(1).unwrap().unwrap();
+        submit(&mut sequencer, &keys[2], Operation::Withdrawal { account: 2, amount: 3, fee: 1, nonce: 0 })
+            .unwrap();
+        let second = sequencer.produce_block(2).unwrap().unwrap();
+        assert_eq!(first.touched, vec![0, 1, OPERATOR]);
+        assert_eq!(second.touched, vec![2, OPERATOR]);
+
+        // blank()으로 만든 키가 계정 인덱스와 무관하게 모든 블록에 맞아야 함
+        let params: Params<EqAffine> = Params::new(14);
+        let pinned = |circuit: &ZKRollupCircuit| format!("{:?}", keygen_vk(&params, circuit).unwrap().pinned());
+        let blank = pinned(&ZKRollupCircuit::blank(MAX_BATCH_SIZE));
+        assert_eq!(pinned(&first.circuit), blank);
+        assert_eq!(pinned(&second.circuit), blank);
+    }
+}
@@ -0,0 +1,419 @@
+//! Canonical rollup types with the native hashing of the circuits.
+//!
+//! `Transaction::hash` is the off-circuit `TransactionChip::tx_compress`, `Block::tx_root`
+//! the off-circuit `MerkleTreeChip::compute_merkle_root` over the transaction slots, and
+//! `Account::hash` the leaf of `AccountTreeChip`. All of them serialize with serde and
+//! have a canonical byte encoding (`utils::encoding`).
+
+use halo2_proofs::pasta::{pallas, Fp};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::TX_FIELDS_COUNT;
+use crate::utils::encoding::{fp_hex, Decode, Encode};
+use crate::utils::schnorr::SigningKey;
+use crate::utils::hash_domain::{MerkleNode, TxFields, TxHash};
+use crate::utils::{build_merkle_tree, poseidon_hash, AccountUpdate, BlockHeader, TransferWitness};
+
+pub use crate::utils::Account;
+
+/// Leaf of the transaction tree in the slot of a noop transaction. Transaction hashes
+/// are `TxHash` outputs, so no transaction can hash to it.
+pub const PADDING_TX_LEAF: Fp = Fp::zero();
+
+/// Key signing the noop and deposit transactions. Its secret is public, so its address
+/// must never be the pubkey of an account.
+pub fn padding_key() -> SigningKey {
+    SigningKey::new(pallas::Scalar::one())
+}
+
+/// Transaction root of a batch: `tx_hashes` padded with `PADDING_TX_LEAF` up to
+/// `batch_size` slots.
+pub fn tx_root(tx_hashes: &[Fp], batch_size: usize) -> Fp {
+    assert!(tx_hashes.len() <= batch_size);
+    let mut leaves = tx_hashes.to_vec();
+    leaves.resize(batch_size, PADDING_TX_LEAF);
+    build_merkle_tree::<MerkleNode>(&leaves, PADDING_TX_LEAF).last().unwrap()[0]
+}
+
+/// Operation of a transaction. It is one of the signed fields, so an operator cannot
+/// apply a signed transfer as a withdrawal or the other way around; the circuit ties it
+/// to its deposit and withdrawal flags as `deposit + 2 * withdrawal`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxKind {
+    /// Transfer between two accounts, also the kind of noops
+    #[default]
+    Transfer,
+    /// Credit of an L1 deposit: only the receiver side is applied
+    Deposit,
+    /// Withdrawal to L1: only the sender side is applied
+    Withdrawal,
+}
+
+impl TxKind {
+    pub fn to_u64(self) -> u64 {
+        match self {
+            TxKind::Transfer => 0,
+            TxKind::Deposit => 1,
+            TxKind::Withdrawal => 2,
+        }
+    }
+
+    pub fn from_u64(value: u64) -> Result<Self, String> {
+        match value {
+            0 => Ok(TxKind::Transfer),
+            1 => Ok(TxKind::Deposit),
+            2 => Ok(TxKind::Withdrawal),
+            _ => Err(format!("unknown transaction kind {value}")),
+        }
+    }
+}
+
+impl Encode for TxKind {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.to_u64().encode_to(out);
+    }
+}
+
+impl Decode for TxKind {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Self::from_u64(u64::decode_from(input)?)
+    }
+}
+
+/// Transaction fields as signed by the sender and checked by `TransactionChip`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub sender_balance_before: u64,
+    pub receiver_balance_before: u64,
+    pub amount: u64,
+    pub sender_balance_after: u64,
+    pub receiver_balance_after: u64,
+    /// Address of the signing key, also the sender account's pubkey
+    #[serde(with = "fp_hex")]
+    pub sender: Fp,
+    #[serde(with = "fp_hex")]
+    pub receiver: Fp,
+    /// Sender nonce before the transaction
+    pub nonce: u64,
+    pub fee: u64,
+    pub kind: TxKind,
+}
+
+impl Transaction {
+    /// Transfer recorded by `AccountTree::transfer`.
+    pub fn transfer(witness: &TransferWitness) -> Self {
+        let sender = &witness.sender;
+        let receiver = &witness.receiver;
+        Self {
+            sender_balance_before: sender.before.balance,
+            receiver_balance_before: receiver.before.balance,
+            amount: witness.amount,
+            sender_balance_after: sender.after.balance,
+            receiver_balance_after: receiver.after.balance,
+            sender: sender.before.pubkey,
+            receiver: receiver.before.pubkey,
+            nonce: sender.before.nonce,
+            fee: witness.fee,
+            kind: TxKind::Transfer,
+        }
+    }
+
+    /// Deposit credited to the account of `update`. The sender side is not applied; it
+    /// is filled in so that the transfer gate holds.
+    pub fn deposit(update: &AccountUpdate) -> Self {
+        let amount = update.after.balance - update.before.balance;
+        Self {
+            sender_balance_before: amount,
+            receiver_balance_before: update.before.balance,
+            amount,
+            sender_balance_after: 0,
+            receiver_balance_after: update.after.balance,
+            sender: padding_key().public_key().address(),
+            receiver: update.before.pubkey,
+            nonce: 0,
+            fee: 0,
+            kind: TxKind::Deposit,
+        }
+    }
+
+    /// Withdrawal of `amount` from the account of `update`. The receiver side is not
+    /// applied; it is filled in so that the transfer gate holds.
+    pub fn withdrawal(update: &AccountUpdate, amount: u64, fee: u64) -> Self {
+        Self {
+            sender_balance_before: update.before.balance,
+            receiver_balance_before: 0,
+            amount,
+            sender_balance_after: update.after.balance,
+            receiver_balance_after: amount,
+            sender: update.before.pubkey,
+            receiver: Fp::zero(),
+            nonce: update.before.nonce,
+            fee,
+            kind: TxKind::Withdrawal,
+        }
+    }
+
+    /// Padding transaction, signed by `padding_key`.
+    pub fn noop() -> Self {
+        Self { sender: padding_key().public_key().address(), ..Self::default() }
+    }
+
+    /// Fields in the order of `ZKRollupConfig::tx_fields_advice`.
+    pub fn fields(&self) -> [Fp; TX_FIELDS_COUNT] {
+        [
+            Fp::from(self.sender_balance_before),
+            Fp::from(self.receiver_balance_before),
+            Fp::from(self.amount),
+            Fp::from(self.sender_balance_after),
+            Fp::from(self.receiver_balance_after),
+            self.sender,
+            self.receiver,
+            Fp::from(self.nonce),
+            Fp::from(self.fee),
+            Fp::from(self.kind.to_u64()),
+        ]
+    }
+
+    /// Merkle root of the zero-padded fields, the levels below the root hashed in the
+    /// `TxFields` domain and the root in `TxHash`: the hash signed by the sender.
+    pub fn hash(&self) -> Fp {
+        let tree = build_merkle_tree::<TxFields>(&self.fields(), Fp::zero());
+        let top = &tree[tree.len() - 2];
+        poseidon_hash::<TxHash>(&[top[0], top[1]])
+    }
+}
+
+impl Encode for Transaction {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        for value in [
+            self.sender_balance_before,
+            self.receiver_balance_before,
+            self.amount,
+            self.sender_balance_after,
+            self.receiver_balance_after,
+        ] {
+            value.encode_to(out);
+        }
+        self.sender.encode_to(out);
+        self.receiver.encode_to(out);
+        self.nonce.encode_to(out);
+        self.fee.encode_to(out);
+        self.kind.encode_to(out);
+    }
+}
+
+impl Decode for Transaction {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Ok(Self {
+            sender_balance_before: u64::decode_from(input)?,
+            receiver_balance_before: u64::decode_from(input)?,
+            amount: u64::decode_from(input)?,
+            sender_balance_after: u64::decode_from(input)?,
+            receiver_balance_after: u64::decode_from(input)?,
+            sender: Fp::decode_from(input)?,
+            receiver: Fp::decode_from(input)?,
+            nonce: u64::decode_from(input)?,
+            fee: u64::decode_from(input)?,
+            kind: TxKind::decode_from(input)?,
+        })
+    }
+}
+
+/// Rollup block: its header and the transactions of the batch, without the noop padding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    pub header: BlockHeader,
+    /// Number of transaction slots of the circuit that proved the block
+    pub batch_size: u64,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    /// Hash of the header, which commits to the transactions through `tx_root`.
+    pub fn hash(&self) -> Fp {
+        self.header.hash()
+    }
+
+    pub fn tx_root(&self) -> Fp {
+        let tx_hashes = self.transactions.iter().map(Transaction::hash).collect::<Vec<_>>();
+        tx_root(&tx_hashes, self.batch_size as usize)
+    }
+
+    /// Checks that the header commits to the transactions.
+    pub fn check(&self) -> Result<(), String> {
+        if self.transactions.len() as u64 > self.batch_size {
+            return Err(format!("{} transactions for {} slots", self.transactions.len(), self.batch_size));
+        }
+        if self.header.tx_root != self.tx_root() {
+            return Err("header tx root does not match the transactions".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Encode for Block {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.header.encode_to(out);
+        self.batch_size.encode_to(out);
+        self.transactions.encode_to(out);
+    }
+}
+
+impl Decode for Block {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Ok(Self {
+            header: BlockHeader::decode_from(input)?,
+            batch_size: u64::decode_from(input)?,
+            transactions: Vec::decode_from(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2_gadgets::poseidon::primitives::P128Pow5T3;
+
+    use crate::circuits::merkle_tree_chip::{MerkleTreeChip, MerkleTreeConfig};
+    use crate::circuits::poseidon_chip::PoseidonChip;
+    use crate::circuits::transaction_chips::{TransactionChip, TransactionConfig};
+    use crate::constants::{ACCOUNT_TREE_DEPTH, POSEIDON_INPUTS, POSEIDON_RATE, POSEIDON_WIDTH};
+    use crate::utils::{AccountTree, BridgeEntry};
+
+    /// Hashes the transactions with `tx_compress` and their slots with
+    /// `compute_merkle_root`, exposing the hashes and then the root.
+    struct BlockHashCircuit {
+        batch_size: usize,
+        transactions: Vec<[Value<Fp>; TX_FIELDS_COUNT]>,
+    }
+
+    impl Circuit<Fp> for BlockHashCircuit {
+        type Config = (MerkleTreeConfig<POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>, TransactionConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                batch_size: self.batch_size,
+                transactions: vec![[Value::unknown(); TX_FIELDS_COUNT]; self.transactions.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let inputs = (0..3).map(|_| meta.advice_column()).collect();
+            let merkle_config = MerkleTreeChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::configure(meta, inputs);
+            let advice = [(); TX_FIELDS_COUNT].map(|_| meta.advice_column());
+            let nonce_after = meta.advice_column();
+            let tx_config = TransactionChip::<Fp>::configure(meta, advice, nonce_after);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (merkle_config, tx_config, instance)
+        }
+
+        fn synthesize(&self, (merkle_config, tx_config, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let poseidon_chip = PoseidonChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(
+                merkle_config.poseidon_config.clone(),
+            );
+            let merkle_chip = MerkleTreeChip::<P128Pow5T3, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_INPUTS>::construct(merkle_config.clone());
+            let tx_chip = TransactionChip::<Fp>::construct(tx_config);
+
+            let mut leaves = vec![];
+            for (i, fields) in self.transactions.iter().enumerate() {
+                let cells: [AssignedCell<Fp, Fp>; TX_FIELDS_COUNT] = layouter.assign_region(
+                    || format!("tx {i} fields"),
+                    |mut region| {
+                        let cells = fields
+                            .iter()
+                            .enumerate()
+                            .map(|(j, field)| region.assign_advice(|| format!("field {j}"), merkle_config.inputs[0], j, || *field))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(cells.try_into().unwrap())
+                    },
+                )?;
+                let hash = tx_chip.tx_compress(&mut layouter, &poseidon_chip, &cells)?;
+                layouter.constrain_instance(hash.cell(), instance, i)?;
+                leaves.push(hash);
+            }
+            while leaves.len() < self.batch_size {
+                leaves.push(poseidon_chip.assign_constant(&mut layouter, PADDING_TX_LEAF)?);
+            }
+            let root = merkle_chip.compute_merkle_root(&mut layouter, &leaves)?;
+            layouter.constrain_instance(root.cell(), instance, self.transactions.len())
+        }
+    }
+
+    fn block() -> Block {
+        let mut accounts = AccountTree::new(ACCOUNT_TREE_DEPTH);
+        accounts.insert(0, Account { pubkey: Fp::from(11), balance: 100, nonce: 3 });
+        accounts.insert(1, Account { pubkey: Fp::from(22), balance: 5, nonce: 0 });
+        let genesis = BlockHeader::genesis(accounts.root());
+
+        let transfer = accounts.transfer(0, 1, 30, 2, 3).unwrap();
+        let deposit = accounts.deposit(1, &BridgeEntry { pubkey: Fp::from(22), amount: 7 }).unwrap();
+        let withdrawal = accounts.withdraw(1, 10, 1, 0).unwrap();
+        let transactions = vec![
+            Transaction::transfer(&transfer),
+            Transaction::deposit(&deposit),
+            Transaction::withdrawal(&withdrawal, 10, 1),
+        ];
+        let mut block = Block { header: genesis, batch_size: 4, transactions };
+        block.header = genesis.next(1_700_000_000, accounts.root(), block.tx_root());
+        block
+    }
+
+    #[test]
+    fn test_native_hashes_match_circuit() {
+        let block = block();
+        block.check().unwrap();
+        let circuit = BlockHashCircuit {
+            batch_size: block.batch_size as usize,
+            transactions: block.transactions.iter().map(|tx| tx.fields().map(Value::known)).collect(),
+        };
+        let mut public_inputs = block.transactions.iter().map(Transaction::hash).collect::<Vec<_>>();
+        public_inputs.push(block.tx_root());
+        MockProver::run(12, &circuit, vec![public_inputs.clone()]).unwrap().assert_satisfied();
+
+        // The root is hashed in `TxHash`, not in `TxFields` like the levels below it
+        let mut fields_root = public_inputs.clone();
+        fields_root[0] = build_merkle_tree::<TxFields>(&block.transactions[0].fields(), Fp::zero()).last().unwrap()[0];
+        assert_ne!(fields_root[0], public_inputs[0]);
+        assert!(MockProver::run(12, &circuit, vec![fields_root]).unwrap().verify().is_err());
+
+        // A different fee changes the hash the circuit computes
+        let mut circuit = circuit;
+        circuit.transactions[0][8] = Value::known(Fp::from(3));
+        assert!(MockProver::run(12, &circuit, vec![public_inputs]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_canonical_block_encoding() {
+        let block = block();
+        let bytes = block.to_bytes();
+        assert_eq!(bytes.len(), 112 + 8 + 4 + 3 * (8 * 8 + 2 * 32));
+        let decoded = Block::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, block);
+        let kinds = decoded.transactions.iter().map(|tx| tx.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [TxKind::Transfer, TxKind::Deposit, TxKind::Withdrawal]);
+        assert!(Block::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // A withdrawal and a transfer to pubkey 0 with the same fields differ only in the kind
+        let withdrawal = block.transactions[2];
+        let transfer = Transaction { kind: TxKind::Transfer, ..withdrawal };
+        assert_ne!(transfer.to_bytes(), withdrawal.to_bytes());
+        assert_ne!(transfer.hash(), withdrawal.hash());
+        assert_eq!(Transaction::from_bytes(&withdrawal.to_bytes()).unwrap(), withdrawal);
+
+        // The kind is the last field of a transaction
+        let mut unknown_kind = withdrawal.to_bytes();
+        let last = unknown_kind.len() - 8;
+        unknown_kind[last..].copy_from_slice(&3u64.to_bytes());
+        assert!(Transaction::from_bytes(&unknown_kind).is_err());
+
+        let account = Account { pubkey: Fp::from(11), balance: 100, nonce: 3 };
+        assert_eq!(Account::from_bytes(&account.to_bytes()).unwrap(), account);
+    }
+}
@@ -0,0 +1,73 @@
+//! Rollup block headers. Each header commits to its predecessor, so the header hashes
+//! form a chain that the block circuits extend one block at a time.
+
+use halo2_proofs::pasta::Fp;
+use serde::{Deserialize, Serialize};
+
+use super::encoding::{fp_hex, Decode, Encode};
+use super::hash_domain::HeaderHash;
+use super::poseidon_hash;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub number: u64,
+    pub timestamp: u64,
+    /// Hash of the previous header
+    #[serde(with = "fp_hex")]
+    pub prev_hash: Fp,
+    /// Account state root after the block
+    #[serde(with = "fp_hex")]
+    pub state_root: Fp,
+    /// Merkle root of the transaction hashes of the block
+    #[serde(with = "fp_hex")]
+    pub tx_root: Fp,
+}
+
+impl BlockHeader {
+    /// Header number 0, committing to the initial account state.
+    pub fn genesis(state_root: Fp) -> Self {
+        Self { state_root, ..Self::default() }
+    }
+
+    /// `H(H(H(H(number, timestamp), prev_hash), state_root), tx_root)`
+    pub fn hash(&self) -> Fp {
+        [self.prev_hash, self.state_root, self.tx_root]
+            .into_iter()
+            .fold(poseidon_hash::<HeaderHash>(&[Fp::from(self.number), Fp::from(self.timestamp)]), |acc, field| {
+                poseidon_hash::<HeaderHash>(&[acc, field])
+            })
+    }
+
+    /// Header of the block following this one.
+    pub fn next(&self, timestamp: u64, state_root: Fp, tx_root: Fp) -> Self {
+        Self {
+            number: self.number + 1,
+            timestamp,
+            prev_hash: self.hash(),
+            state_root,
+            tx_root,
+        }
+    }
+}
+
+impl Encode for BlockHeader {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.number.encode_to(out);
+        self.timestamp.encode_to(out);
+        for field in [self.prev_hash, self.state_root, self.tx_root] {
+            field.encode_to(out);
+        }
+    }
+}
+
+impl Decode for BlockHeader {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Ok(Self {
+            number: u64::decode_from(input)?,
+            timestamp: u64::decode_from(input)?,
+            prev_hash: Fp::decode_from(input)?,
+            state_root: Fp::decode_from(input)?,
+            tx_root: Fp::decode_from(input)?,
+        })
+    }
+}
@@ -0,0 +1,99 @@
+//! L1 side of the rollup bridge.
+//!
+//! Deposits are queued on L1 and consumed by the rollup in order; withdrawals are emitted
+//! by each batch. Both are committed to as Poseidon hash chains starting from zero:
+//!
+//! ```text
+//! chain' = H(chain, H(pubkey, amount))
+//! ```
+//!
+//! with the entry and chain hashes in the `BridgeEntryHash` and `BridgeChain` domains.
+
+use halo2_proofs::pasta::Fp;
+
+use super::hash_domain::{BridgeChain, BridgeEntryHash};
+use super::poseidon_hash;
+
+/// Deposit credited to, or withdrawal debited from, the account owned by `pubkey`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BridgeEntry {
+    pub pubkey: Fp,
+    pub amount: u64,
+}
+
+impl BridgeEntry {
+    pub fn hash(&self) -> Fp {
+        poseidon_hash::<BridgeEntryHash>(&[self.pubkey, Fp::from(self.amount)])
+    }
+}
+
+pub fn hash_chain(chain: Fp, entries: &[BridgeEntry]) -> Fp {
+    entries.iter().fold(chain, |chain, entry| poseidon_hash::<BridgeChain>(&[chain, entry.hash()]))
+}
+
+/// L1 deposit queue. The rollup proves it consumed the entries between the chain values
+/// before and after a batch.
+#[derive(Clone, Debug, Default)]
+pub struct DepositQueue {
+    entries: Vec<BridgeEntry>,
+    /// `chains[i]` is the hash chain of the first `i` entries
+    chains: Vec<Fp>,
+    consumed: usize,
+}
+
+impl DepositQueue {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            chains: vec![Fp::zero()],
+            consumed: 0,
+        }
+    }
+
+    /// Queues a deposit and returns the new chain value.
+    pub fn push(&mut self, entry: BridgeEntry) -> Fp {
+        let chain = hash_chain(*self.chains.last().unwrap(), &[entry]);
+        self.entries.push(entry);
+        self.chains.push(chain);
+        chain
+    }
+
+    /// Chain value of the entries consumed so far.
+    pub fn consumed_chain(&self) -> Fp {
+        self.chains[self.consumed]
+    }
+
+    pub fn pending(&self) -> &[BridgeEntry] {
+        &self.entries[self.consumed..]
+    }
+
+    /// Marks the next `count` entries as consumed and returns them.
+    pub fn consume(&mut self, count: usize) -> &[BridgeEntry] {
+        assert!(count <= self.pending().len(), "not enough pending deposits");
+        let start = self.consumed;
+        self.consumed += count;
+        &self.entries[start..self.consumed]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_queue_chain() {
+        let entries = [11, 22, 33].map(|i| BridgeEntry { pubkey: Fp::from(i), amount: i });
+        let mut queue = DepositQueue::new();
+        for entry in entries {
+            queue.push(entry);
+        }
+
+        assert_eq!(queue.consume(2), &entries[..2]);
+        assert_eq!(queue.consumed_chain(), hash_chain(Fp::zero(), &entries[..2]));
+        assert_eq!(queue.pending(), &entries[2..]);
+        // 이어서 소비한 체인은 전체 체인과 같음
+        let before = queue.consumed_chain();
+        let rest = queue.consume(1).to_vec();
+        assert_eq!(hash_chain(before, &rest), hash_chain(Fp::zero(), &entries));
+    }
+}
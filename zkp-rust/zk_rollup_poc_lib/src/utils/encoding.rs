@@ -0,0 +1,136 @@
+//! Canonical byte encoding of the rollup types: integers are little-endian, field
+//! elements their 32-byte canonical representation and sequences are prefixed with their
+//! length as a `u32`. Decoding rejects non-canonical field elements and trailing bytes,
+//! so every value has exactly one encoding.
+
+use group::ff::PrimeField;
+use halo2_proofs::pasta::Fp;
+
+pub trait Encode {
+    fn encode_to(&self, out: &mut Vec<u8>);
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.encode_to(&mut out);
+        out
+    }
+}
+
+pub trait Decode: Sized {
+    /// Reads a value from the front of `input` and advances it.
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String>;
+
+    fn from_bytes(mut bytes: &[u8]) -> Result<Self, String> {
+        let value = Self::decode_from(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(format!("{} trailing bytes", bytes.len()));
+        }
+        Ok(value)
+    }
+}
+
+fn take<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], String> {
+    if input.len() < N {
+        return Err(format!("expected {} more bytes, got {}", N, input.len()));
+    }
+    let (head, tail) = input.split_at(N);
+    *input = tail;
+    Ok(head.try_into().unwrap())
+}
+
+impl Encode for u64 {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Decode for u64 {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Ok(u64::from_le_bytes(take(input)?))
+    }
+}
+
+impl Encode for Fp {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_repr());
+    }
+}
+
+impl Decode for Fp {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Option::from(Fp::from_repr(take(input)?)).ok_or_else(|| "non-canonical field element".to_string())
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.len() as u32).to_le_bytes());
+        for item in self {
+            item.encode_to(out);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        let len = u32::from_le_bytes(take(input)?) as usize;
+        (0..len).map(|_| T::decode_from(input)).collect()
+    }
+}
+
+/// `0x`-prefixed big-endian hex, as printed by `{:?}`.
+pub fn fp_to_hex(value: &Fp) -> String {
+    let digits = value.to_repr().iter().rev().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    format!("0x{}", digits)
+}
+
+pub fn fp_from_hex(hex: &str) -> Result<Fp, String> {
+    let digits = hex.strip_prefix("0x").ok_or("missing 0x prefix")?;
+    if digits.len() != 64 || !digits.is_ascii() {
+        return Err(format!("expected 64 hex digits, got {:?}", digits));
+    }
+    let mut repr = [0u8; 32];
+    for (i, byte) in repr.iter_mut().rev().enumerate() {
+        *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).map_err(|e| e.to_string())?;
+    }
+    Fp::decode_from(&mut &repr[..])
+}
+
+/// `#[serde(with = "fp_hex")]` for field elements, as `fp_to_hex` strings.
+pub mod fp_hex {
+    use halo2_proofs::pasta::Fp;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Fp, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::fp_to_hex(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fp, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        super::fp_from_hex(&hex).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_encoding() {
+        let values = vec![Fp::zero(), Fp::from(7), -Fp::one()];
+        let bytes = values.to_bytes();
+        assert_eq!(bytes.len(), 4 + 3 * 32);
+        assert_eq!(Vec::<Fp>::from_bytes(&bytes).unwrap(), values);
+
+        // Trailing bytes and the modulus itself are rejected
+        assert!(u64::from_bytes(&[0; 9]).is_err());
+        let mut modulus = (-Fp::one()).to_repr();
+        modulus[0] += 1;
+        assert!(Fp::from_bytes(&modulus).is_err());
+
+        for value in values {
+            assert_eq!(fp_to_hex(&value), format!("{:?}", value));
+            assert_eq!(fp_from_hex(&fp_to_hex(&value)).unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Domain separation of the rollup's Poseidon hashes.
+//!
+//! Every hash is Poseidon (`P128Pow5T3`) over two field elements, a single permutation.
+//! Each hashing context starts the sponge from its own capacity element,
+//! `2 * 2^64 + TAG * 2^96`, so an output of one context is no preimage in another: an
+//! internal tree node cannot pose as a transaction or account leaf. The transaction field
+//! tree hashes its lower levels in `TxFields` and its root in `TxHash`, so no node of that
+//! tree is a transaction hash. `Tagged<0>` is exactly the `ConstantLength<2>` hash of
+//! `halo2_gadgets`.
+//!
+//! In the circuit the same domains are passed to `PoseidonChip::hash`.
+
+use std::iter;
+
+use group::ff::PrimeField;
+use halo2_gadgets::poseidon::primitives::{Domain, P128Pow5T3, Spec};
+use halo2_proofs::pasta::Fp;
+
+use crate::constants::{POSEIDON_INPUTS, POSEIDON_RATE, POSEIDON_WIDTH};
+
+/// Hashing context `TAG`, a sponge domain over `POSEIDON_INPUTS` elements.
+#[derive(Clone, Copy, Debug)]
+pub struct Tagged<const TAG: u64>;
+
+impl<const TAG: u64> Domain<Fp, POSEIDON_RATE> for Tagged<TAG> {
+    type Padding = iter::Empty<Fp>;
+
+    fn name() -> String {
+        format!("Tagged<{}>", TAG)
+    }
+
+    fn initial_capacity_element() -> Fp {
+        Fp::from_u128(((TAG as u128) << 96) + ((POSEIDON_INPUTS as u128) << 64))
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        // 입력 2개가 rate를 정확히 채우므로 패딩 없음
+        assert_eq!(input_len, POSEIDON_INPUTS);
+        iter::empty()
+    }
+}
+
+/// Internal nodes of the transaction and account trees, empty subtrees included
+pub type MerkleNode = Tagged<1>;
+/// Levels of the transaction field tree below the root
+pub type TxFields = Tagged<2>;
+/// Account leaf `H(H(pubkey, balance), nonce)`
+pub type AccountLeaf = Tagged<3>;
+/// Block header hash
+pub type HeaderHash = Tagged<4>;
+/// Bridge entry `H(pubkey, amount)`
+pub type BridgeEntryHash = Tagged<5>;
+/// Deposit and withdrawal hash chains
+pub type BridgeChain = Tagged<6>;
+/// Account address `H(PK.x, PK.y)`
+pub type Address = Tagged<7>;
+/// Schnorr challenge
+pub type Challenge = Tagged<8>;
+/// Off-circuit derivations: the Schnorr offset point and signing nonces
+pub type Derivation = Tagged<9>;
+/// Root of the transaction field tree: the transaction hash
+pub type TxHash = Tagged<10>;
+
+type State = [Fp; POSEIDON_WIDTH];
+
+/// The Poseidon permutation, as `halo2_gadgets::poseidon::primitives` computes it.
+fn permute(state: &mut State, mds: &[State; POSEIDON_WIDTH], round_constants: &[State]) {
+    let r_f = <P128Pow5T3 as Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>>::full_rounds() / 2;
+    let r_p = <P128Pow5T3 as Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>>::partial_rounds();
+    let sbox = <P128Pow5T3 as Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>>::sbox;
+
+    let apply_mds = |state: &mut State| {
+        let mut next = [Fp::zero(); POSEIDON_WIDTH];
+        for (i, row) in mds.iter().enumerate() {
+            for (entry, word) in row.iter().zip(state.iter()) {
+                next[i] += *entry * word;
+            }
+        }
+        *state = next;
+    };
+
+    for (round, constants) in round_constants.iter().enumerate() {
+        for (word, constant) in state.iter_mut().zip(constants) {
+            *word += constant;
+        }
+        if round < r_f || round >= r_f + r_p {
+            for word in state.iter_mut() {
+                *word = sbox(*word);
+            }
+        } else {
+            // partial round: S-box는 첫 번째 원소에만
+            state[0] = sbox(state[0]);
+        }
+        apply_mds(state);
+    }
+}
+
+/// Native Poseidon hash of `inputs` in domain `D`, the sponge of `PoseidonChip::hash`.
+pub fn poseidon_hash<D: Domain<Fp, POSEIDON_RATE>>(inputs: &[Fp; POSEIDON_INPUTS]) -> Fp {
+    let (round_constants, mds, _) = <P128Pow5T3 as Spec<Fp, POSEIDON_WIDTH, POSEIDON_RATE>>::constants();
+    let mut state = [inputs[0], inputs[1], D::initial_capacity_element()];
+    permute(&mut state, &mds, &round_constants);
+    state[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_gadgets::poseidon::primitives::{ConstantLength, Hash as PoseidonHash};
+
+    #[test]
+    fn test_domain_separation() {
+        let inputs = [Fp::from(1), Fp::from(2)];
+        let plain = PoseidonHash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(inputs);
+        assert_eq!(poseidon_hash::<Tagged<0>>(&inputs), plain);
+
+        let node = poseidon_hash::<MerkleNode>(&inputs);
+        assert_ne!(node, plain);
+        assert_ne!(node, poseidon_hash::<TxFields>(&inputs));
+        assert_ne!(node, poseidon_hash::<AccountLeaf>(&inputs));
+        assert_ne!(poseidon_hash::<TxFields>(&inputs), poseidon_hash::<TxHash>(&inputs));
+    }
+}
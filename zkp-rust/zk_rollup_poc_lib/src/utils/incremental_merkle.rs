@@ -0,0 +1,218 @@
+//! Append-only Merkle tree of fixed depth.
+//!
+//! Leaves are appended left to right and the tree keeps only its frontier: for every
+//! level the last left child whose right sibling is still empty. Empty subtrees hash to
+//! precomputed zero hashes, so an append and the new root cost `depth` hashes, whatever
+//! the number of leaves. Internal nodes are hashed in the `MerkleNode` domain, as in
+//! `MerkleTreeChip`.
+//!
+//! The tree cannot produce a path for an old leaf by itself. An `IncrementalWitness` is
+//! taken right after its leaf is appended and then fed every later leaf, which keeps its
+//! path valid for the current root, also in `O(depth)` per leaf.
+
+use halo2_proofs::pasta::Fp;
+
+use super::hash_domain::MerkleNode;
+use super::poseidon_hash;
+
+fn node_hash(left: Fp, right: Fp) -> Fp {
+    poseidon_hash::<MerkleNode>(&[left, right])
+}
+
+#[derive(Clone, Debug)]
+pub struct IncrementalMerkleTree {
+    /// `zero_hashes[level]` is the root of an empty subtree of height `level`
+    zero_hashes: Vec<Fp>,
+    /// `frontier[level]` is the last left child at `level`, valid for the levels where
+    /// the next leaf index has a 1 bit
+    frontier: Vec<Fp>,
+    last_leaf: Option<Fp>,
+    size: u64,
+    root: Fp,
+}
+
+impl IncrementalMerkleTree {
+    pub fn new(depth: usize, empty_leaf: Fp) -> Self {
+        assert!(depth < 64, "tree depth must fit u64 indices");
+        let mut zero_hashes = vec![empty_leaf];
+        for level in 0..depth {
+            zero_hashes.push(node_hash(zero_hashes[level], zero_hashes[level]));
+        }
+        Self::with_zero_hashes(zero_hashes)
+    }
+
+    fn with_zero_hashes(zero_hashes: Vec<Fp>) -> Self {
+        let depth = zero_hashes.len() - 1;
+        Self {
+            frontier: zero_hashes[..depth].to_vec(),
+            root: zero_hashes[depth],
+            zero_hashes,
+            last_leaf: None,
+            size: 0,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.zero_hashes.len() - 1
+    }
+
+    pub fn capacity(&self) -> u64 {
+        1 << self.depth()
+    }
+
+    /// Number of leaves appended so far.
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn root(&self) -> Fp {
+        self.root
+    }
+
+    /// Appends `leaf` and returns its index.
+    pub fn append(&mut self, leaf: Fp) -> Result<u64, String> {
+        if self.size == self.capacity() {
+            return Err(format!("tree of depth {} is full", self.depth()));
+        }
+        let index = self.size;
+        let mut current = leaf;
+        for level in 0..self.depth() {
+            current = if (index >> level) & 1 == 0 {
+                // 왼쪽 자식: 오른쪽은 아직 빈 subtree
+                self.frontier[level] = current;
+                node_hash(current, self.zero_hashes[level])
+            } else {
+                node_hash(self.frontier[level], current)
+            };
+        }
+        self.root = current;
+        self.last_leaf = Some(leaf);
+        self.size += 1;
+        Ok(index)
+    }
+
+    /// Witness of the leaf appended last, or `None` for an empty tree.
+    pub fn witness(&self) -> Option<IncrementalWitness> {
+        let leaf = self.last_leaf?;
+        let index = self.size - 1;
+        let siblings = (0..self.depth())
+            .map(|level| {
+                if (index >> level) & 1 == 1 {
+                    self.frontier[level]
+                } else {
+                    self.zero_hashes[level]
+                }
+            })
+            .collect();
+        Some(IncrementalWitness {
+            index,
+            leaf,
+            siblings,
+            zero_hashes: self.zero_hashes.clone(),
+            next: index + 1,
+            cursor: None,
+        })
+    }
+}
+
+/// Authentication path of one leaf of an `IncrementalMerkleTree`, kept up to date by
+/// `append`ing every leaf appended to the tree after it.
+#[derive(Clone, Debug)]
+pub struct IncrementalWitness {
+    index: u64,
+    leaf: Fp,
+    /// Sibling hashes from the leaf up, for the tree of `next` leaves
+    siblings: Vec<Fp>,
+    zero_hashes: Vec<Fp>,
+    /// Index of the next leaf to be appended
+    next: u64,
+    /// Right sibling subtree currently being filled, with its level
+    cursor: Option<(usize, IncrementalMerkleTree)>,
+}
+
+impl IncrementalWitness {
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub fn leaf(&self) -> Fp {
+        self.leaf
+    }
+
+    /// Records the next leaf appended to the tree. Only one sibling changes: the right
+    /// sibling subtree at the level where the paths of the two leaves meet.
+    pub fn append(&mut self, leaf: Fp) -> Result<(), String> {
+        let depth = self.siblings.len();
+        if self.next >> depth != 0 {
+            return Err(format!("tree of depth {} is full", depth));
+        }
+        let level = (63 - (self.index ^ self.next).leading_zeros()) as usize;
+        if !matches!(&self.cursor, Some((cursor_level, _)) if *cursor_level == level) {
+            // 새 오른쪽 subtree에 진입: 그 안의 leaf들만으로 다시 시작
+            let subtree = IncrementalMerkleTree::with_zero_hashes(self.zero_hashes[..=level].to_vec());
+            self.cursor = Some((level, subtree));
+        }
+        let (_, cursor) = self.cursor.as_mut().unwrap();
+        cursor.append(leaf)?;
+        self.siblings[level] = cursor.root();
+        self.next += 1;
+        Ok(())
+    }
+
+    /// Sibling hashes and path bits (`true` = right child) from the leaf up to the root,
+    /// as `get_merkle_proof`.
+    pub fn proof(&self) -> (Vec<Fp>, Vec<bool>) {
+        let path_bits = (0..self.siblings.len()).map(|level| (self.index >> level) & 1 == 1).collect();
+        (self.siblings.clone(), path_bits)
+    }
+
+    /// Root of the tree after the leaves seen so far.
+    pub fn root(&self) -> Fp {
+        let (siblings, path_bits) = self.proof();
+        super::merkle_root_from_path(self.leaf, &siblings, &path_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{build_merkle_tree, get_merkle_proof};
+
+    const DEPTH: usize = 4;
+
+    #[test]
+    fn test_incremental_tree_matches_full_tree() {
+        let empty_leaf = Fp::zero();
+        let mut tree = IncrementalMerkleTree::new(DEPTH, empty_leaf);
+        let mut witnesses: Vec<IncrementalWitness> = vec![];
+        let mut leaves = vec![];
+
+        assert_eq!(tree.root(), build_merkle_tree::<MerkleNode>(&[empty_leaf; 16], empty_leaf).last().unwrap()[0]);
+        for i in 0..tree.capacity() {
+            let leaf = Fp::from(100 + i);
+            for witness in &mut witnesses {
+                witness.append(leaf).unwrap();
+            }
+            assert_eq!(tree.append(leaf).unwrap(), i);
+            witnesses.push(tree.witness().unwrap());
+            leaves.push(leaf);
+
+            let mut padded = leaves.clone();
+            padded.resize(tree.capacity() as usize, empty_leaf);
+            let full = build_merkle_tree::<MerkleNode>(&padded, empty_leaf);
+            assert_eq!(tree.root(), full.last().unwrap()[0]);
+            // 이전 leaf들의 witness도 모두 현재 루트에 대해 유효
+            for witness in &witnesses {
+                assert_eq!(witness.proof(), get_merkle_proof(&full, witness.index() as usize));
+                assert_eq!(witness.root(), tree.root());
+            }
+        }
+
+        assert!(tree.append(Fp::one()).is_err());
+        assert!(witnesses[0].append(Fp::one()).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Minimal embedded key-value store: a directory holding an append-only log of write
+//! batches, replayed into memory when the store is opened.
+//!
+//! Each batch is one length-prefixed record, written and synced before it is applied, so
+//! a crash leaves at most a torn last record, which is dropped on the next open.
+//!
+//! ```text
+//! record = len: u32 | op*            (len = byte length of the ops)
+//! op     = 1 | key_len: u32 | key | value_len: u32 | value     (put)
+//!        | 0 | key_len: u32 | key                              (delete)
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_FILE: &str = "store.log";
+
+/// Puts and deletes applied atomically by `KvStore::write`.
+#[derive(Clone, Debug, Default)]
+pub struct WriteBatch {
+    ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(&mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) {
+        self.ops.push((key.into(), Some(value.into())));
+    }
+
+    pub fn delete(&mut self, key: impl Into<Vec<u8>>) {
+        self.ops.push((key.into(), None));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Appends the ops of `other`, applied after the ones of `self`.
+    pub fn extend(&mut self, other: WriteBatch) {
+        self.ops.extend(other.ops);
+    }
+
+    /// Log record of the batch.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut ops = vec![];
+        for (key, value) in &self.ops {
+            ops.push(value.is_some() as u8);
+            ops.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            ops.extend_from_slice(key);
+            if let Some(value) = value {
+                ops.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                ops.extend_from_slice(value);
+            }
+        }
+        let mut record = (ops.len() as u32).to_le_bytes().to_vec();
+        record.extend(ops);
+        record
+    }
+
+    /// Inverse of `encode`.
+    pub fn decode_record(bytes: &[u8]) -> io::Result<Self> {
+        match bytes.get(4..) {
+            Some(ops) if bytes[..4] == (ops.len() as u32).to_le_bytes() => Self::decode(ops),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "bad write batch record")),
+        }
+    }
+
+    /// Inverse of the ops part of `encode`.
+    fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        fn take<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+            if bytes.len() < len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated write batch"));
+            }
+            let (head, tail) = bytes.split_at(len);
+            *bytes = tail;
+            Ok(head)
+        }
+        fn take_len(bytes: &mut &[u8]) -> io::Result<usize> {
+            Ok(u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap()) as usize)
+        }
+
+        let mut batch = Self::new();
+        while !bytes.is_empty() {
+            let op = take(&mut bytes, 1)?[0];
+            let len = take_len(&mut bytes)?;
+            let key = take(&mut bytes, len)?.to_vec();
+            match op {
+                1 => {
+                    let len = take_len(&mut bytes)?;
+                    batch.put(key, take(&mut bytes, len)?);
+                }
+                0 => batch.delete(key),
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown write batch op")),
+            }
+        }
+        Ok(batch)
+    }
+}
+
+pub struct KvStore {
+    dir: PathBuf,
+    log: File,
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl KvStore {
+    /// Opens the store in `dir`, creating it if needed.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE);
+        let bytes = if path.exists() { fs::read(&path)? } else { vec![] };
+
+        let mut entries = BTreeMap::new();
+        let mut offset = 0;
+        while bytes.len() - offset >= 4 {
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            let Some(ops) = bytes.get(offset + 4..offset + 4 + len) else {
+                break;
+            };
+            Self::apply(&mut entries, WriteBatch::decode(ops)?);
+            offset += 4 + len;
+        }
+
+        let log = OpenOptions::new().create(true).append(true).open(&path)?;
+        // Drop a torn last record
+        if offset < bytes.len() {
+            log.set_len(offset as u64)?;
+        }
+        Ok(Self { dir: dir.to_path_buf(), log, entries })
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    /// Entries whose key starts with `prefix`, in key order.
+    pub fn scan_prefix<'a>(&'a self, prefix: &'a [u8]) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + 'a {
+        self.entries
+            .range(prefix.to_vec()..)
+            .take_while(move |(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    /// Appends `batch` to the log and syncs it before applying it.
+    pub fn write(&mut self, batch: WriteBatch) -> io::Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.log.write_all(&batch.encode())?;
+        self.log.sync_data()?;
+        Self::apply(&mut self.entries, batch);
+        Ok(())
+    }
+
+    /// Rewrites the log as a single batch of the live entries.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut batch = WriteBatch::new();
+        for (key, value) in &self.entries {
+            batch.put(key.clone(), value.clone());
+        }
+        let path = self.dir.join(LOG_FILE);
+        let tmp = self.dir.join(format!("{}.tmp", LOG_FILE));
+        let mut file = File::create(&tmp)?;
+        file.write_all(&batch.encode())?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        self.log = OpenOptions::new().append(true).open(&path)?;
+        Ok(())
+    }
+
+    fn apply(entries: &mut BTreeMap<Vec<u8>, Vec<u8>>, batch: WriteBatch) {
+        for (key, value) in batch.ops {
+            match value {
+                Some(value) => entries.insert(key, value),
+                None => entries.remove(&key),
+            };
+        }
+    }
+}
+
+/// Fresh directory under the system temp dir, for tests.
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zk_rollup_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kv_store_reopen() {
+        let dir = test_dir("kv_store");
+        let mut store = KvStore::open(&dir).unwrap();
+        let mut batch = WriteBatch::new();
+        batch.put(b"a/1".to_vec(), b"one".to_vec());
+        batch.put(b"a/2".to_vec(), b"two".to_vec());
+        batch.put(b"b/1".to_vec(), b"other".to_vec());
+        store.write(batch).unwrap();
+        let mut batch = WriteBatch::new();
+        batch.delete(b"a/1".to_vec());
+        store.write(batch).unwrap();
+        drop(store);
+
+        // A torn record at the end of the log is dropped
+        let mut log = OpenOptions::new().append(true).open(dir.join(LOG_FILE)).unwrap();
+        log.write_all(&[100, 0, 0, 0, 1, 2]).unwrap();
+        drop(log);
+
+        let mut store = KvStore::open(&dir).unwrap();
+        assert_eq!(store.get(b"a/1"), None);
+        let prefixed = store.scan_prefix(b"a/").collect::<Vec<_>>();
+        assert_eq!(prefixed, vec![(&b"a/2"[..], &b"two"[..])]);
+
+        store.compact().unwrap();
+        let mut batch = WriteBatch::new();
+        batch.put(b"a/3".to_vec(), b"three".to_vec());
+        store.write(batch).unwrap();
+        let store = KvStore::open(&dir).unwrap();
+        assert_eq!(store.scan_prefix(b"a/").count(), 2);
+        assert_eq!(store.get(b"b/1"), Some(&b"other"[..]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
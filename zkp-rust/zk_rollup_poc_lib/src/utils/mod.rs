@@ -0,0 +1,82 @@
+use halo2_gadgets::poseidon::primitives::Domain;
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::{
+    circuit::{Layouter, AssignedCell},
+    plonk::{Instance, Column, Error},
+};
+
+use crate::constants::POSEIDON_RATE;
+use hash_domain::MerkleNode;
+
+pub mod block_header;
+pub mod bridge;
+pub mod encoding;
+pub mod hash_domain;
+pub mod incremental_merkle;
+pub mod kv_store;
+pub mod multi_proof;
+pub mod schnorr;
+pub mod sparse_merkle;
+pub mod state_db;
+
+pub use block_header::BlockHeader;
+pub use bridge::{BridgeEntry, DepositQueue};
+pub use hash_domain::poseidon_hash;
+pub use incremental_merkle::{IncrementalMerkleTree, IncrementalWitness};
+pub use multi_proof::{check_multi_proof_indices, fold_multi_proof, get_multi_proof, multi_root_from_proof};
+pub use sparse_merkle::{Account, AccountTree, AccountUpdate, SparseMerkleTree, TransferWitness};
+pub use state_db::StateDb;
+
+pub fn expose_public(
+    layouter: &mut impl Layouter<Fp>,
+    cell: &AssignedCell<Fp, Fp>,
+    instance: Column<Instance>,
+    row: usize,
+) -> Result<(), Error> {
+    layouter.constrain_instance(cell.cell(), instance, row)
+}
+
+/// Merkle tree over `hashes`, padded with `padding` to a power of two and hashed in
+/// domain `D`. Returns the layers from the leaves up to the root. For large or growing
+/// trees use `IncrementalMerkleTree`, which keeps only the frontier.
+pub fn build_merkle_tree<D: Domain<Fp, POSEIDON_RATE>>(hashes: &[Fp], padding: Fp) -> Vec<Vec<Fp>> {
+    let mut padded = hashes.to_vec();
+    let next_pow_of_2 = hashes.len().next_power_of_two();
+    padded.resize(next_pow_of_2, padding);
+
+    let mut tree = vec![padded];
+    while tree.last().unwrap().len() > 1 {
+        let layer = tree.last().unwrap();
+        // 2의 거듭제곱으로 패딩했으므로 항상 짝이 맞음
+        let next_layer = layer.chunks(2).map(|pair| poseidon_hash::<D>(&[pair[0], pair[1]])).collect();
+        tree.push(next_layer);
+    }
+    tree
+}
+
+pub fn get_merkle_proof(tree: &[Vec<Fp>], leaf_index: usize) -> (Vec<Fp>, Vec<bool>) {
+    let mut index = leaf_index;
+    let mut proof = vec![];
+    let mut path_bits = vec![];
+
+    for level in &tree[..tree.len() - 1] {
+        proof.push(level[index ^ 1]);
+        path_bits.push(index % 2 == 1); // right if odd
+        index /= 2;
+    }
+
+    (proof, path_bits)
+}
+
+/// Recomputes the root from a leaf and its `get_merkle_proof` style path, hashing the
+/// internal nodes in the `MerkleNode` domain.
+pub fn merkle_root_from_path(leaf: Fp, proof: &[Fp], path_bits: &[bool]) -> Fp {
+    assert_eq!(proof.len(), path_bits.len());
+    proof.iter().zip(path_bits).fold(leaf, |current, (sibling, is_right)| {
+        if *is_right {
+            poseidon_hash::<MerkleNode>(&[*sibling, current])
+        } else {
+            poseidon_hash::<MerkleNode>(&[current, *sibling])
+        }
+    })
+}
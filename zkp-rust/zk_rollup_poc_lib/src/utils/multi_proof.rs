@@ -0,0 +1,147 @@
+//! Merkle multi-proofs: one proof for several leaves of the same tree.
+//!
+//! The paths of the leaves are walked together, level by level from the leaves up and
+//! left to right within a level. A node whose sibling is also on some path is paired
+//! with it, so every shared node is hashed once. Only the siblings that are on no path
+//! are in the proof, in the order the walk needs them. `fold_multi_proof` is that walk;
+//! the native functions here and `MerkleTreeChip::compute_multi_root` all use it.
+
+use std::convert::Infallible;
+
+use halo2_proofs::pasta::Fp;
+
+use super::hash_domain::MerkleNode;
+use super::poseidon_hash;
+
+/// Checks that `indices` are non-empty, sorted, distinct and below `2^depth`, with one
+/// of `leaf_count` leaves each.
+pub fn check_multi_proof_indices(depth: usize, indices: &[usize], leaf_count: usize) -> Result<(), String> {
+    if indices.is_empty() || indices.len() != leaf_count {
+        return Err(format!("{} indices for {} leaves", indices.len(), leaf_count));
+    }
+    if !indices.windows(2).all(|pair| pair[0] < pair[1]) {
+        return Err("indices must be sorted and distinct".to_string());
+    }
+    let last = indices[indices.len() - 1];
+    if last.checked_shr(depth as u32).unwrap_or(0) != 0 {
+        return Err(format!("leaf index {last} out of range for depth {depth}"));
+    }
+    Ok(())
+}
+
+/// Walks the multi-proof of the leaves at `indices` (sorted, distinct) in a tree of
+/// `depth` levels and returns the root. `sibling(level, index)` supplies the sibling
+/// nodes that are not computed from the leaves, `hash(level, index, left, right)`
+/// computes the parent at `(level + 1, index)` of two nodes at `level`.
+///
+/// Panics unless `check_multi_proof_indices` accepts `indices`; callers with untrusted
+/// indices check them first.
+pub fn fold_multi_proof<T, E>(
+    depth: usize,
+    indices: &[usize],
+    leaves: Vec<T>,
+    mut sibling: impl FnMut(usize, usize) -> Result<T, E>,
+    mut hash: impl FnMut(usize, usize, T, T) -> Result<T, E>,
+) -> Result<T, E> {
+    if let Err(e) = check_multi_proof_indices(depth, indices, leaves.len()) {
+        panic!("{e}");
+    }
+
+    let mut layer = indices.iter().copied().zip(leaves).collect::<Vec<_>>();
+    for level in 0..depth {
+        let mut next = vec![];
+        let mut nodes = layer.into_iter().peekable();
+        while let Some((index, node)) = nodes.next() {
+            let (left, right) = if index % 2 == 1 {
+                (sibling(level, index - 1)?, node)
+            } else if let Some((_, right)) = nodes.next_if(|(next, _)| *next == index + 1) {
+                // 형제도 경로 위에 있으므로 증명에서 가져오지 않음
+                (node, right)
+            } else {
+                (node, sibling(level, index + 1)?)
+            };
+            next.push((index / 2, hash(level, index / 2, left, right)?));
+        }
+        layer = next;
+    }
+    Ok(layer.pop().unwrap().1)
+}
+
+/// Multi-proof of the leaves at `indices` (sorted, distinct) in a `build_merkle_tree`
+/// tree: the siblings that are on none of their paths.
+pub fn get_multi_proof(tree: &[Vec<Fp>], indices: &[usize]) -> Vec<Fp> {
+    let mut proof = vec![];
+    let walked = fold_multi_proof::<(), Infallible>(
+        tree.len() - 1,
+        indices,
+        vec![(); indices.len()],
+        |level, index| {
+            proof.push(tree[level][index]);
+            Ok(())
+        },
+        |_, _, _, _| Ok(()),
+    );
+    let Ok(()) = walked;
+    proof
+}
+
+/// Recomputes the root from the `leaves` at `indices` and their `get_multi_proof`,
+/// hashing the internal nodes in the `MerkleNode` domain. Malformed indices or proofs
+/// are errors.
+pub fn multi_root_from_proof(depth: usize, indices: &[usize], leaves: &[Fp], proof: &[Fp]) -> Result<Fp, String> {
+    check_multi_proof_indices(depth, indices, leaves.len())?;
+    let mut siblings = proof.iter();
+    let root = fold_multi_proof(
+        depth,
+        indices,
+        leaves.to_vec(),
+        |_, _| siblings.next().copied().ok_or_else(|| "multi-proof is missing siblings".to_string()),
+        |_, _, left, right| Ok(poseidon_hash::<MerkleNode>(&[left, right])),
+    )?;
+    if siblings.next().is_some() {
+        return Err("multi-proof has unused siblings".to_string());
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{build_merkle_tree, get_merkle_proof};
+
+    #[test]
+    fn test_multi_proof() {
+        let leaves = (0..16).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        let tree = build_merkle_tree::<MerkleNode>(&leaves, Fp::zero());
+        let root = tree.last().unwrap()[0];
+
+        for indices in [vec![5], vec![0, 1], vec![1, 2, 3, 6], vec![0, 7, 8, 15]] {
+            let values = indices.iter().map(|&i| leaves[i]).collect::<Vec<_>>();
+            let proof = get_multi_proof(&tree, &indices);
+            assert_eq!(multi_root_from_proof(4, &indices, &values, &proof), Ok(root));
+
+            // 개별 경로보다 길지 않음
+            let separate = indices.iter().map(|&i| get_merkle_proof(&tree, i).0.len()).sum::<usize>();
+            assert!(proof.len() <= separate);
+
+            let mut wrong = values.clone();
+            wrong[0] += Fp::one();
+            assert_ne!(multi_root_from_proof(4, &indices, &wrong, &proof), Ok(root));
+            assert!(multi_root_from_proof(4, &indices, &values, &proof[1..]).is_err());
+        }
+
+        // 잘못된 입력은 panic 대신 에러
+        let values = [leaves[1], leaves[2]];
+        let proof = get_multi_proof(&tree, &[1, 2]);
+        assert!(multi_root_from_proof(4, &[2, 1], &values, &proof).is_err());
+        assert!(multi_root_from_proof(4, &[1, 1], &values, &proof).is_err());
+        assert!(multi_root_from_proof(4, &[1], &values, &proof).is_err());
+        assert!(multi_root_from_proof(4, &[], &[], &proof).is_err());
+        assert!(multi_root_from_proof(4, &[1, 16], &values, &proof).is_err());
+        assert!(multi_root_from_proof(4, &[1, usize::MAX], &values, &proof).is_err());
+        assert!(multi_root_from_proof(4, &[1, 2], &values, &[proof.clone(), vec![Fp::one()]].concat()).is_err());
+
+        // 0, 1은 레벨 0에서 짝: 레벨 1, 2, 3의 형제 3개만 필요
+        assert_eq!(get_multi_proof(&tree, &[0, 1]), vec![tree[1][1], tree[2][1], tree[3][1]]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Schnorr signatures over the Pallas curve with a Poseidon challenge.
+//!
+//! The Pallas base field is the circuit field `Fp`, so points can be handled natively
+//! in the circuit (see `circuits::schnorr_chip`). A signature `(R, s)` on `msg` under
+//! the public key `PK` is valid when
+//!
+//! ```text
+//! [s]G == R + [e]PK,   e = H(H(H(R.x, R.y), address(PK)), msg),   address(PK) = H(PK.x, PK.y)
+//! ```
+//!
+//! The challenge hashes and the address use the `Challenge` and `Address` domains of
+//! `utils::hash_domain`.
+//!
+//! Both `s` and `e` are used as `SCALAR_BITS`-bit integers in the circuit; the signer
+//! retries with a fresh nonce in the (negligible) case that either one does not fit.
+
+use group::ff::{Field, PrimeField};
+use group::{Curve, Group};
+use halo2_proofs::pasta::Fp;
+use pasta_curves::{arithmetic::CurveAffine, pallas};
+use rand_core::RngCore;
+
+use super::hash_domain::{Address, Challenge, Derivation};
+use super::poseidon_hash;
+
+/// Bit length of the scalars decomposed in the circuit. `2^254 < p`, so the bit
+/// decomposition of a field element of this size is unique.
+pub const SCALAR_BITS: usize = 254;
+
+fn fits_scalar_bits(value: &Fp) -> bool {
+    value.to_repr().as_ref()[31] < 0x40
+}
+
+fn to_scalar(value: &Fp) -> pallas::Scalar {
+    // p < q, so every base field element is a valid scalar
+    pallas::Scalar::from_repr(value.to_repr()).unwrap()
+}
+
+pub fn point_coordinates(point: &pallas::Affine) -> (Fp, Fp) {
+    let coordinates = point.coordinates().unwrap();
+    (*coordinates.x(), *coordinates.y())
+}
+
+/// Start point of the in-circuit double-and-add. It is found by hashing to an
+/// x-coordinate on the curve, so its discrete log is unknown.
+pub fn offset_point() -> pallas::Affine {
+    (0u64..)
+        .find_map(|i| {
+            let x = poseidon_hash::<Derivation>(&[Fp::from(i), Fp::zero()]);
+            let y: Option<Fp> = (x.square() * x + Fp::from(5)).sqrt().into();
+            y.map(|y| pallas::Affine::from_xy(x, y).unwrap())
+        })
+        .unwrap()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey(pub pallas::Affine);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub r: pallas::Affine,
+    pub s: Fp,
+}
+
+pub struct SigningKey(pallas::Scalar);
+
+/// Fiat-Shamir challenge `H(H(H(R.x, R.y), address), msg)`.
+pub fn challenge(r: &pallas::Affine, address: Fp, msg: Fp) -> Fp {
+    let (r_x, r_y) = point_coordinates(r);
+    let r_hash = poseidon_hash::<Challenge>(&[r_x, r_y]);
+    poseidon_hash::<Challenge>(&[poseidon_hash::<Challenge>(&[r_hash, address]), msg])
+}
+
+impl PublicKey {
+    pub fn coordinates(&self) -> (Fp, Fp) {
+        point_coordinates(&self.0)
+    }
+
+    /// Account address: `H(PK.x, PK.y)`
+    pub fn address(&self) -> Fp {
+        let (x, y) = self.coordinates();
+        poseidon_hash::<Address>(&[x, y])
+    }
+
+    pub fn verify(&self, msg: Fp, signature: &Signature) -> bool {
+        let e = challenge(&signature.r, self.address(), msg);
+        if !fits_scalar_bits(&e) || !fits_scalar_bits(&signature.s) {
+            return false;
+        }
+        let lhs = pallas::Point::generator() * to_scalar(&signature.s);
+        let rhs = pallas::Point::from(signature.r) + pallas::Point::from(self.0) * to_scalar(&e);
+        lhs == rhs
+    }
+}
+
+impl SigningKey {
+    pub fn new(secret: pallas::Scalar) -> Self {
+        assert!(!bool::from(secret.is_zero()), "secret key must be non-zero");
+        Self(secret)
+    }
+
+    pub fn random(rng: impl RngCore) -> Self {
+        Self::new(pallas::Scalar::random(rng))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey((pallas::Point::generator() * self.0).to_affine())
+    }
+
+    /// Deterministic nonce `H(H(sk_lo, sk_hi), H(msg, counter))`, so signing needs no RNG.
+    fn nonce(&self, msg: Fp, counter: u64) -> pallas::Scalar {
+        let repr = self.0.to_repr();
+        let limb = |bytes: &[u8]| Fp::from_u128(u128::from_le_bytes(bytes.try_into().unwrap()));
+        let secret = poseidon_hash::<Derivation>(&[limb(&repr[..16]), limb(&repr[16..])]);
+        to_scalar(&poseidon_hash::<Derivation>(&[secret, poseidon_hash::<Derivation>(&[msg, Fp::from(counter)])]))
+    }
+
+    pub fn sign(&self, msg: Fp) -> Signature {
+        let address = self.public_key().address();
+        (0u64..)
+            .find_map(|counter| {
+                let k = self.nonce(msg, counter);
+                let r = (pallas::Point::generator() * k).to_affine();
+                let e = challenge(&r, address, msg);
+                if !fits_scalar_bits(&e) {
+                    return None;
+                }
+                let s: Option<Fp> = Fp::from_repr((k + to_scalar(&e) * self.0).to_repr()).into();
+                s.filter(fits_scalar_bits).map(|s| Signature { r, s })
+            })
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_verify() {
+        let key = SigningKey::new(pallas::Scalar::from(7));
+        let public_key = key.public_key();
+        let msg = Fp::from(1234);
+        let signature = key.sign(msg);
+
+        assert!(public_key.verify(msg, &signature));
+        assert!(!public_key.verify(msg + Fp::one(), &signature));
+        assert!(!SigningKey::new(pallas::Scalar::from(8)).public_key().verify(msg, &signature));
+    }
+}
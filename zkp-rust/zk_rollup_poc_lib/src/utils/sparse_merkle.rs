@@ -0,0 +1,382 @@
+//! Sparse Merkle tree of rollup accounts.
+//!
+//! Every one of the `2^depth` slots starts out holding the empty account, so the tree
+//! only stores nodes that differ from the precomputed empty subtree hashes. Proofs use
+//! the same `(siblings, path_bits)` layout as `get_merkle_proof`, bottom-up.
+
+use std::collections::{BTreeMap, HashMap};
+
+use halo2_proofs::pasta::Fp;
+use serde::{Deserialize, Serialize};
+
+use super::bridge::BridgeEntry;
+use super::encoding::{fp_hex, Decode, Encode};
+use super::hash_domain::{AccountLeaf, MerkleNode};
+use super::poseidon_hash;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    /// Public key (hash) of the account owner
+    #[serde(with = "fp_hex")]
+    pub pubkey: Fp,
+    pub balance: u64,
+    pub nonce: u64,
+}
+
+impl Account {
+    /// Leaf stored in the account tree: `H(H(pubkey, balance), nonce)`, as
+    /// `AccountTreeChip::account_leaf`.
+    pub fn hash(&self) -> Fp {
+        let inner = poseidon_hash::<AccountLeaf>(&[self.pubkey, Fp::from(self.balance)]);
+        poseidon_hash::<AccountLeaf>(&[inner, Fp::from(self.nonce)])
+    }
+}
+
+impl Encode for Account {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.pubkey.encode_to(out);
+        self.balance.encode_to(out);
+        self.nonce.encode_to(out);
+    }
+}
+
+impl Decode for Account {
+    fn decode_from(input: &mut &[u8]) -> Result<Self, String> {
+        Ok(Self {
+            pubkey: Fp::decode_from(input)?,
+            balance: u64::decode_from(input)?,
+            nonce: u64::decode_from(input)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree {
+    depth: usize,
+    /// `empty_hashes[level]` is the root of an empty subtree of height `level`
+    empty_hashes: Vec<Fp>,
+    /// Non-empty nodes keyed by `(level, index)`, level 0 being the leaves
+    nodes: HashMap<(usize, u64), Fp>,
+}
+
+impl SparseMerkleTree {
+    pub fn new(depth: usize, empty_leaf: Fp) -> Self {
+        assert!(depth < 64, "tree depth must fit u64 indices");
+        let mut empty_hashes = vec![empty_leaf];
+        for level in 0..depth {
+            let below = empty_hashes[level];
+            empty_hashes.push(poseidon_hash::<MerkleNode>(&[below, below]));
+        }
+        Self {
+            depth,
+            empty_hashes,
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn capacity(&self) -> u64 {
+        1 << self.depth
+    }
+
+    pub fn node(&self, level: usize, index: u64) -> Fp {
+        self.nodes
+            .get(&(level, index))
+            .copied()
+            .unwrap_or(self.empty_hashes[level])
+    }
+
+    pub fn root(&self) -> Fp {
+        self.node(self.depth, 0)
+    }
+
+    pub fn leaf(&self, index: u64) -> Fp {
+        self.node(0, index)
+    }
+
+    /// Node at `(level, index)` if it differs from the empty subtree hash.
+    pub fn stored_node(&self, level: usize, index: u64) -> Option<Fp> {
+        self.nodes.get(&(level, index)).copied()
+    }
+
+    /// Replaces the leaf at `index` and returns the new root.
+    pub fn update(&mut self, index: u64, leaf: Fp) -> Fp {
+        assert!(index < self.capacity(), "leaf index out of range");
+        let mut index = index;
+        let mut current = leaf;
+        for level in 0..self.depth {
+            self.set_node(level, index, current);
+            let sibling = self.node(level, index ^ 1);
+            current = if index & 1 == 1 {
+                poseidon_hash::<MerkleNode>(&[sibling, current])
+            } else {
+                poseidon_hash::<MerkleNode>(&[current, sibling])
+            };
+            index >>= 1;
+        }
+        self.set_node(self.depth, 0, current);
+        current
+    }
+
+    /// Sibling hashes and path bits (`true` = right child) from the leaf up to the root.
+    pub fn proof(&self, index: u64) -> (Vec<Fp>, Vec<bool>) {
+        assert!(index < self.capacity(), "leaf index out of range");
+        (0..self.depth)
+            .map(|level| {
+                let index = index >> level;
+                (self.node(level, index ^ 1), index & 1 == 1)
+            })
+            .unzip()
+    }
+
+    fn set_node(&mut self, level: usize, index: u64, hash: Fp) {
+        if hash == self.empty_hashes[level] {
+            self.nodes.remove(&(level, index));
+        } else {
+            self.nodes.insert((level, index), hash);
+        }
+    }
+}
+
+/// One account leaf replaced in place. The sibling path is shared by the old and the
+/// new leaf, so it proves membership of `before` in the old root and yields the new root.
+#[derive(Clone, Debug)]
+pub struct AccountUpdate {
+    pub index: u64,
+    pub before: Account,
+    pub after: Account,
+    pub siblings: Vec<Fp>,
+    pub path_bits: Vec<bool>,
+}
+
+/// Witness of a transfer: the sender is updated first, then the receiver against the
+/// intermediate root.
+#[derive(Clone, Debug)]
+pub struct TransferWitness {
+    pub amount: u64,
+    /// Paid by the sender on top of `amount`, collected by the operator at the end of the batch
+    pub fee: u64,
+    pub old_root: Fp,
+    pub sender: AccountUpdate,
+    pub receiver: AccountUpdate,
+    pub new_root: Fp,
+}
+
+/// Account state of the rollup, indexed by account slot.
+#[derive(Clone, Debug)]
+pub struct AccountTree {
+    tree: SparseMerkleTree,
+    accounts: BTreeMap<u64, Account>,
+}
+
+impl AccountTree {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            tree: SparseMerkleTree::new(depth, Account::default().hash()),
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// Rebuilds the tree from stored accounts and non-empty nodes, e.g. loaded by
+    /// `StateDb`, without rehashing.
+    pub fn from_stored(
+        depth: usize,
+        accounts: BTreeMap<u64, Account>,
+        nodes: impl IntoIterator<Item = ((usize, u64), Fp)>,
+    ) -> Self {
+        let mut tree = SparseMerkleTree::new(depth, Account::default().hash());
+        tree.nodes.extend(nodes);
+        Self { tree, accounts }
+    }
+
+    pub fn root(&self) -> Fp {
+        self.tree.root()
+    }
+
+    /// Indices of the non-empty accounts.
+    pub fn indices(&self) -> impl Iterator<Item = u64> + '_ {
+        self.accounts.keys().copied()
+    }
+
+    pub fn tree(&self) -> &SparseMerkleTree {
+        &self.tree
+    }
+
+    pub fn account(&self, index: u64) -> Account {
+        self.accounts.get(&index).copied().unwrap_or_default()
+    }
+
+    /// Overwrites the account at `index` and returns the new root.
+    pub fn insert(&mut self, index: u64, account: Account) -> Fp {
+        if account == Account::default() {
+            self.accounts.remove(&index);
+        } else {
+            self.accounts.insert(index, account);
+        }
+        self.tree.update(index, account.hash())
+    }
+
+    /// Applies `f` to the account at `index`, recording the path for the circuit.
+    pub fn update(&mut self, index: u64, f: impl FnOnce(&mut Account)) -> AccountUpdate {
+        let before = self.account(index);
+        let mut after = before;
+        f(&mut after);
+        let (siblings, path_bits) = self.tree.proof(index);
+        self.insert(index, after);
+        AccountUpdate {
+            index,
+            before,
+            after,
+            siblings,
+            path_bits,
+        }
+    }
+
+    /// Moves `amount` from `sender` to `receiver` and takes `fee` from `sender`. `nonce`
+    /// must be the sender's current nonce, which is then incremented, so a signed transfer
+    /// cannot be replayed.
+    pub fn transfer(
+        &mut self,
+        sender: u64,
+        receiver: u64,
+        amount: u64,
+        fee: u64,
+        nonce: u64,
+    ) -> Result<TransferWitness, String> {
+        let sender_nonce = self.account(sender).nonce;
+        if nonce != sender_nonce {
+            return Err(format!("account {sender} expects nonce {sender_nonce}, got {nonce}"));
+        }
+        let sender_balance = self.account(sender).balance;
+        let total = amount.checked_add(fee).ok_or("amount plus fee overflows")?;
+        if sender_balance < total {
+            return Err(format!("account {sender} has balance {sender_balance}, cannot pay {amount} + fee {fee}"));
+        }
+        let receiver_balance = self.account(receiver).balance;
+        let receiver_balance = if sender == receiver { sender_balance - total } else { receiver_balance };
+        if receiver_balance.checked_add(amount).is_none() {
+            return Err(format!("account {receiver} balance overflows"));
+        }
+
+        let old_root = self.root();
+        let sender = self.update(sender, |account| {
+            account.balance -= total;
+            account.nonce += 1;
+        });
+        let receiver = self.update(receiver, |account| account.balance += amount);
+        Ok(TransferWitness {
+            amount,
+            fee,
+            old_root,
+            sender,
+            receiver,
+            new_root: self.root(),
+        })
+    }
+
+    /// Credits an L1 deposit to the account at `index`, which must be owned by `deposit.pubkey`.
+    pub fn deposit(&mut self, index: u64, deposit: &BridgeEntry) -> Result<AccountUpdate, String> {
+        let account = self.account(index);
+        if account.pubkey != deposit.pubkey {
+            return Err(format!("account {index} is not owned by the depositor"));
+        }
+        if account.balance.checked_add(deposit.amount).is_none() {
+            return Err(format!("account {index} balance overflows"));
+        }
+        Ok(self.update(index, |account| account.balance += deposit.amount))
+    }
+
+    /// Debits `amount` plus `fee` from the account at `index` for a withdrawal to L1.
+    /// Like a transfer, it is signed with the account's current `nonce`.
+    pub fn withdraw(&mut self, index: u64, amount: u64, fee: u64, nonce: u64) -> Result<AccountUpdate, String> {
+        let account = self.account(index);
+        if nonce != account.nonce {
+            return Err(format!("account {index} expects nonce {}, got {nonce}", account.nonce));
+        }
+        let total = amount.checked_add(fee).ok_or("amount plus fee overflows")?;
+        if account.balance < total {
+            return Err(format!("account {index} has balance {}, cannot withdraw {amount} + fee {fee}", account.balance));
+        }
+        Ok(self.update(index, |account| {
+            account.balance -= total;
+            account.nonce += 1;
+        }))
+    }
+
+    /// Credits the fees of a batch to the `operator` account.
+    pub fn collect_fees(&mut self, operator: u64, fees: u64) -> Result<AccountUpdate, String> {
+        let balance = self.account(operator).balance;
+        if balance.checked_add(fees).is_none() {
+            return Err(format!("operator account {operator} balance overflows"));
+        }
+        Ok(self.update(operator, |account| account.balance += fees))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::merkle_root_from_path;
+
+    #[test]
+    fn test_sparse_tree_proofs() {
+        let mut tree = SparseMerkleTree::new(4, Fp::zero());
+        let empty_root = tree.root();
+        tree.update(5, Fp::from(55));
+        let root = tree.update(12, Fp::from(1212));
+
+        for index in [0, 5, 12, 15] {
+            let (siblings, path_bits) = tree.proof(index);
+            assert_eq!(merkle_root_from_path(tree.leaf(index), &siblings, &path_bits), root);
+        }
+
+        // Clearing every leaf brings back the empty root
+        tree.update(5, Fp::zero());
+        assert_eq!(tree.update(12, Fp::zero()), empty_root);
+    }
+
+    #[test]
+    fn test_account_transfer_witness() {
+        let mut accounts = AccountTree::new(4);
+        accounts.insert(1, Account { pubkey: Fp::from(11), balance: 100, nonce: 0 });
+        accounts.insert(2, Account { pubkey: Fp::from(22), balance: 5, nonce: 0 });
+
+        let witness = accounts.transfer(1, 2, 30, 1, 0).unwrap();
+        let sender = &witness.sender;
+        assert_eq!(
+            merkle_root_from_path(sender.before.hash(), &sender.siblings, &sender.path_bits),
+            witness.old_root
+        );
+        let receiver = &witness.receiver;
+        assert_eq!(
+            merkle_root_from_path(receiver.after.hash(), &receiver.siblings, &receiver.path_bits),
+            witness.new_root
+        );
+        assert_eq!(accounts.account(1).balance, 69);
+        assert_eq!(accounts.account(2).balance, 35);
+        assert_eq!(accounts.account(1).nonce, 1);
+
+        assert!(accounts.transfer(2, 1, 36, 0, 0).is_err());
+        // The fee counts against the sender's balance
+        assert!(accounts.transfer(2, 1, 35, 1, 0).is_err());
+        // Replaying the first transfer is rejected
+        assert!(accounts.transfer(1, 2, 30, 1, 0).is_err());
+
+        let deposit = BridgeEntry { pubkey: Fp::from(22), amount: 10 };
+        assert_eq!(accounts.deposit(2, &deposit).unwrap().after.balance, 45);
+        assert!(accounts.deposit(1, &deposit).is_err());
+        let withdrawal = accounts.withdraw(2, 40, 1, 0).unwrap();
+        assert_eq!((withdrawal.after.balance, withdrawal.after.nonce), (4, 1));
+        assert!(accounts.withdraw(2, 4, 1, 1).is_err());
+
+        let operator = accounts.collect_fees(0, witness.fee).unwrap();
+        assert_eq!(operator.after.balance, 1);
+        assert_eq!(
+            merkle_root_from_path(operator.after.hash(), &operator.siblings, &operator.path_bits),
+            accounts.root()
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Durable account state of the rollup, on top of `KvStore`.
+//!
+//! The store holds the accounts and the non-empty sparse Merkle tree nodes keyed by path,
+//! plus the header of every committed block as a snapshot of its state root. Committing
+//! a block also records the previous value of every key it overwrites, so the state can
+//! be rolled back to an earlier block, e.g. when a batch fails to prove.
+//!
+//! ```text
+//! "depth"                          -> depth: u64
+//! "n" | level: u8 | index: u64     -> node
+//! "a" | index: u64                 -> Account
+//! "h" | number: u64                -> BlockHeader
+//! "u" | number: u64                -> undo journal of the block, as a WriteBatch
+//! ```
+//!
+//! Integers are big-endian in keys, so blocks scan in order. Values use the canonical
+//! encoding of `utils::encoding`.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use halo2_proofs::pasta::Fp;
+
+use super::encoding::{Decode, Encode};
+use super::kv_store::{KvStore, WriteBatch};
+use super::{Account, AccountTree, BlockHeader};
+
+const DEPTH_KEY: &[u8] = b"depth";
+const NODE_PREFIX: u8 = b'n';
+const ACCOUNT_PREFIX: u8 = b'a';
+const HEADER_PREFIX: u8 = b'h';
+const UNDO_PREFIX: u8 = b'u';
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn node_key(level: usize, index: u64) -> Vec<u8> {
+    let mut key = vec![NODE_PREFIX, level as u8];
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
+fn numbered_key(prefix: u8, number: u64) -> Vec<u8> {
+    let mut key = vec![prefix];
+    key.extend_from_slice(&number.to_be_bytes());
+    key
+}
+
+fn decode<T: Decode>(bytes: &[u8]) -> io::Result<T> {
+    T::from_bytes(bytes).map_err(invalid_data)
+}
+
+pub struct StateDb {
+    store: KvStore,
+    depth: usize,
+}
+
+impl StateDb {
+    /// Opens the database in `dir`. An empty database is initialised with `genesis` as
+    /// block 0; otherwise `genesis` only has to have the same tree depth.
+    pub fn open(dir: &Path, genesis: &AccountTree) -> io::Result<Self> {
+        let depth = genesis.tree().depth();
+        let mut db = Self { store: KvStore::open(dir)?, depth };
+        match db.store.get(DEPTH_KEY) {
+            Some(stored) => {
+                if stored != (depth as u64).to_le_bytes() {
+                    return Err(invalid_data("account tree depth does not match the database"));
+                }
+            }
+            None => {
+                let mut batch = WriteBatch::new();
+                batch.put(DEPTH_KEY, (depth as u64).to_le_bytes());
+                let touched = genesis.indices().collect::<Vec<_>>();
+                db.write_accounts(&mut batch, genesis, &touched, &mut WriteBatch::new());
+                batch.put(numbered_key(HEADER_PREFIX, 0), BlockHeader::genesis(genesis.root()).to_bytes());
+                db.store.write(batch)?;
+            }
+        }
+        Ok(db)
+    }
+
+    /// Header of the last committed block.
+    pub fn latest(&self) -> io::Result<BlockHeader> {
+        let prefix = [HEADER_PREFIX];
+        let (_, header) = self.store.scan_prefix(&prefix).last().ok_or_else(|| invalid_data("no block header"))?;
+        decode(header)
+    }
+
+    pub fn header(&self, number: u64) -> io::Result<Option<BlockHeader>> {
+        self.store.get(&numbered_key(HEADER_PREFIX, number)).map(decode).transpose()
+    }
+
+    /// Account state after the last committed block.
+    pub fn accounts(&self) -> io::Result<AccountTree> {
+        let mut accounts: BTreeMap<u64, Account> = BTreeMap::new();
+        for (key, value) in self.store.scan_prefix(&[ACCOUNT_PREFIX]) {
+            let index = u64::from_be_bytes(key[1..].try_into().map_err(|_| invalid_data("bad account key"))?);
+            accounts.insert(index, decode(value)?);
+        }
+        let mut nodes: Vec<((usize, u64), Fp)> = vec![];
+        for (key, value) in self.store.scan_prefix(&[NODE_PREFIX]) {
+            let index = u64::from_be_bytes(key[2..].try_into().map_err(|_| invalid_data("bad node key"))?);
+            nodes.push(((key[1] as usize, index), decode(value)?));
+        }
+        Ok(AccountTree::from_stored(self.depth, accounts, nodes))
+    }
+
+    /// Stores the block after the last committed one. `accounts` is the state after it
+    /// and `touched` the indices of the accounts it updated; only their leaves and paths
+    /// are written.
+    pub fn commit_block(&mut self, header: &BlockHeader, accounts: &AccountTree, touched: &[u64]) -> io::Result<()> {
+        let latest = self.latest()?;
+        if header.number != latest.number + 1 || header.prev_hash != latest.hash() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "block does not follow the last committed block"));
+        }
+        if header.state_root != accounts.root() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "block state root does not match the accounts"));
+        }
+
+        let mut batch = WriteBatch::new();
+        let mut undo = WriteBatch::new();
+        self.write_accounts(&mut batch, accounts, touched, &mut undo);
+        batch.put(numbered_key(HEADER_PREFIX, header.number), header.to_bytes());
+        batch.put(numbered_key(UNDO_PREFIX, header.number), undo.encode());
+        self.store.write(batch)
+    }
+
+    /// Undoes every block after `number`, which becomes the last committed block.
+    pub fn rollback(&mut self, number: u64) -> io::Result<()> {
+        let latest = self.latest()?;
+        if number > latest.number {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot roll forward"));
+        }
+        let mut batch = WriteBatch::new();
+        for block in (number + 1..=latest.number).rev() {
+            let undo_key = numbered_key(UNDO_PREFIX, block);
+            let undo = self.store.get(&undo_key).ok_or_else(|| invalid_data("missing undo journal"))?;
+            batch.extend(WriteBatch::decode_record(undo)?);
+            batch.delete(undo_key);
+            batch.delete(numbered_key(HEADER_PREFIX, block));
+        }
+        self.store.write(batch)?;
+        self.store.compact()
+    }
+
+    /// Rolls back to the last block whose state root is `root` and returns its header.
+    pub fn rollback_to_root(&mut self, root: Fp) -> io::Result<BlockHeader> {
+        let mut headers = vec![];
+        for (_, value) in self.store.scan_prefix(&[HEADER_PREFIX]) {
+            headers.push(decode(value)?);
+        }
+        let header = headers
+            .into_iter()
+            .rev()
+            .find(|header| header.state_root == root)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no block with this state root"))?;
+        self.rollback(header.number)?;
+        Ok(header)
+    }
+
+    /// Writes the leaves and paths of `touched` accounts to `batch`, and the values they
+    /// replace to `undo`.
+    fn write_accounts(&self, batch: &mut WriteBatch, accounts: &AccountTree, touched: &[u64], undo: &mut WriteBatch) {
+        let mut keys = BTreeMap::new();
+        for &index in touched {
+            let account = accounts.account(index);
+            let value = (account != Account::default()).then(|| account.to_bytes());
+            keys.insert(numbered_key(ACCOUNT_PREFIX, index), value);
+            for level in 0..=self.depth {
+                let node = accounts.tree().stored_node(level, index >> level);
+                keys.insert(node_key(level, index >> level), node.map(|node| node.to_bytes()));
+            }
+        }
+        for (key, value) in keys {
+            match self.store.get(&key) {
+                Some(old) => undo.put(key.clone(), old),
+                None => undo.delete(key.clone()),
+            }
+            match value {
+                Some(value) => batch.put(key, value),
+                None => batch.delete(key),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::kv_store::test_dir;
+
+    fn genesis() -> AccountTree {
+        let mut accounts = AccountTree::new(4);
+        accounts.insert(1, Account { pubkey: Fp::from(11), balance: 100, nonce: 0 });
+        accounts.insert(2, Account { pubkey: Fp::from(22), balance: 5, nonce: 0 });
+        accounts
+    }
+
+    #[test]
+    fn test_state_db_commit_and_rollback() {
+        let dir = test_dir("state_db");
+        let mut accounts = genesis();
+        let mut db = StateDb::open(&dir, &accounts).unwrap();
+        let genesis_header = db.latest().unwrap();
+        assert_eq!(genesis_header.state_root, accounts.root());
+
+        accounts.transfer(1, 2, 30, 0, 0).unwrap();
+        let first = genesis_header.next(1, accounts.root(), Fp::zero());
+        db.commit_block(&first, &accounts, &[1, 2]).unwrap();
+        // Account 3 is created, so rolling back deletes it
+        accounts.transfer(2, 3, 35, 0, 0).unwrap();
+        let second = first.next(2, accounts.root(), Fp::zero());
+        db.commit_block(&second, &accounts, &[2, 3]).unwrap();
+        assert!(db.commit_block(&second, &accounts, &[]).is_err());
+
+        // Reopening reads the state back without rehashing
+        drop(db);
+        let mut db = StateDb::open(&dir, &genesis()).unwrap();
+        assert_eq!(db.latest().unwrap(), second);
+        let stored = db.accounts().unwrap();
+        assert_eq!(stored.root(), accounts.root());
+        assert_eq!(stored.account(3).balance, 35);
+
+        assert_eq!(db.rollback_to_root(first.state_root).unwrap(), first);
+        assert_eq!(db.latest().unwrap(), first);
+        assert_eq!(db.header(2).unwrap(), None);
+        db.rollback(0).unwrap();
+        let stored = db.accounts().unwrap();
+        assert_eq!(stored.root(), genesis().root());
+        assert_eq!(stored.account(2).balance, 5);
+        assert_eq!(stored.account(3), Account::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}